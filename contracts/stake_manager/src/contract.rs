@@ -1,43 +1,78 @@
+use crate::execute_accept_rate::execute_accept_rate;
+use crate::execute_admin_transfer::{execute_accept_admin, execute_cancel_admin_transfer};
 use crate::execute_admin_transfer_funds::execute_admin_transfer_funds;
 use crate::execute_admin_unbond_all::execute_admin_unbond_all;
+use crate::execute_cancel_unstake::execute_cancel_unstake;
 use crate::execute_config_decimals::execute_config_decimals;
 use crate::execute_config_pool_stack_fee::execute_config_pool_stack_fee;
 use crate::execute_config_unbonding_seconds::execute_config_unbonding_seconds;
 use crate::execute_era_active::execute_era_active;
 use crate::execute_era_collect_withdraw::execute_era_collect_withdraw;
+use crate::execute_era_process::execute_era_process;
+use crate::execute_era_rebalance::execute_era_rebalance;
+use crate::execute_era_recover::execute_era_recover;
 use crate::execute_era_restake::execute_era_restake;
 use crate::execute_era_stake::execute_era_stake;
 use crate::execute_era_update::execute_era_update;
+use crate::execute_fee_vault::{execute_fund_fee_vault, execute_withdraw_fee_vault};
+use crate::execute_gov_vote::{execute_gov_vote, execute_open_gov_proposal};
+use crate::execute_gov_vote::{
+    execute_reclaim_gov_vote, execute_relay_gov_vote, execute_signal_gov_vote,
+};
 use crate::execute_icq_update_period::update_icq_update_period;
 use crate::execute_init_pool::execute_init_pool;
-use crate::execute_open_channel::execute_open_channel;
+use crate::execute_instant_unstake::execute_instant_unstake;
+use crate::execute_open_channel::{execute_open_channel, execute_reopen_channel};
 use crate::execute_pool_add_validator::execute_add_pool_validators;
 use crate::execute_pool_rm_validator::execute_rm_pool_validator;
+use crate::execute_pool_role::{execute_grant_role, execute_revoke_role};
 use crate::execute_pool_update_validator::execute_pool_update_validator;
 use crate::execute_redeem_token_for_share::execute_redeem_token_for_share;
+use crate::execute_referral::{execute_claim_referral_fee, execute_register_referral_code};
 use crate::execute_register_pool::{execute_register_pool, sudo_open_ack};
-use crate::execute_stake::execute_stake;
+use crate::execute_stake::{execute_stake, execute_stake_from_host};
 use crate::execute_stake_lsm::execute_stake_lsm;
+use crate::execute_timelock::{execute_cancel_operation, execute_config_timelock_delay};
+use crate::execute_timelock::{execute_execute_operation, execute_queue_operation};
 use crate::execute_unstake::execute_unstake;
-use crate::execute_withdraw::execute_withdraw;
+use crate::execute_unstake_ticket::execute_enable_unstake_ticket;
+use crate::execute_wind_down::{execute_wind_down_exit, execute_wind_down_snapshot};
+use crate::execute_withdraw::{execute_set_withdraw_receiver, execute_withdraw_for};
+use crate::execute_withdraw::{execute_withdraw, execute_withdraw_all, execute_withdraw_eras};
+use crate::execute_withdraw_to_neutron::{
+    execute_withdraw_to_neutron, execute_withdraw_to_neutron_expired,
+    execute_withdraw_to_neutron_received,
+};
 use crate::helper::{
     QUERY_REPLY_ID_RANGE_END, QUERY_REPLY_ID_RANGE_START, REPLY_ID_RANGE_END, REPLY_ID_RANGE_START,
 };
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::query::query_closed_channels;
 use crate::query::{
     interchain_account_id_from_creator, query_balance_by_addr, query_decimals,
     query_validator_by_addr,
 };
 use crate::query::{query_delegation_by_addr, query_era_rate};
+use crate::query::{query_era_rate_history, query_pool_unstakes, query_pools};
+use crate::query::{query_era_records, query_slash_records};
 use crate::query::{query_era_snapshot, query_total_stack_fee};
-use crate::query::{query_ids, query_user_unstake_index};
+use crate::query::{query_fee_vault, query_wind_down};
+use crate::query::{query_gov_proposal, query_gov_signal, query_gov_vote_mode};
+use crate::query::{query_ids, query_liquidity_buffer, query_user_unstake_index};
+use crate::query::{query_interchain_account_ids, query_stack_info};
 use crate::query::{
     query_interchain_address, query_interchain_address_contract, query_pool_info,
     query_user_unstake,
 };
-use crate::query::{query_stack_info, query_unbonding_seconds};
+use crate::query::{query_keeper_bounty, query_timelock_delay, query_timelock_operations};
+use crate::query::{query_neutron_withdraw, query_pending_admin};
+use crate::query::{query_platform_fee_recipients, query_unstake_ticket, query_withdraw_receiver};
+use crate::query::{query_rate_apr, query_rate_twap};
+use crate::query::{query_referral, query_referral_code, query_referral_pool};
+use crate::query::{query_role_members, query_roles};
+use crate::query::{query_unbonding_seconds, query_validator_weights};
 use crate::query_callback::write_reply_id_to_query_id;
-use crate::state::{Stack, STACK};
+use crate::state::{migrate_unstakes_of_user, Stack, STACK};
 use crate::tx_callback::{prepare_sudo_payload, sudo_error, sudo_response, sudo_timeout};
 use crate::{error_conversion::ContractError, query_callback::sudo_kv_query_result};
 use crate::{execute_config_pool::execute_config_pool, query::get_ica_registered_query};
@@ -87,6 +122,7 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    migrate_unstakes_of_user(deps.storage)?;
     Ok(Response::default())
 }
 
@@ -121,6 +157,7 @@ pub fn query(deps: Deps<NeutronQuery>, env: Env, msg: QueryMsg) -> NeutronResult
         QueryMsg::Validators { pool_addr } => {
             Ok(to_json_binary(&query_validator_by_addr(deps, pool_addr)?)?)
         }
+        QueryMsg::ValidatorWeights { pool_addr } => query_validator_weights(deps, pool_addr),
         QueryMsg::PoolInfo { pool_addr } => query_pool_info(deps, env, pool_addr),
         QueryMsg::StackInfo {} => query_stack_info(deps),
         QueryMsg::TotalStackFee { pool_addr } => query_total_stack_fee(deps, pool_addr),
@@ -147,6 +184,73 @@ pub fn query(deps: Deps<NeutronQuery>, env: Env, msg: QueryMsg) -> NeutronResult
         QueryMsg::InterchainAccountIdFromCreator { addr } => {
             interchain_account_id_from_creator(deps, addr)
         }
+        QueryMsg::Pools { start_after, limit } => query_pools(deps, start_after, limit),
+        QueryMsg::EraRateHistory {
+            pool_addr,
+            start_after,
+            limit,
+        } => query_era_rate_history(deps, pool_addr, start_after, limit),
+        QueryMsg::PoolUnstakes {
+            pool_addr,
+            status,
+            start_after,
+            limit,
+        } => query_pool_unstakes(deps, pool_addr, status, start_after, limit),
+        QueryMsg::InterchainAccountIds { start_after, limit } => {
+            query_interchain_account_ids(deps, start_after, limit)
+        }
+        QueryMsg::LiquidityBuffer { pool_addr } => query_liquidity_buffer(deps, pool_addr),
+        QueryMsg::ReferralCode { code } => query_referral_code(deps, code),
+        QueryMsg::Referral { pool_addr, code } => query_referral(deps, pool_addr, code),
+        QueryMsg::ReferralPool { pool_addr } => query_referral_pool(deps, pool_addr),
+        QueryMsg::GovVoteMode { pool_addr } => query_gov_vote_mode(deps, pool_addr),
+        QueryMsg::ClosedChannels { pool_addr } => query_closed_channels(deps, pool_addr),
+        QueryMsg::GovProposal {
+            pool_addr,
+            proposal_id,
+        } => query_gov_proposal(deps, pool_addr, proposal_id),
+        QueryMsg::GovSignal {
+            pool_addr,
+            proposal_id,
+            voter,
+        } => query_gov_signal(deps, pool_addr, proposal_id, voter),
+        QueryMsg::PlatformFeeRecipients { pool_addr } => {
+            query_platform_fee_recipients(deps, pool_addr)
+        }
+        QueryMsg::UnstakeTicket { pool_addr } => query_unstake_ticket(deps, pool_addr),
+        QueryMsg::WithdrawReceiver { pool_addr, user } => {
+            query_withdraw_receiver(deps, pool_addr, user)
+        }
+        QueryMsg::NeutronWithdraw { withdraw_id } => query_neutron_withdraw(deps, withdraw_id),
+        QueryMsg::PendingAdmin { pool_addr } => query_pending_admin(deps, pool_addr),
+        QueryMsg::Roles { pool_addr, account } => query_roles(deps, pool_addr, account),
+        QueryMsg::RoleMembers {
+            pool_addr,
+            role,
+            start_after,
+            limit,
+        } => query_role_members(deps, pool_addr, role, start_after, limit),
+        QueryMsg::SlashRecords {
+            pool_addr,
+            start_after,
+            limit,
+        } => query_slash_records(deps, pool_addr, start_after, limit),
+        QueryMsg::EraRecords {
+            pool_addr,
+            start_after,
+            limit,
+        } => query_era_records(deps, pool_addr, start_after, limit),
+        QueryMsg::RateApr { pool_addr, eras } => query_rate_apr(deps, pool_addr, eras),
+        QueryMsg::RateTwap { pool_addr, eras } => query_rate_twap(deps, pool_addr, eras),
+        QueryMsg::KeeperBounty { pool_addr } => query_keeper_bounty(deps, pool_addr),
+        QueryMsg::FeeVault { pool_addr } => query_fee_vault(deps, pool_addr),
+        QueryMsg::WindDown { pool_addr } => query_wind_down(deps, pool_addr),
+        QueryMsg::TimelockOperations {
+            pool_addr,
+            start_after,
+            limit,
+        } => query_timelock_operations(deps, pool_addr, start_after, limit),
+        QueryMsg::TimelockDelay { pool_addr } => query_timelock_delay(deps, pool_addr),
     }
 }
 
@@ -165,6 +269,34 @@ pub fn execute(
         ExecuteMsg::InitPool(params) => execute_init_pool(deps, env, info, *params),
         ExecuteMsg::ConfigPool(params) => execute_config_pool(deps, info, env, *params),
         ExecuteMsg::ConfigStack(params) => execute_config_stack(deps, info, *params),
+        ExecuteMsg::AcceptAdmin { pool_addr } => execute_accept_admin(deps, info, pool_addr),
+        ExecuteMsg::CancelAdminTransfer { pool_addr } => {
+            execute_cancel_admin_transfer(deps, info, pool_addr)
+        }
+        ExecuteMsg::GrantRole {
+            pool_addr,
+            role,
+            account,
+        } => execute_grant_role(deps, info, pool_addr, role, account),
+        ExecuteMsg::RevokeRole {
+            pool_addr,
+            role,
+            account,
+        } => execute_revoke_role(deps, info, pool_addr, role, account),
+        ExecuteMsg::QueueOperation {
+            pool_addr,
+            operation,
+        } => execute_queue_operation(deps, env, info, pool_addr, operation),
+        ExecuteMsg::CancelOperation { pool_addr, id } => {
+            execute_cancel_operation(deps, info, pool_addr, id)
+        }
+        ExecuteMsg::ExecuteOperation { pool_addr, id } => {
+            execute_execute_operation(deps, env, info, pool_addr, id)
+        }
+        ExecuteMsg::ConfigTimelockDelay {
+            pool_addr,
+            delay_seconds,
+        } => execute_config_timelock_delay(deps, info, pool_addr, delay_seconds),
         ExecuteMsg::ConfigPoolStackFee(params) => {
             execute_config_pool_stack_fee(deps, info, *params)
         }
@@ -179,20 +311,66 @@ pub fn execute(
         ExecuteMsg::OpenChannel {
             pool_addr,
             closed_channel_id,
-        } => execute_open_channel(deps, info, pool_addr, closed_channel_id),
+        } => execute_open_channel(deps, env, info, pool_addr, closed_channel_id),
+        ExecuteMsg::ReopenChannel { pool_addr } => {
+            execute_reopen_channel(deps, env, info, pool_addr)
+        }
         ExecuteMsg::RedeemTokenForShare { pool_addr, tokens } => {
             execute_redeem_token_for_share(deps, info, pool_addr, tokens)
         }
         ExecuteMsg::Stake {
             neutron_address,
             pool_addr,
-        } => execute_stake(deps, env, neutron_address, pool_addr, info),
+            referral,
+        } => execute_stake(deps, env, neutron_address, pool_addr, info, referral),
+        ExecuteMsg::StakeFromHost {
+            neutron_address,
+            pool_addr,
+            host_sender,
+        } => execute_stake_from_host(deps, env, info, neutron_address, pool_addr, host_sender),
         ExecuteMsg::Unstake { amount, pool_addr } => execute_unstake(deps, info, amount, pool_addr),
+        ExecuteMsg::InstantUnstake {
+            amount,
+            pool_addr,
+            receiver,
+        } => execute_instant_unstake(deps, info, amount, pool_addr, receiver),
         ExecuteMsg::Withdraw {
             pool_addr,
             receiver,
             unstake_index_list,
         } => execute_withdraw(deps, info, pool_addr, receiver, unstake_index_list),
+        ExecuteMsg::CancelUnstake {
+            pool_addr,
+            unstake_index,
+        } => execute_cancel_unstake(deps, info, pool_addr, unstake_index),
+        ExecuteMsg::WithdrawAll {
+            pool_addr,
+            receiver,
+        } => execute_withdraw_all(deps, info, pool_addr, receiver),
+        ExecuteMsg::WithdrawFor { pool_addr, user } => {
+            execute_withdraw_for(deps, info, pool_addr, user)
+        }
+        ExecuteMsg::SetWithdrawReceiver {
+            pool_addr,
+            receiver,
+        } => execute_set_withdraw_receiver(deps, info, pool_addr, receiver),
+        ExecuteMsg::WithdrawEras {
+            pool_addr,
+            receiver,
+            start_era,
+            end_era,
+        } => execute_withdraw_eras(deps, info, pool_addr, receiver, start_era, end_era),
+        ExecuteMsg::WithdrawToNeutron {
+            pool_addr,
+            receiver,
+            unstake_index_list,
+        } => execute_withdraw_to_neutron(deps, env, info, pool_addr, receiver, unstake_index_list),
+        ExecuteMsg::WithdrawToNeutronReceived { withdraw_id } => {
+            execute_withdraw_to_neutron_received(deps, info, withdraw_id)
+        }
+        ExecuteMsg::WithdrawToNeutronExpired { withdraw_id } => {
+            execute_withdraw_to_neutron_expired(deps, env, withdraw_id)
+        }
         ExecuteMsg::PoolRmValidator {
             pool_addr,
             validator_addr,
@@ -200,26 +378,93 @@ pub fn execute(
         ExecuteMsg::PoolAddValidator {
             pool_addr,
             validator_addr,
-        } => execute_add_pool_validators(deps, info, pool_addr, validator_addr),
+            weight,
+        } => execute_add_pool_validators(deps, info, pool_addr, validator_addr, weight),
         ExecuteMsg::PoolUpdateValidator {
             pool_addr,
             old_validator,
             new_validator,
-        } => execute_pool_update_validator(deps, info, pool_addr, old_validator, new_validator),
+            weight,
+        } => execute_pool_update_validator(
+            deps,
+            info,
+            pool_addr,
+            old_validator,
+            new_validator,
+            weight,
+        ),
         ExecuteMsg::PoolUpdateValidatorsIcq { pool_addr } => {
             execute_update_validators_icq(deps, env, info, pool_addr)
         }
         ExecuteMsg::EraUpdate { pool_addr } => execute_era_update(deps, env, info, pool_addr),
         ExecuteMsg::EraStake { pool_addr } => execute_era_stake(deps, env, info, pool_addr),
         ExecuteMsg::EraCollectWithdraw { pool_addr } => {
-            execute_era_collect_withdraw(deps, info, pool_addr)
+            execute_era_collect_withdraw(deps, env, info, pool_addr)
+        }
+        ExecuteMsg::EraRestake { pool_addr } => execute_era_restake(deps, env, info, pool_addr),
+        ExecuteMsg::EraRecover { pool_addr } => execute_era_recover(deps, env, pool_addr),
+        ExecuteMsg::EraActive { pool_addr } => execute_era_active(deps, env, pool_addr),
+        ExecuteMsg::AcceptRate {
+            pool_addr,
+            rate,
+            max_deviation,
+        } => execute_accept_rate(deps, info, pool_addr, rate, max_deviation),
+        ExecuteMsg::EraProcess { pool_addr } => execute_era_process(deps, env, info, pool_addr),
+        ExecuteMsg::FundFeeVault { pool_addr } => execute_fund_fee_vault(deps, info, pool_addr),
+        ExecuteMsg::WindDownSnapshot { pool_addr } => {
+            execute_wind_down_snapshot(deps, env, info, pool_addr)
         }
-        ExecuteMsg::EraRestake { pool_addr } => execute_era_restake(deps, info, pool_addr),
-        ExecuteMsg::EraActive { pool_addr } => execute_era_active(deps, pool_addr),
+        ExecuteMsg::WindDownExit {
+            pool_addr,
+            lsd_token_amount,
+            receiver,
+        } => execute_wind_down_exit(deps, info, pool_addr, lsd_token_amount, receiver),
+        ExecuteMsg::WithdrawFeeVault {
+            pool_addr,
+            receiver,
+            amount,
+        } => execute_withdraw_fee_vault(deps, info, pool_addr, receiver, amount),
+        ExecuteMsg::EnableUnstakeTicket {
+            pool_addr,
+            name,
+            symbol,
+        } => execute_enable_unstake_ticket(deps, env, info, pool_addr, name, symbol),
+        ExecuteMsg::EraRebalance { pool_addr } => execute_era_rebalance(deps, env, info, pool_addr),
         ExecuteMsg::StakeLsm {
             neutron_address,
             pool_addr,
-        } => execute_stake_lsm(deps, env, info, neutron_address, pool_addr),
+            referral,
+        } => execute_stake_lsm(deps, env, info, neutron_address, pool_addr, referral),
+        ExecuteMsg::RegisterReferralCode { code } => {
+            execute_register_referral_code(deps, info, code)
+        }
+        ExecuteMsg::ClaimReferralFee { pool_addr, code } => {
+            execute_claim_referral_fee(deps, info, pool_addr, code)
+        }
+        ExecuteMsg::GovVote {
+            pool_addr,
+            proposal_id,
+            options,
+        } => execute_gov_vote(deps, info, pool_addr, proposal_id, options),
+        ExecuteMsg::OpenGovProposal {
+            pool_addr,
+            proposal_id,
+            signal_end,
+        } => execute_open_gov_proposal(deps, env, info, pool_addr, proposal_id, signal_end),
+        ExecuteMsg::SignalGovVote {
+            pool_addr,
+            proposal_id,
+            option,
+            amount,
+        } => execute_signal_gov_vote(deps, env, info, pool_addr, proposal_id, option, amount),
+        ExecuteMsg::RelayGovVote {
+            pool_addr,
+            proposal_id,
+        } => execute_relay_gov_vote(deps, env, info, pool_addr, proposal_id),
+        ExecuteMsg::ReclaimGovVote {
+            pool_addr,
+            proposal_id,
+        } => execute_reclaim_gov_vote(deps, info, pool_addr, proposal_id),
         ExecuteMsg::UpdateIcqUpdatePeriod { pool_addr } => {
             update_icq_update_period(deps, info, pool_addr)
         }
@@ -20,6 +20,9 @@ pub enum ContractError {
     #[error("Rate change over limit")]
     RateChangeOverLimit {},
 
+    #[error("Rate is not the accepted one")]
+    RateNotAccepted {},
+
     #[error("Encode error: {0}")]
     EncodeError(String),
 
@@ -59,6 +62,54 @@ pub enum ContractError {
     #[error("Validators empty")]
     ValidatorsEmpty {},
 
+    #[error("Total validator weight is zero")]
+    TotalValidatorWeightZero {},
+
+    #[error("Era recover not expired")]
+    EraRecoverNotExpired {},
+
+    #[error("Era recover ICQ submission height")]
+    EraRecoverSubmissionHeight {},
+
+    #[error("Era recover has no ICQ amount from before the step")]
+    EraRecoverNoBeforeAmount {},
+
+    #[error("Era record: {0} not found")]
+    EraRecordNotFound(u64),
+
+    #[error("Rebalance not needed")]
+    RebalanceNotNeeded {},
+
+    #[error("Liquidity buffer not enough")]
+    LiquidityBufferNotEnough {},
+
+    #[error("Instant unstake pending")]
+    InstantUnstakePending {},
+
+    #[error("Commission exceeds limit")]
+    CommissionExceedLimit {},
+
+    #[error("Timelock required")]
+    TimelockRequired {},
+
+    #[error("Timelock not expired")]
+    TimelockNotExpired {},
+
+    #[error("No pending admin")]
+    NoPendingAdmin {},
+
+    #[error("IBC hooks sender not match")]
+    IbcHooksSenderNotMatch {},
+
+    #[error("Host channel id not set")]
+    HostChannelIdNotSet {},
+
+    #[error("Withdraw to neutron: {0} not expired")]
+    NeutronWithdrawNotExpired(u64),
+
+    #[error("Withdraw to neutron: {0} funds not match")]
+    NeutronWithdrawFundsNotMatch(u64),
+
     #[error("Old validator not exist")]
     OldValidatorNotExist {},
 
@@ -131,9 +182,6 @@ pub enum ContractError {
     #[error("Encode error: LSD token amount is zero")]
     EncodeErrLsdTokenAmountZero {},
 
-    #[error("Encode error: Unstake times limit reached")]
-    EncodeErrUnstakeTimesLimitReached {},
-
     #[error("Encode error: Zero withdraw amount")]
     EncodeErrZeroWithdrawAmount {},
 
@@ -175,6 +223,84 @@ pub enum ContractError {
 
     #[error("Period too small")]
     PeriodTooSmall {},
+
+    #[error("Era: {0} rate is zero")]
+    EraRateZero(u64),
+
+    #[error("Withdraw receiver not set")]
+    WithdrawReceiverNotSet {},
+
+    #[error("Fee recipients must be distinct, nonzero and sum to 10000 bps")]
+    InvalidFeeRecipients {},
+
+    #[error("Invalid referral code")]
+    InvalidReferralCode {},
+
+    #[error("Referral code taken")]
+    ReferralCodeTaken {},
+
+    #[error("Referral code not found")]
+    ReferralCodeNotFound {},
+
+    #[error("No referral fee to claim")]
+    NoReferralFee {},
+
+    #[error("Referral code owned by the staker")]
+    SelfReferral {},
+
+    #[error("Unstake index: {0} can't be canceled")]
+    UnstakeIndexNotCancelable(u64),
+
+    #[error("Unstake ticket code id not set")]
+    UnstakeTicketCodeIdNotSet {},
+
+    #[error("Unstake ticket already enabled")]
+    UnstakeTicketEnabled {},
+
+    #[error("Gov vote mode not match")]
+    GovVoteModeNotMatch {},
+
+    #[error("Gov vote options must be distinct, nonzero and sum to 1")]
+    InvalidGovVoteOptions {},
+
+    #[error("Gov proposal already opened")]
+    GovProposalExists {},
+
+    #[error("Gov signal period closed")]
+    GovSignalClosed {},
+
+    #[error("Gov signal period not ended")]
+    GovSignalNotEnded {},
+
+    #[error("Pool channel closed, call reopen_channel")]
+    PoolChannelClosed {},
+
+    #[error("Pool not degraded")]
+    PoolNotDegraded {},
+
+    #[error("Channel reopen pending")]
+    ChannelReopenPending {},
+
+    #[error("Fee vault insufficient")]
+    FeeVaultInsufficient {},
+
+    #[error("Wind down not started")]
+    WindDownNotStarted {},
+
+    #[error("Wind down still unbonding")]
+    WindDownUnbonding {},
+
+    #[error("Wind down balance submission height")]
+    WindDownSnapshotHeight {},
+
+    #[error("Wind down waits for the withdraw ica sweep")]
+    WindDownSweeping {},
+
+    #[error("Wind down waits for the pending withdraws")]
+    WindDownPendingWithdraw {},
+
+    #[error("Pool is winding down")]
+    PoolWindingDown {},
 }
 
 impl From<ContractError> for NeutronError {
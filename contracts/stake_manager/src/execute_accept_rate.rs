@@ -0,0 +1,46 @@
+use cosmwasm_std::{DepsMut, MessageInfo, Response, Uint128};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::state::EraStatus::EraRestakeEnded;
+use crate::state::{AcceptedRate, ACCEPTED_RATE_ERAS, POOLS};
+
+// lets era active of the current era through rate_change_limit, e.g. after a slash, as long as
+// it lands near the rate the admin expects, without lifting the limit for later eras
+pub fn execute_accept_rate(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    rate: Uint128,
+    max_deviation: Uint128,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+    if pool_info.status != EraRestakeEnded {
+        return Err(ContractError::StatusNotAllow {}.into());
+    }
+
+    if rate.is_zero() {
+        return Err(ContractError::EraRateZero(pool_info.era).into());
+    }
+
+    ACCEPTED_RATE_ERAS.save(
+        deps.storage,
+        pool_addr.clone(),
+        &AcceptedRate {
+            era: pool_info.era,
+            rate,
+            max_deviation,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_rate")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("era", pool_info.era.to_string())
+        .add_attribute("rate", rate)
+        .add_attribute("max_deviation", max_deviation))
+}
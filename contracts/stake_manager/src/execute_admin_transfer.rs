@@ -0,0 +1,83 @@
+use cosmwasm_std::{DepsMut, MessageInfo, Response};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::state::{PENDING_POOL_ADMINS, PENDING_STACK_ADMIN, POOLS, STACK};
+
+pub fn execute_accept_admin(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: Option<String>,
+) -> NeutronResult<Response<NeutronMsg>> {
+    match pool_addr {
+        Some(pool_addr) => {
+            let pending_admin = PENDING_POOL_ADMINS
+                .may_load(deps.storage, pool_addr.clone())?
+                .ok_or(ContractError::NoPendingAdmin {})?;
+            if info.sender != pending_admin {
+                return Err(ContractError::Unauthorized {}.into());
+            }
+
+            let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+            pool_info.admin = pending_admin;
+            POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
+            PENDING_POOL_ADMINS.remove(deps.storage, pool_addr.clone());
+
+            Ok(Response::new()
+                .add_attribute("action", "accept_pool_admin")
+                .add_attribute("pool", pool_addr)
+                .add_attribute("admin", info.sender))
+        }
+        None => {
+            let pending_admin = PENDING_STACK_ADMIN
+                .may_load(deps.storage)?
+                .ok_or(ContractError::NoPendingAdmin {})?;
+            if info.sender != pending_admin {
+                return Err(ContractError::Unauthorized {}.into());
+            }
+
+            let mut stack = STACK.load(deps.storage)?;
+            stack.admin = pending_admin;
+            STACK.save(deps.storage, &stack)?;
+            PENDING_STACK_ADMIN.remove(deps.storage);
+
+            Ok(Response::new()
+                .add_attribute("action", "accept_stack_admin")
+                .add_attribute("admin", info.sender))
+        }
+    }
+}
+
+pub fn execute_cancel_admin_transfer(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: Option<String>,
+) -> NeutronResult<Response<NeutronMsg>> {
+    match pool_addr {
+        Some(pool_addr) => {
+            let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+            pool_info.authorize(&info.sender)?;
+            if !PENDING_POOL_ADMINS.has(deps.storage, pool_addr.clone()) {
+                return Err(ContractError::NoPendingAdmin {}.into());
+            }
+            PENDING_POOL_ADMINS.remove(deps.storage, pool_addr.clone());
+
+            Ok(Response::new()
+                .add_attribute("action", "cancel_pool_admin_transfer")
+                .add_attribute("pool", pool_addr))
+        }
+        None => {
+            let stack = STACK.load(deps.storage)?;
+            stack.authorize(&info.sender)?;
+            if !PENDING_STACK_ADMIN.exists(deps.storage) {
+                return Err(ContractError::NoPendingAdmin {}.into());
+            }
+            PENDING_STACK_ADMIN.remove(deps.storage);
+
+            Ok(Response::new().add_attribute("action", "cancel_stack_admin_transfer"))
+        }
+    }
+}
@@ -2,7 +2,7 @@ use cosmwasm_std::{DepsMut, MessageInfo, Response, Uint128};
 
 use crate::error_conversion::ContractError;
 use crate::helper::{self, gen_msg_send, DEFAULT_TIMEOUT_SECONDS};
-use crate::state::{SudoPayload, TxType, INFO_OF_ICA_ID, POOLS};
+use crate::state::{load_timelock_delay, SudoPayload, TxPayload, INFO_OF_ICA_ID, POOLS};
 use crate::tx_callback::msg_with_sudo_callback;
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
@@ -10,6 +10,21 @@ use neutron_sdk::{
 };
 
 pub fn execute_admin_transfer_funds(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    receiver: String,
+    amount: Uint128,
+) -> NeutronResult<Response<NeutronMsg>> {
+    // fund movements have to be queued in the timelock when the pool has a delay
+    if load_timelock_delay(deps.storage, pool_addr.clone())? > 0 {
+        return Err(ContractError::TimelockRequired {}.into());
+    }
+
+    admin_transfer_funds(deps, info, pool_addr, receiver, amount)
+}
+
+pub fn admin_transfer_funds(
     mut deps: DepsMut<NeutronQuery>,
     info: MessageInfo,
     pool_addr: String,
@@ -49,9 +64,8 @@ pub fn execute_admin_transfer_funds(
         cosmos_msg,
         SudoPayload {
             port_id: pool_ica_info.ctrl_port_id,
-            message: format!("{}_{}", amount, receiver),
+            message: TxPayload::AdminTransfer { amount, receiver },
             pool_addr: pool_addr.clone(),
-            tx_type: TxType::AdminTransfer,
         },
     )?;
 
@@ -61,17 +75,25 @@ pub fn execute_admin_transfer_funds(
 pub fn sudo_admin_transfer_callback(
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::AdminTransfer { amount, receiver } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
     Ok(Response::new()
         .add_attribute("action", "admin_transfer_callback")
         .add_attribute("pool_addr", payload.pool_addr)
-        .add_attribute("transfer", payload.message))
+        .add_attribute("transfer", format!("{}_{}", amount, receiver)))
 }
 
 pub fn sudo_admin_transfer_failed_callback(
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::AdminTransfer { amount, receiver } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
     Ok(Response::new()
         .add_attribute("action", "admin_transfer_failed_callback")
         .add_attribute("pool_addr", payload.pool_addr)
-        .add_attribute("transfer", payload.message))
+        .add_attribute("transfer", format!("{}_{}", amount, receiver)))
 }
@@ -4,9 +4,10 @@ use cosmos_sdk_proto::prost::Message;
 use cosmwasm_std::{Binary, DepsMut, Env, MessageInfo, Response};
 
 use crate::error_conversion::ContractError;
+use crate::execute_wind_down::start_wind_down;
 use crate::helper::{self, DEFAULT_TIMEOUT_SECONDS};
 use crate::query::query_delegation_by_addr;
-use crate::state::{SudoPayload, TxType, INFO_OF_ICA_ID, POOLS};
+use crate::state::{SudoPayload, TxPayload, INFO_OF_ICA_ID, POOLS};
 use crate::tx_callback::msg_with_sudo_callback;
 use neutron_sdk::bindings::types::ProtobufAny;
 use neutron_sdk::{
@@ -72,8 +73,6 @@ pub fn execute_admin_unbond_all(
         validator_list.push(delegation.validator.clone());
     }
 
-    let msg_str = validator_list.join("_");
-
     pool_info.paused = true;
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
 
@@ -93,9 +92,10 @@ pub fn execute_admin_unbond_all(
         cosmos_msg,
         SudoPayload {
             port_id: pool_ica_info.ctrl_port_id,
-            message: msg_str,
+            message: TxPayload::AdminUnbondAll {
+                validators: validator_list,
+            },
             pool_addr: pool_addr.clone(),
-            tx_type: TxType::AdminUnbondAll,
         },
     )?;
 
@@ -103,19 +103,31 @@ pub fn execute_admin_unbond_all(
 }
 
 pub fn sudo_admin_unbond_all_callback(
+    deps: DepsMut,
+    env: Env,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::AdminUnbondAll { validators } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    start_wind_down(deps.storage, &env, payload.pool_addr.clone())?;
+
     Ok(Response::new()
         .add_attribute("action", "admin_unbond_all_callback")
         .add_attribute("pool_addr", payload.pool_addr)
-        .add_attribute("validators", payload.message))
+        .add_attribute("validators", validators.join("_")))
 }
 
 pub fn sudo_admin_unbond_all_failed_callback(
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::AdminUnbondAll { validators } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
     Ok(Response::new()
         .add_attribute("action", "admin_unbond_all_failed_callback")
         .add_attribute("pool_addr", payload.pool_addr)
-        .add_attribute("validators", payload.message))
+        .add_attribute("validators", validators.join("_")))
 }
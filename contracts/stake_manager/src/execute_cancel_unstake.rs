@@ -0,0 +1,165 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use cosmwasm_std::{to_json_binary, CosmosMsg, DepsMut, MessageInfo, Response, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::execute_withdraw::{remove_unstakes, unstake_owner};
+use crate::state::{WithdrawStatus, POOLS, SNAPSHOT_UNSTAKE_INDEXES, UNSTAKES_OF_INDEX};
+use crate::{error_conversion::ContractError, helper::CAL_BASE};
+
+pub fn execute_cancel_unstake(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    unstake_index: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    if pool_info.paused {
+        return Err(ContractError::PoolIsPaused {}.into());
+    }
+
+    let unstake_info = UNSTAKES_OF_INDEX.load(deps.storage, (pool_addr.clone(), unstake_index))?;
+    if unstake_owner(deps.storage, &deps.querier, &unstake_info)? != info.sender {
+        return Err(ContractError::UnstakeIndexUnstakerNotMatch(unstake_index).into());
+    }
+
+    // era_stake undelegates what the era snapshot took, so only later unstakes can be undone
+    let snapshot_unstake_index = SNAPSHOT_UNSTAKE_INDEXES
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    if unstake_info.era != pool_info.era
+        || unstake_info.index <= snapshot_unstake_index
+        || unstake_info.status != WithdrawStatus::Default
+    {
+        return Err(ContractError::UnstakeIndexNotCancelable(unstake_index).into());
+    }
+
+    // reverse exactly what unstake moved, not the amount left after its precision margin
+    let token_amount = unstake_info.unbond_amount;
+    pool_info.unbond = pool_info.unbond.sub(token_amount);
+    pool_info.active = pool_info.active.add(token_amount);
+
+    let lsd_token_amount = token_amount.mul(CAL_BASE).div(pool_info.rate);
+    if lsd_token_amount.is_zero() {
+        return Err(ContractError::EncodeErrLsdTokenAmountZero {}.into());
+    }
+    pool_info.total_lsd_token_amount = pool_info.total_lsd_token_amount.add(lsd_token_amount);
+
+    let mint_msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(
+            &(Cw20ExecuteMsg::Mint {
+                recipient: info.sender.to_string(),
+                amount: lsd_token_amount,
+            }),
+        )?,
+        funds: vec![],
+    };
+
+    let burn_msgs = remove_unstakes(
+        deps.storage,
+        pool_addr.clone(),
+        &info.sender,
+        &[unstake_index],
+    )?;
+    POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(mint_msg))
+        .add_messages(burn_msgs)
+        .add_attribute("action", "cancel_unstake")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("from", info.sender)
+        .add_attribute("unstake_index", unstake_index.to_string())
+        .add_attribute("token_amount", token_amount)
+        .add_attribute("lsd_token_amount", lsd_token_amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, Addr, Uint128};
+
+    use super::execute_cancel_unstake;
+    use crate::execute_stake::execute_stake;
+    use crate::execute_unstake::execute_unstake;
+    use crate::state::{PoolInfo, POOLS, UNSTAKES_OF_INDEX};
+    use crate::testing::{mock_neutron_dependencies, save_pool, POOL_ADDR};
+
+    #[test]
+    fn test_cancel_unstake_restores_pool() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                ibc_denom: "ibc/atom".to_string(),
+                lsd_token: Addr::unchecked("lsd_token"),
+                rate: Uint128::new(1_100_000),
+                era: 3,
+                ..PoolInfo::default()
+            },
+        );
+
+        execute_stake(
+            deps.as_mut(),
+            mock_env(),
+            "user".to_string(),
+            POOL_ADDR.to_string(),
+            mock_info("user", &coins(1000, "ibc/atom")),
+            None,
+        )
+        .unwrap();
+        let staked = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(staked.active, Uint128::new(1000));
+        assert_eq!(staked.total_lsd_token_amount, Uint128::new(909));
+
+        // two unstakes in one era add up in one claim, each keeps its own precision margin back
+        for lsd_token_amount in [500, 300] {
+            execute_unstake(
+                deps.as_mut(),
+                mock_info("user", &[]),
+                Uint128::new(lsd_token_amount),
+                POOL_ADDR.to_string(),
+            )
+            .unwrap();
+        }
+        let unstake_info = UNSTAKES_OF_INDEX
+            .load(deps.as_ref().storage, (POOL_ADDR.to_string(), 1))
+            .unwrap();
+        assert_eq!(unstake_info.amount, Uint128::new(870));
+        assert_eq!(unstake_info.unbond_amount, Uint128::new(880));
+        let unstaked = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(unstaked.active, Uint128::new(120));
+        assert_eq!(unstaked.unbond, Uint128::new(880));
+
+        assert!(execute_cancel_unstake(
+            deps.as_mut(),
+            mock_info("other", &[]),
+            POOL_ADDR.to_string(),
+            1
+        )
+        .is_err());
+        execute_cancel_unstake(
+            deps.as_mut(),
+            mock_info("user", &[]),
+            POOL_ADDR.to_string(),
+            1,
+        )
+        .unwrap();
+        let canceled = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(canceled.active, staked.active);
+        assert_eq!(canceled.unbond, staked.unbond);
+        assert_eq!(canceled.total_lsd_token_amount, Uint128::new(909));
+        assert!(!UNSTAKES_OF_INDEX.has(deps.as_ref().storage, (POOL_ADDR.to_string(), 1)));
+    }
+}
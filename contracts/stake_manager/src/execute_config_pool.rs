@@ -1,6 +1,11 @@
+use crate::helper::check_fee_recipients;
+use crate::state::{load_timelock_delay, PoolInfo, Role};
+use crate::state::{GOV_VOTE_MODES, REFERRAL_POOLS, WIND_DOWNS};
+use crate::state::{HOST_CHANNEL_ID_OF_IBC_DENOM, KEEPER_BOUNTIES, LIQUIDITY_BUFFERS};
+use crate::state::{PENDING_POOL_ADMINS, PLATFORM_FEE_RECIPIENTS, SLASHING_ACTIONS};
 use crate::{error_conversion::ContractError, msg::ConfigPoolParams, state::UNBONDING_SECONDS};
-use crate::{helper::MAX_ERA_SECONDS, helper::MIN_ERA_SECONDS, state::POOLS};
-use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response};
+use crate::{helper::CAL_BASE, helper::MAX_ERA_SECONDS, helper::MIN_ERA_SECONDS, state::POOLS};
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
@@ -12,18 +17,97 @@ pub fn execute_config_pool(
     info: MessageInfo,
     env: Env,
     param: ConfigPoolParams,
+) -> NeutronResult<Response<NeutronMsg>> {
+    // commission hikes have to be queued in the timelock when the pool has a delay
+    if load_timelock_delay(deps.storage, param.pool_addr.clone())? > 0
+        && raises_commission(deps.storage, &param)?
+    {
+        return Err(ContractError::TimelockRequired {}.into());
+    }
+
+    config_pool(deps, info, env, param)
+}
+
+pub fn raises_commission(store: &dyn Storage, param: &ConfigPoolParams) -> StdResult<bool> {
+    let pool_info = POOLS.load(store, param.pool_addr.clone())?;
+    let liquidity_buffer = LIQUIDITY_BUFFERS
+        .may_load(store, param.pool_addr.clone())?
+        .unwrap_or_default();
+
+    Ok(param
+        .unbond_commission
+        .is_some_and(|c| c > pool_info.unbond_commission)
+        || param
+            .platform_fee_commission
+            .is_some_and(|c| c > pool_info.platform_fee_commission)
+        || param
+            .instant_unstake_commission
+            .is_some_and(|c| c > liquidity_buffer.instant_unstake_commission))
+}
+
+// which of the pause, fee and other params the update sets
+fn config_pool_param_groups(param: &ConfigPoolParams) -> (bool, bool, bool) {
+    let set_pause = param.paused.is_some();
+    let set_fee = param.unbond_commission.is_some()
+        || param.platform_fee_commission.is_some()
+        || param.platform_fee_receiver.is_some()
+        || param.platform_fee_recipients.is_some()
+        || param.referral_fee_commission.is_some()
+        || param.instant_unstake_commission.is_some();
+    let set_other = param.minimal_stake.is_some()
+        || param.era_seconds.is_some()
+        || param.lsm_support.is_some()
+        || param.lsm_pending_limit.is_some()
+        || param.rate_change_limit.is_some()
+        || param.new_admin.is_some()
+        || param.liquidity_buffer_target.is_some()
+        || param.host_channel_id_of_ibc_denom.is_some()
+        || param.keeper_bounty.is_some()
+        || param.slashing_action.is_some()
+        || param.gov_vote_mode.is_some();
+
+    (set_pause, set_fee, set_other)
+}
+
+// the pauser and the fee manager can only set their own params, the rest needs the admin
+pub fn authorize_config_pool(
+    store: &dyn Storage,
+    pool_info: &PoolInfo,
+    param: &ConfigPoolParams,
+    sender: &Addr,
+) -> NeutronResult<()> {
+    let (set_pause, set_fee, set_other) = config_pool_param_groups(param);
+    if set_other || !(set_pause || set_fee) {
+        pool_info.authorize(sender)?;
+    }
+    if set_pause {
+        pool_info.authorize_role(store, param.pool_addr.clone(), Role::Pauser, sender)?;
+    }
+    if set_fee {
+        pool_info.authorize_role(store, param.pool_addr.clone(), Role::FeeManager, sender)?;
+    }
+
+    Ok(())
+}
+
+pub fn config_pool(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    env: Env,
+    param: ConfigPoolParams,
 ) -> NeutronResult<Response<NeutronMsg>> {
     let mut pool_info = POOLS.load(deps.storage, param.pool_addr.clone())?;
-    pool_info.authorize(&info.sender)?;
-    pool_info.require_era_ended()?;
-    pool_info.require_update_validator_ended()?;
+    authorize_config_pool(deps.storage, &pool_info, &param, &info.sender)?;
+    // an emergency pause can't wait for the era to end
+    let (set_pause, set_fee, set_other) = config_pool_param_groups(&param);
+    if !set_pause || set_fee || set_other {
+        pool_info.require_era_ended()?;
+        pool_info.require_update_validator_ended()?;
+    }
 
     if let Some(minimal_stake) = param.minimal_stake {
         pool_info.minimal_stake = minimal_stake;
     }
-    if let Some(unstake_times_limit) = param.unstake_times_limit {
-        pool_info.unstake_times_limit = unstake_times_limit;
-    }
     if let Some(unbond_commission) = param.unbond_commission {
         pool_info.unbond_commission = unbond_commission;
     }
@@ -59,7 +143,32 @@ pub fn execute_config_pool(
     if let Some(receiver) = param.platform_fee_receiver {
         pool_info.platform_fee_receiver = Addr::unchecked(receiver);
     }
+    if let Some(recipients) = param.platform_fee_recipients {
+        if recipients.is_empty() {
+            PLATFORM_FEE_RECIPIENTS.remove(deps.storage, param.pool_addr.clone());
+        } else {
+            for recipient in recipients.iter() {
+                deps.api.addr_validate(recipient.receiver.as_str())?;
+            }
+            check_fee_recipients(&recipients)?;
+            PLATFORM_FEE_RECIPIENTS.save(deps.storage, param.pool_addr.clone(), &recipients)?;
+        }
+    }
+    if let Some(commission) = param.referral_fee_commission {
+        if commission > CAL_BASE {
+            return Err(ContractError::CommissionExceedLimit {}.into());
+        }
+        let mut referral_pool = REFERRAL_POOLS
+            .may_load(deps.storage, param.pool_addr.clone())?
+            .unwrap_or_default();
+        referral_pool.fee_commission = commission;
+        REFERRAL_POOLS.save(deps.storage, param.pool_addr.clone(), &referral_pool)?;
+    }
     if let Some(paused) = param.paused {
+        // a pool that unbonded everything can't take stake again
+        if !paused && WIND_DOWNS.has(deps.storage, param.pool_addr.clone()) {
+            return Err(ContractError::PoolWindingDown {}.into());
+        }
         pool_info.paused = paused;
     }
     if let Some(lsm_support) = param.lsm_support {
@@ -71,8 +180,44 @@ pub fn execute_config_pool(
     if let Some(rate_change_limit) = param.rate_change_limit {
         pool_info.rate_change_limit = rate_change_limit;
     }
+    // takes effect once new_admin calls accept_admin
     if let Some(new_admin) = param.new_admin {
-        pool_info.admin = new_admin;
+        PENDING_POOL_ADMINS.save(deps.storage, param.pool_addr.clone(), &new_admin)?;
+    }
+    if param.liquidity_buffer_target.is_some() || param.instant_unstake_commission.is_some() {
+        let mut liquidity_buffer = LIQUIDITY_BUFFERS
+            .may_load(deps.storage, param.pool_addr.clone())?
+            .unwrap_or_default();
+        if let Some(target) = param.liquidity_buffer_target {
+            liquidity_buffer.target = target;
+        }
+        if let Some(commission) = param.instant_unstake_commission {
+            if commission > CAL_BASE {
+                return Err(ContractError::CommissionExceedLimit {}.into());
+            }
+            liquidity_buffer.instant_unstake_commission = commission;
+        }
+        LIQUIDITY_BUFFERS.save(deps.storage, param.pool_addr.clone(), &liquidity_buffer)?;
+    }
+
+    if let Some(host_channel_id) = param.host_channel_id_of_ibc_denom {
+        HOST_CHANNEL_ID_OF_IBC_DENOM.save(
+            deps.storage,
+            param.pool_addr.clone(),
+            &host_channel_id,
+        )?;
+    }
+
+    if let Some(keeper_bounty) = param.keeper_bounty {
+        KEEPER_BOUNTIES.save(deps.storage, param.pool_addr.clone(), &keeper_bounty)?;
+    }
+
+    if let Some(slashing_action) = param.slashing_action {
+        SLASHING_ACTIONS.save(deps.storage, param.pool_addr.clone(), &slashing_action)?;
+    }
+
+    if let Some(gov_vote_mode) = param.gov_vote_mode {
+        GOV_VOTE_MODES.save(deps.storage, param.pool_addr.clone(), &gov_vote_mode)?;
     }
 
     POOLS.save(deps.storage, param.pool_addr.clone(), &pool_info)?;
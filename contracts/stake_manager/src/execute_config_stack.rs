@@ -6,7 +6,7 @@ use neutron_sdk::{
 };
 
 use crate::msg::ConfigStackParams;
-use crate::state::STACK;
+use crate::state::{PENDING_STACK_ADMIN, STACK, UNSTAKE_TICKET_CODE_ID};
 
 pub fn execute_config_stack(
     deps: DepsMut<NeutronQuery>,
@@ -22,12 +22,16 @@ pub fn execute_config_stack(
     if let Some(stack_fee_commission) = param.stack_fee_commission {
         stack.stack_fee_commission = stack_fee_commission;
     }
+    // takes effect once new_admin calls accept_admin
     if let Some(new_admin) = param.new_admin {
-        stack.admin = new_admin;
+        PENDING_STACK_ADMIN.save(deps.storage, &new_admin)?;
     }
     if let Some(lsd_token_code_id) = param.lsd_token_code_id {
         stack.lsd_token_code_id = lsd_token_code_id;
     }
+    if let Some(unstake_ticket_code_id) = param.unstake_ticket_code_id {
+        UNSTAKE_TICKET_CODE_ID.save(deps.storage, &unstake_ticket_code_id)?;
+    }
     if let Some(add_entrusted_pool) = param.add_entrusted_pool {
         if !stack.entrusted_pools.contains(&add_entrusted_pool) {
             stack.entrusted_pools.push(add_entrusted_pool);
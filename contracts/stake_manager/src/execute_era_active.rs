@@ -1,18 +1,26 @@
 use core::ops::{Mul, Sub};
 use std::ops::{Add, Div};
+use std::str::FromStr;
 
-use cosmwasm_std::{to_json_binary, DepsMut, Response, Uint128, WasmMsg};
+use cosmwasm_std::{to_json_binary, Delegation, DepsMut, Env, Event, Response, Uint128, WasmMsg};
 pub use cw20::Cw20ExecuteMsg;
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
 };
 
+use crate::execute_referral::distribute_referral_fee;
+use crate::helper::{load_validator_weights, platform_fee_shares};
+use crate::query::query_validator_by_addr;
+use crate::state::{record_era_step, ERA_RECORDS};
 use crate::state::{
     EraStatus::{ActiveEnded, EraRestakeEnded},
     STACK,
 };
-use crate::{error_conversion::ContractError, state::POOLS};
+use crate::state::{PoolInfo, SlashRecord, SlashingAction, ACCEPTED_RATE_ERAS, SLASHING_ACTIONS};
+use crate::state::{QueryKind, ADDRESS_TO_REPLY_ID, REPLY_ID_TO_QUERY_ID};
+use crate::state::{SLASH_RECORDS, VALIDATOR_TOKEN_RATES, VALIDATOR_WEIGHTS};
+use crate::{error_conversion::ContractError, state::LIQUIDITY_BUFFERS, state::POOLS};
 use crate::{helper::get_update_pool_icq_msgs, state::ERA_RATE};
 use crate::{helper::CAL_BASE, query::query_delegation_by_addr};
 use crate::{
@@ -21,7 +29,8 @@ use crate::{
 };
 
 pub fn execute_era_active(
-    deps: DepsMut<NeutronQuery>,
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
     pool_addr: String,
 ) -> NeutronResult<Response<NeutronMsg>> {
     let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
@@ -43,18 +52,23 @@ pub fn execute_era_active(
     if delegations_resp.last_submitted_local_height <= pool_info.era_snapshot.last_step_height {
         return Err(ContractError::DelegationSubmissionHeight {}.into());
     }
-
     let mut total_amount = cosmwasm_std::Coin {
         denom: pool_info.remote_denom.clone(),
         amount: Uint128::zero(),
     };
-    for delegation in delegations_resp.delegations {
+    for delegation in delegations_resp.delegations.iter() {
         total_amount.amount = total_amount.amount.add(delegation.amount.amount);
     }
+    // the liquidity buffer is kept undelegated on the pool ica but still backs the lsd token
+    if let Some(liquidity_buffer) = LIQUIDITY_BUFFERS.may_load(deps.storage, pool_addr.clone())? {
+        total_amount.amount = total_amount.amount.add(liquidity_buffer.amount);
+    }
 
     // calculate protocol fee
-    let (platform_fee, stack_fee) = if total_amount.amount > pool_info.era_snapshot.active {
-        let reward = total_amount.amount.sub(pool_info.era_snapshot.active);
+    let reward = total_amount
+        .amount
+        .saturating_sub(pool_info.era_snapshot.active);
+    let (platform_fee, stack_fee) = if !reward.is_zero() {
         let platform_fee_raw = reward
             .mul(pool_info.platform_fee_commission)
             .div(pool_info.rate);
@@ -86,23 +100,19 @@ pub fn execute_era_active(
         CAL_BASE
     };
 
-    if !pool_info.rate_change_limit.is_zero() {
-        let rate_change = if pool_info.rate > new_rate {
-            pool_info
-                .rate
-                .sub(new_rate)
-                .mul(CAL_BASE)
-                .div(pool_info.rate)
-        } else {
-            new_rate
-                .sub(pool_info.rate)
-                .mul(CAL_BASE)
-                .div(pool_info.rate)
-        };
-
-        if rate_change > pool_info.rate_change_limit {
-            return Err(ContractError::RateChangeOverLimit {}.into());
+    // the admin accepted a rate for this era, e.g. after a slash, the new one has to be near it
+    let accepted_rate = ACCEPTED_RATE_ERAS
+        .may_load(deps.storage, pool_addr.clone())?
+        .filter(|accepted_rate| accepted_rate.era == pool_info.era);
+    let rate_accepted = accepted_rate.is_some();
+    if let Some(accepted_rate) = accepted_rate {
+        if rate_deviation(accepted_rate.rate, new_rate) > accepted_rate.max_deviation {
+            return Err(ContractError::RateNotAccepted {}.into());
         }
+    } else if !pool_info.rate_change_limit.is_zero()
+        && rate_deviation(pool_info.rate, new_rate) > pool_info.rate_change_limit
+    {
+        return Err(ContractError::RateChangeOverLimit {}.into());
     }
 
     // Solve first stake calculation accuracy
@@ -114,23 +124,53 @@ pub fn execute_era_active(
         new_active = pool_info.active;
     }
 
+    // only once the rate is through, so the slashes behind a rejected rate are seen by the retry
+    let slash_events = record_slashings(
+        deps.branch(),
+        &mut pool_info,
+        pool_addr.clone(),
+        &delegations_resp.delegations,
+    )?;
+
     pool_info.rate = new_rate;
     pool_info.status = ActiveEnded;
     pool_info.active = new_active;
 
-    let mut resp = Response::new().add_attribute("new_rate", pool_info.rate);
+    if rate_accepted {
+        ACCEPTED_RATE_ERAS.remove(deps.storage, pool_addr.clone());
+    }
+
+    let mut resp = match slash_events {
+        Some(slash_events) => Response::new().add_events(slash_events),
+        None => Response::new().add_attribute("slash_detection", "skipped"),
+    }
+    .add_attribute("new_rate", pool_info.rate);
     if !platform_fee.is_zero() {
-        let msg = WasmMsg::Execute {
-            contract_addr: pool_info.lsd_token.to_string(),
-            msg: to_json_binary(
-                &(Cw20ExecuteMsg::Mint {
-                    recipient: pool_info.platform_fee_receiver.to_string(),
-                    amount: platform_fee,
-                }),
-            )?,
-            funds: vec![],
-        };
-        resp = resp.add_message(msg);
+        // the referrers' share stays in this contract until they claim it
+        let referral_fee = distribute_referral_fee(deps.storage, pool_addr.clone(), platform_fee)?;
+        let mut fee_shares = platform_fee_shares(
+            deps.storage,
+            &pool_info,
+            pool_addr.clone(),
+            platform_fee.sub(referral_fee),
+        )?;
+        if !referral_fee.is_zero() {
+            fee_shares.push((env.contract.address.clone(), referral_fee));
+            resp = resp.add_attribute("referral_fee", referral_fee);
+        }
+        for (recipient, amount) in fee_shares {
+            let msg = WasmMsg::Execute {
+                contract_addr: pool_info.lsd_token.to_string(),
+                msg: to_json_binary(
+                    &(Cw20ExecuteMsg::Mint {
+                        recipient: recipient.to_string(),
+                        amount,
+                    }),
+                )?,
+                funds: vec![],
+            };
+            resp = resp.add_message(msg);
+        }
 
         pool_info.total_platform_fee = pool_info.total_platform_fee.add(platform_fee);
     }
@@ -155,6 +195,27 @@ pub fn execute_era_active(
         TOTAL_STACK_FEE.save(deps.storage, pool_addr.clone(), &total_stack_fee)?;
     }
 
+    let mut era_record = record_era_step(
+        deps.storage,
+        pool_addr.clone(),
+        pool_info.era,
+        "era_active",
+        &env,
+    )?;
+    era_record.bond = pool_info.era_snapshot.bond;
+    era_record.unbond = pool_info.era_snapshot.unbond;
+    era_record.active = pool_info.era_snapshot.active;
+    era_record.restake_amount = pool_info.era_snapshot.restake_amount;
+    era_record.reward = reward;
+    era_record.platform_fee = platform_fee;
+    era_record.stack_fee = stack_fee;
+    era_record.rate = pool_info.rate;
+    ERA_RECORDS.save(
+        deps.storage,
+        (pool_addr.clone(), pool_info.era),
+        &era_record,
+    )?;
+
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
     ERA_RATE.save(
         deps.storage,
@@ -176,3 +237,267 @@ pub fn execute_era_active(
         .add_attribute("era", pool_info.era.to_string())
         .add_attribute("rate", new_rate))
 }
+
+// how far rate is from base, in CAL_BASE
+fn rate_deviation(base: Uint128, rate: Uint128) -> Uint128 {
+    let diff = if base > rate {
+        base.sub(rate)
+    } else {
+        rate.sub(base)
+    };
+    diff.mul(CAL_BASE).div(base)
+}
+
+// a validator's tokens per share only drop when it is slashed, so compare them with the last era,
+// None when the pool has no validators icq to compare with
+fn record_slashings(
+    deps: DepsMut<NeutronQuery>,
+    pool_info: &mut PoolInfo,
+    pool_addr: String,
+    delegations: &[Delegation],
+) -> NeutronResult<Option<Vec<Event>>> {
+    let validators_query = ADDRESS_TO_REPLY_ID.may_load(
+        deps.storage,
+        (pool_addr.clone(), QueryKind::Validators.to_string()),
+    )?;
+    let registered = match validators_query {
+        Some(reply_id) => REPLY_ID_TO_QUERY_ID.has(deps.storage, reply_id),
+        None => false,
+    };
+    if !registered {
+        return Ok(None);
+    }
+    let validators_resp = query_validator_by_addr(deps.as_ref(), pool_addr.clone())?;
+
+    let mut slash_records = vec![];
+    for validator in validators_resp.validator.validators {
+        let val_token_amount = Uint128::from_str(&validator.tokens)?;
+        let val_share_amount = Uint128::from_str(&validator.delegator_shares)?
+            .div(Uint128::from(1_000_000_000_000_000_000u128));
+        if val_share_amount.is_zero() {
+            continue;
+        }
+        let token_rate = val_token_amount.mul(CAL_BASE).div(val_share_amount);
+
+        let key = (pool_addr.clone(), validator.operator_address.clone());
+        let last_token_rate = VALIDATOR_TOKEN_RATES.may_load(deps.storage, key.clone())?;
+        VALIDATOR_TOKEN_RATES.save(deps.storage, key, &token_rate)?;
+
+        let Some(token_rate_before) = last_token_rate else {
+            continue;
+        };
+        if token_rate >= token_rate_before || token_rate.is_zero() {
+            continue;
+        }
+
+        let delegated = delegations
+            .iter()
+            .find(|delegation| delegation.validator == validator.operator_address)
+            .map(|delegation| delegation.amount.amount)
+            .unwrap_or_default();
+        slash_records.push(SlashRecord {
+            era: pool_info.era,
+            validator: validator.operator_address,
+            token_rate_before,
+            token_rate_after: token_rate,
+            shortfall: delegated.multiply_ratio(token_rate_before.sub(token_rate), token_rate),
+        });
+    }
+    if slash_records.is_empty() {
+        return Ok(Some(vec![]));
+    }
+
+    match SLASHING_ACTIONS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default()
+    {
+        SlashingAction::RecordOnly => {}
+        SlashingAction::ZeroWeight => {
+            let weights = load_validator_weights(
+                deps.storage,
+                pool_addr.clone(),
+                &pool_info.validator_addrs,
+            )?;
+            let remaining_weight: u64 = weights
+                .iter()
+                .filter(|(validator, _)| !slash_records.iter().any(|r| &r.validator == validator))
+                .map(|(_, weight)| weight)
+                .sum();
+            // new stake has to go somewhere, so keep the weights when every validator was slashed
+            if remaining_weight > 0 {
+                for record in slash_records.iter() {
+                    VALIDATOR_WEIGHTS.save(
+                        deps.storage,
+                        (pool_addr.clone(), record.validator.clone()),
+                        &0,
+                    )?;
+                }
+            }
+        }
+        SlashingAction::PausePool => pool_info.paused = true,
+    }
+
+    SLASH_RECORDS.save(
+        deps.storage,
+        (pool_addr.clone(), pool_info.era),
+        &slash_records,
+    )?;
+
+    Ok(Some(
+        slash_records
+            .into_iter()
+            .map(|record| {
+                Event::new("slashing")
+                    .add_attribute("pool", pool_addr.clone())
+                    .add_attribute("era", record.era.to_string())
+                    .add_attribute("validator", record.validator)
+                    .add_attribute("shortfall", record.shortfall)
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{Addr, Uint128};
+
+    use super::{execute_era_active, record_slashings};
+    use crate::execute_accept_rate::execute_accept_rate;
+    use crate::state::EraStatus::{ActiveEnded, EraRestakeEnded};
+    use crate::state::{EraSnapshot, PoolInfo, POOLS, SLASH_RECORDS, VALIDATOR_TOKEN_RATES};
+    use crate::testing::{
+        delegations_kv, mock_neutron_dependencies, mock_neutron_querier, register_pool_queries,
+        save_pool, validators_kv, NeutronDeps, ADMIN, DELEGATIONS_QUERY_ID, POOL_ADDR,
+        VALIDATORS_QUERY_ID,
+    };
+
+    #[test]
+    fn test_slashings_recorded_with_accepted_rate() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                era: 5,
+                status: EraRestakeEnded,
+                rate: Uint128::new(1_000_000),
+                rate_change_limit: Uint128::new(10_000),
+                active: Uint128::new(10_000),
+                total_lsd_token_amount: Uint128::new(10_000),
+                era_snapshot: EraSnapshot {
+                    active: Uint128::new(10_000),
+                    last_step_height: 10,
+                    ..PoolInfo::default().era_snapshot
+                },
+                validator_addrs: vec!["val1".to_string()],
+                lsd_token: Addr::unchecked("lsd_token"),
+                remote_denom: "uatom".to_string(),
+                ..PoolInfo::default()
+            },
+        );
+        register_pool_queries(deps.as_mut().storage);
+        let key = (POOL_ADDR.to_string(), "val1".to_string());
+        VALIDATOR_TOKEN_RATES
+            .save(deps.as_mut().storage, key.clone(), &Uint128::new(1_000_000))
+            .unwrap();
+        // val1 lost a fifth of its tokens
+        deps.querier = mock_neutron_querier(vec![
+            (
+                DELEGATIONS_QUERY_ID,
+                11,
+                delegations_kv("uatom", &[("val1", 8_000, 10_000)]),
+            ),
+            (
+                VALIDATORS_QUERY_ID,
+                11,
+                validators_kv(&[("val1", 8_000, 10_000)]),
+            ),
+        ]);
+
+        // the rate is rejected and the slash is left for the retry
+        assert!(execute_era_active(deps.as_mut(), mock_env(), POOL_ADDR.to_string()).is_err());
+        assert!(SLASH_RECORDS
+            .may_load(deps.as_ref().storage, (POOL_ADDR.to_string(), 5))
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            VALIDATOR_TOKEN_RATES
+                .load(deps.as_ref().storage, key.clone())
+                .unwrap(),
+            Uint128::new(1_000_000)
+        );
+
+        // the accepted rate isn't a blank check, the new rate has to be near the expected one
+        let accept_rate = |deps: &mut NeutronDeps, rate: u128| {
+            execute_accept_rate(
+                deps.as_mut(),
+                mock_info(ADMIN, &[]),
+                POOL_ADDR.to_string(),
+                Uint128::new(rate),
+                Uint128::new(10_000),
+            )
+            .unwrap();
+        };
+        accept_rate(&mut deps, 900_000);
+        assert!(execute_era_active(deps.as_mut(), mock_env(), POOL_ADDR.to_string()).is_err());
+        assert!(SLASH_RECORDS
+            .may_load(deps.as_ref().storage, (POOL_ADDR.to_string(), 5))
+            .unwrap()
+            .is_none());
+
+        accept_rate(&mut deps, 805_000);
+        let resp = execute_era_active(deps.as_mut(), mock_env(), POOL_ADDR.to_string()).unwrap();
+        assert_eq!(resp.events[0].ty, "slashing");
+
+        let slash_records = SLASH_RECORDS
+            .load(deps.as_ref().storage, (POOL_ADDR.to_string(), 5))
+            .unwrap();
+        assert_eq!(slash_records.len(), 1);
+        assert_eq!(slash_records[0].validator, "val1");
+        assert_eq!(slash_records[0].token_rate_before, Uint128::new(1_000_000));
+        assert_eq!(slash_records[0].token_rate_after, Uint128::new(800_000));
+        assert_eq!(slash_records[0].shortfall, Uint128::new(2_000));
+        assert_eq!(
+            VALIDATOR_TOKEN_RATES
+                .load(deps.as_ref().storage, key)
+                .unwrap(),
+            Uint128::new(800_000)
+        );
+
+        let pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(pool_info.status, ActiveEnded);
+        assert_eq!(pool_info.rate, Uint128::new(800_000));
+        assert_eq!(pool_info.active, Uint128::new(8_000));
+    }
+
+    #[test]
+    fn test_slash_detection_skipped_without_validators_icq() {
+        let mut deps = mock_neutron_dependencies();
+        let mut pool_info = PoolInfo {
+            era: 5,
+            ..PoolInfo::default()
+        };
+
+        // nothing to compare with, the caller says so
+        let slash_events =
+            record_slashings(deps.as_mut(), &mut pool_info, POOL_ADDR.to_string(), &[]).unwrap();
+        assert!(slash_events.is_none());
+
+        // a registered query without a result is an error, not a skip
+        register_pool_queries(deps.as_mut().storage);
+        assert!(
+            record_slashings(deps.as_mut(), &mut pool_info, POOL_ADDR.to_string(), &[]).is_err()
+        );
+
+        deps.querier = mock_neutron_querier(vec![(
+            VALIDATORS_QUERY_ID,
+            11,
+            validators_kv(&[("val1", 8_000, 10_000)]),
+        )]);
+        let slash_events =
+            record_slashings(deps.as_mut(), &mut pool_info, POOL_ADDR.to_string(), &[]).unwrap();
+        assert_eq!(slash_events, Some(vec![]));
+    }
+}
@@ -1,8 +1,10 @@
-use crate::helper::{self, gen_msg_send, get_withdraw_ica_id};
+use crate::execute_era_recover::save_era_step_checkpoint;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::{gen_msg_send, get_withdraw_ica_id};
 use crate::query::query_balance_by_addr;
 use crate::state::EraStatus::{EraStakeEnded, WithdrawEnded, WithdrawStarted};
-use crate::state::{SudoPayload, TxType, INFO_OF_ICA_ID, POOLS};
-use crate::tx_callback::msg_with_sudo_callback;
+use crate::state::{record_era_step, SudoPayload, TxPayload, INFO_OF_ICA_ID, POOLS};
+use crate::tx_callback::msg_with_fee_refund;
 use crate::{error_conversion::ContractError, helper::DEFAULT_TIMEOUT_SECONDS};
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128};
 use neutron_sdk::{
@@ -12,6 +14,7 @@ use neutron_sdk::{
 
 pub fn execute_era_collect_withdraw(
     mut deps: DepsMut<NeutronQuery>,
+    env: Env,
     info: MessageInfo,
     pool_addr: String,
 ) -> NeutronResult<Response<NeutronMsg>> {
@@ -22,6 +25,13 @@ pub fn execute_era_collect_withdraw(
         return Err(ContractError::StatusNotAllow {}.into());
     }
     pool_info.status = WithdrawStarted;
+    record_era_step(
+        deps.storage,
+        pool_addr.clone(),
+        pool_info.era,
+        "era_collect_withdraw",
+        &env,
+    )?;
 
     let (_, withdraw_ica_info, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
 
@@ -56,7 +66,7 @@ pub fn execute_era_collect_withdraw(
         return Ok(Response::default());
     }
 
-    let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
     let cosmos_msg = NeutronMsg::submit_tx(
         withdraw_ica_info.ctrl_connection_id.clone(),
         get_withdraw_ica_id(pool_info.ica_id.clone()),
@@ -71,18 +81,23 @@ pub fn execute_era_collect_withdraw(
         ibc_fee.clone(),
     );
 
-    let submsg = msg_with_sudo_callback(
-        deps.branch(),
-        cosmos_msg,
-        SudoPayload {
-            port_id: withdraw_ica_info.ctrl_port_id,
-            message: "".to_string(),
-            pool_addr: pool_addr.clone(),
-            tx_type: TxType::EraCollectWithdraw,
-        },
-    )?;
+    let payload = SudoPayload {
+        port_id: withdraw_ica_info.ctrl_port_id,
+        message: TxPayload::EraCollectWithdraw {},
+        pool_addr: pool_addr.clone(),
+    };
+    let submsg = msg_with_fee_refund(deps.branch(), cosmos_msg, payload.clone(), &ibc_fee)?;
 
     pool_info.era_snapshot.restake_amount = withdraw_amount;
+    save_era_step_checkpoint(
+        deps.branch(),
+        &env,
+        &pool_info,
+        payload,
+        submsg.id,
+        Uint128::zero(),
+        withdraw_amount,
+    )?;
     POOLS.save(deps.storage, pool_addr, &pool_info)?;
 
     Ok(Response::default().add_submessage(submsg))
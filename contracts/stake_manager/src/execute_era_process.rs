@@ -0,0 +1,85 @@
+use std::ops::Sub;
+
+use cosmwasm_std::{coins, BankMsg, DepsMut, Env, MessageInfo, Response, Uint128};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::execute_era_active::execute_era_active;
+use crate::execute_era_collect_withdraw::execute_era_collect_withdraw;
+use crate::execute_era_restake::execute_era_restake;
+use crate::execute_era_stake::execute_era_stake;
+use crate::execute_era_update::execute_era_update;
+use crate::helper::FEE_DENOM;
+use crate::state::EraStatus::{ActiveEnded, EraRestakeEnded, EraStakeEnded};
+use crate::state::EraStatus::{EraUpdateEnded, WithdrawEnded};
+use crate::state::{FEE_VAULTS, KEEPER_BOUNTIES, POOLS};
+
+// runs whichever era step follows the pool's status, the caller still attaches the ibc fees
+// the step needs, and is paid the keeper bounty once the step is executed
+pub fn execute_era_process(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let keeper = info.sender.clone();
+
+    let (step, resp) = match pool_info.status {
+        ActiveEnded => (
+            "era_update",
+            execute_era_update(deps.branch(), env, info, pool_addr.clone())?,
+        ),
+        EraUpdateEnded => (
+            "era_stake",
+            execute_era_stake(deps.branch(), env, info, pool_addr.clone())?,
+        ),
+        EraStakeEnded => (
+            "era_collect_withdraw",
+            execute_era_collect_withdraw(deps.branch(), env, info, pool_addr.clone())?,
+        ),
+        WithdrawEnded => (
+            "era_restake",
+            execute_era_restake(deps.branch(), env, info, pool_addr.clone())?,
+        ),
+        EraRestakeEnded => (
+            "era_active",
+            execute_era_active(deps.branch(), env, pool_addr.clone())?,
+        ),
+        _ => return Err(ContractError::StatusNotAllow {}.into()),
+    };
+
+    // an empty vault doesn't block the era, the step just goes unpaid
+    let keeper_bounty = KEEPER_BOUNTIES
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    let vault_balance = FEE_VAULTS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    let paid_bounty = if !keeper_bounty.is_zero() && vault_balance >= keeper_bounty {
+        FEE_VAULTS.save(
+            deps.storage,
+            pool_addr.clone(),
+            &vault_balance.sub(keeper_bounty),
+        )?;
+        keeper_bounty
+    } else {
+        Uint128::zero()
+    };
+
+    let mut resp = resp
+        .add_attribute("era_step", step)
+        .add_attribute("keeper", keeper.to_string())
+        .add_attribute("keeper_bounty", paid_bounty);
+    if !paid_bounty.is_zero() {
+        resp = resp.add_message(BankMsg::Send {
+            to_address: keeper.to_string(),
+            amount: coins(paid_bounty.u128(), FEE_DENOM),
+        });
+    }
+
+    Ok(resp)
+}
@@ -0,0 +1,387 @@
+use cosmwasm_std::{
+    Delegation, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::{
+    gen_redelegate_txs, load_validator_weights, weighted_targets, DEFAULT_TIMEOUT_SECONDS,
+    MAX_ENTRIES_PER_VALIDATOR, REBALANCE_MSGS_LIMIT, STAKE_SPLIT_THRESHOLD,
+};
+use crate::query::query_delegation_by_addr;
+use crate::state::{
+    SudoPayload, TxPayload, ValidatorUpdateStatus, INFO_OF_ICA_ID, POOLS,
+    VALIDATORS_REDELEGATIONS_TIME,
+};
+use crate::tx_callback::msg_with_fee_refund;
+use crate::{error_conversion::ContractError, helper::delegated_amount};
+
+#[derive(Clone, Debug, PartialEq)]
+struct RedelegateInfo {
+    pub src_validator: String,
+    pub dst_validator: String,
+    pub amount: Uint128,
+}
+
+pub fn execute_era_rebalance(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    if pool_info.paused {
+        return Err(ContractError::PoolIsPaused {}.into());
+    }
+    pool_info.require_era_ended()?;
+    pool_info.require_update_validator_ended()?;
+
+    let delegations = query_delegation_by_addr(
+        deps.as_ref(),
+        pool_addr.clone(),
+        pool_info.sdk_greater_or_equal_v047,
+    )?;
+    if delegations.last_submitted_local_height <= pool_info.era_snapshot.last_step_height {
+        return Err(ContractError::DelegationSubmissionHeight {}.into());
+    }
+
+    let weights =
+        load_validator_weights(deps.storage, pool_addr.clone(), &pool_info.validator_addrs)?;
+    let redelegate_infos = allocate_redelegate_amount(
+        deps.branch(),
+        env.block.time.seconds(),
+        pool_addr.clone(),
+        &weights,
+        &delegations.delegations,
+        pool_info.unbonding_period * pool_info.era_seconds,
+    )?;
+    if redelegate_infos.is_empty() {
+        return Err(ContractError::RebalanceNotNeeded {}.into());
+    }
+
+    let msgs = redelegate_infos
+        .iter()
+        .map(|r| {
+            gen_redelegate_txs(
+                pool_addr.clone(),
+                r.src_validator.clone(),
+                r.dst_validator.clone(),
+                pool_info.remote_denom.clone(),
+                r.amount,
+            )
+        })
+        .collect();
+    let redelegations = redelegate_infos
+        .iter()
+        .map(|r| (r.src_validator.clone(), r.dst_validator.clone()))
+        .collect();
+
+    let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+    let cosmos_msg = NeutronMsg::submit_tx(
+        pool_ica_info.ctrl_connection_id,
+        pool_info.ica_id.clone(),
+        msgs,
+        "".to_string(),
+        DEFAULT_TIMEOUT_SECONDS,
+        ibc_fee.clone(),
+    );
+
+    let submsg = msg_with_fee_refund(
+        deps.branch(),
+        cosmos_msg,
+        SudoPayload {
+            port_id: pool_ica_info.ctrl_port_id,
+            message: TxPayload::EraRebalance { redelegations },
+            pool_addr: pool_addr.clone(),
+        },
+        &ibc_fee,
+    )?;
+
+    pool_info.validator_update_status = ValidatorUpdateStatus::Start;
+    POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
+
+    let mut resp = Response::default()
+        .add_submessage(submsg)
+        .add_attribute("action", "era_rebalance")
+        .add_attribute("pool", pool_addr);
+    for r in redelegate_infos {
+        resp = resp.add_attribute(
+            "redelegate",
+            format!("{}_{}_{}", r.src_validator, r.dst_validator, r.amount),
+        );
+    }
+
+    Ok(resp)
+}
+
+fn allocate_redelegate_amount(
+    deps: DepsMut<NeutronQuery>,
+    current_time: u64,
+    pool_addr: String,
+    weights: &[(String, u64)],
+    delegations: &[Delegation],
+    unbonding_period_seconds: u64,
+) -> NeutronResult<Vec<RedelegateInfo>> {
+    // clear timestamps of matured redelegations
+    let redelegations: Vec<((String, String), Vec<u64>)> = VALIDATORS_REDELEGATIONS_TIME
+        .sub_prefix(pool_addr.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    let mut active_redelegations = vec![];
+    for ((src_validator, dst_validator), mut timestamps) in redelegations {
+        timestamps.retain(|&t| current_time < t + unbonding_period_seconds);
+        let key = (
+            pool_addr.clone(),
+            src_validator.clone(),
+            dst_validator.clone(),
+        );
+        if timestamps.is_empty() {
+            VALIDATORS_REDELEGATIONS_TIME.remove(deps.storage, key);
+        } else {
+            VALIDATORS_REDELEGATIONS_TIME.save(deps.storage, key, &timestamps)?;
+            active_redelegations.push((src_validator, dst_validator, timestamps.len()));
+        }
+    }
+
+    let total_delegated: Uint128 = delegations.iter().map(|d| d.amount.amount).sum();
+    let targets = weighted_targets(weights, total_delegated)?;
+
+    // Validators over their target (or no longer in the pool) are the sources
+    let mut surpluses: Vec<(String, Uint128)> = delegations
+        .iter()
+        .map(|d| {
+            let target = targets
+                .iter()
+                .find(|(validator, _)| *validator == d.validator)
+                .map(|(_, target)| *target)
+                .unwrap_or_default();
+            (d.validator.clone(), d.amount.amount.saturating_sub(target))
+        })
+        .filter(|(_, surplus)| !surplus.is_zero())
+        .collect();
+    surpluses.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let mut deficits: Vec<(String, Uint128)> = targets
+        .iter()
+        .map(|(validator, target)| {
+            (
+                validator.clone(),
+                target.saturating_sub(delegated_amount(delegations, validator)),
+            )
+        })
+        .filter(|(_, deficit)| !deficit.is_zero())
+        .collect();
+    deficits.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let mut redelegate_infos = vec![];
+    for (src_validator, surplus) in surpluses.iter_mut() {
+        // transitive redelegations are not allowed by the staking module
+        if active_redelegations
+            .iter()
+            .any(|(_, dst, _)| dst == src_validator)
+        {
+            continue;
+        }
+
+        for (dst_validator, deficit) in deficits.iter_mut() {
+            if redelegate_infos.len() >= REBALANCE_MSGS_LIMIT {
+                return Ok(redelegate_infos);
+            }
+            let entries = active_redelegations
+                .iter()
+                .find(|(src, dst, _)| src == src_validator && dst == dst_validator)
+                .map(|(_, _, entries)| *entries)
+                .unwrap_or_default();
+            if entries >= MAX_ENTRIES_PER_VALIDATOR {
+                continue;
+            }
+
+            let amount = (*surplus).min(*deficit);
+            if amount < STAKE_SPLIT_THRESHOLD {
+                continue;
+            }
+
+            *surplus -= amount;
+            *deficit -= amount;
+            redelegate_infos.push(RedelegateInfo {
+                src_validator: src_validator.clone(),
+                dst_validator: dst_validator.clone(),
+                amount,
+            });
+        }
+    }
+
+    Ok(redelegate_infos)
+}
+
+pub fn sudo_era_rebalance_callback(
+    deps: DepsMut,
+    env: Env,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::EraRebalance { redelegations } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+
+    record_redelegations(
+        deps.storage,
+        payload.pool_addr.clone(),
+        env.block.time.seconds(),
+        redelegations,
+    )?;
+
+    pool_info.validator_update_status = ValidatorUpdateStatus::End;
+    pool_info.era_snapshot.last_step_height = env.block.height;
+    POOLS.save(deps.storage, payload.pool_addr.clone(), &pool_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "era_rebalance_callback")
+        .add_attribute("pool", payload.pool_addr))
+}
+
+// every acked redelegation counts toward the cooldowns rebalance checks, whichever step sent it
+pub fn record_redelegations(
+    store: &mut dyn Storage,
+    pool_addr: String,
+    timestamp: u64,
+    redelegations: Vec<(String, String)>,
+) -> StdResult<()> {
+    for (src_validator, dst_validator) in redelegations {
+        let key = (pool_addr.clone(), src_validator, dst_validator);
+        let mut timestamps = VALIDATORS_REDELEGATIONS_TIME
+            .may_load(store, key.clone())?
+            .unwrap_or_default();
+        timestamps.push(timestamp);
+        VALIDATORS_REDELEGATIONS_TIME.save(store, key, &timestamps)?;
+    }
+
+    Ok(())
+}
+
+pub fn sudo_era_rebalance_failed_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    pool_info.validator_update_status = ValidatorUpdateStatus::End;
+    POOLS.save(deps.storage, payload.pool_addr.clone(), &pool_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "era_rebalance_failed_callback")
+        .add_attribute("pool", payload.pool_addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{coin, Addr, Delegation, Timestamp, Uint128};
+
+    use super::{allocate_redelegate_amount, record_redelegations, RedelegateInfo};
+    use crate::execute_pool_rm_validator::sudo_rm_validator_callback;
+    use crate::helper::MAX_ENTRIES_PER_VALIDATOR;
+    use crate::state::{PoolInfo, SudoPayload, TxPayload, VALIDATORS_REDELEGATIONS_TIME};
+    use crate::testing::{mock_neutron_dependencies, save_pool, POOL_ADDR};
+
+    const UNBONDING_SECONDS: u64 = 21 * 86400;
+
+    fn redelegate_info(src_validator: &str, dst_validator: &str, amount: u128) -> RedelegateInfo {
+        RedelegateInfo {
+            src_validator: src_validator.to_string(),
+            dst_validator: dst_validator.to_string(),
+            amount: Uint128::new(amount),
+        }
+    }
+
+    #[test]
+    fn test_rebalance_redelegation_cooldowns() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                validator_addrs: vec!["val1".to_string(), "val4".to_string()],
+                ..PoolInfo::default()
+            },
+        );
+        let weights = vec![
+            ("val1".to_string(), 100),
+            ("val2".to_string(), 100),
+            ("val3".to_string(), 100),
+        ];
+        let delegations = vec![Delegation {
+            delegator: Addr::unchecked(POOL_ADDR),
+            validator: "val1".to_string(),
+            amount: coin(60_000, "uatom"),
+        }];
+        let allocate = |deps: &mut crate::testing::NeutronDeps, now: u64| {
+            allocate_redelegate_amount(
+                deps.as_mut(),
+                now,
+                POOL_ADDR.to_string(),
+                &weights,
+                &delegations,
+                UNBONDING_SECONDS,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            allocate(&mut deps, 1000),
+            vec![
+                redelegate_info("val1", "val2", 20_000),
+                redelegate_info("val1", "val3", 20_000),
+            ]
+        );
+
+        // removing val4 redelegated onto val1, which can't be a source again until it matures
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1000);
+        sudo_rm_validator_callback(
+            deps.as_mut().into_empty(),
+            env,
+            SudoPayload {
+                pool_addr: POOL_ADDR.to_string(),
+                port_id: "port".to_string(),
+                message: TxPayload::RmValidator {
+                    validator: "val4".to_string(),
+                    redelegations: vec![("val4".to_string(), "val1".to_string())],
+                },
+            },
+        )
+        .unwrap();
+        assert!(allocate(&mut deps, 2000).is_empty());
+
+        // matured entries are pruned
+        let matured = 1000 + UNBONDING_SECONDS;
+        assert_eq!(allocate(&mut deps, matured).len(), 2);
+        assert!(!VALIDATORS_REDELEGATIONS_TIME.has(
+            deps.as_ref().storage,
+            (
+                POOL_ADDR.to_string(),
+                "val4".to_string(),
+                "val1".to_string()
+            )
+        ));
+
+        // a pair at the entries limit is skipped
+        for _ in 0..MAX_ENTRIES_PER_VALIDATOR {
+            record_redelegations(
+                deps.as_mut().storage,
+                POOL_ADDR.to_string(),
+                matured,
+                vec![("val1".to_string(), "val2".to_string())],
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            allocate(&mut deps, matured + 1),
+            vec![redelegate_info("val1", "val3", 20_000)]
+        );
+    }
+}
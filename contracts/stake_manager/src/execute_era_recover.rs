@@ -0,0 +1,181 @@
+use std::ops::Add;
+
+use cosmwasm_std::{Deps, DepsMut, Env, Response, StdResult, Storage, Uint128};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::helper::{get_update_pool_icq_msgs, DEFAULT_FAST_PERIOD, DEFAULT_TIMEOUT_SECONDS};
+use crate::query::{query_balance_by_addr, query_delegation_by_addr};
+use crate::state::EraStatus::WithdrawStarted;
+use crate::state::EraStatus::{EraRestakeStarted, EraStakeStarted, EraUpdateStarted};
+use crate::state::{remove_sudo_payload, EraStepCheckpoint, PoolInfo, SudoPayload, TxType};
+use crate::state::{ERA_STEP_CHECKPOINTS, INFO_OF_ICA_ID, POOLS};
+use crate::tx_callback::{sudo_callback, sudo_failed_callback};
+
+// the amount an era step moves, from the latest icq result along with its submission height
+pub fn query_era_step_amount(
+    deps: Deps<NeutronQuery>,
+    pool_info: &PoolInfo,
+    pool_addr: String,
+    tx_type: TxType,
+) -> NeutronResult<(Uint128, u64)> {
+    let balance_addr = match tx_type {
+        TxType::EraUpdate => pool_addr,
+        TxType::EraCollectWithdraw => {
+            let (_, withdraw_ica_info, _) =
+                INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+            withdraw_ica_info.ica_addr
+        }
+        _ => {
+            let delegations_resp =
+                query_delegation_by_addr(deps, pool_addr, pool_info.sdk_greater_or_equal_v047)?;
+            let delegated = delegations_resp
+                .delegations
+                .iter()
+                .map(|delegation| delegation.amount.amount)
+                .sum();
+            return Ok((delegated, delegations_resp.last_submitted_local_height));
+        }
+    };
+
+    let balance_resp =
+        query_balance_by_addr(deps, balance_addr, pool_info.sdk_greater_or_equal_v047)?;
+    let balance = balance_resp
+        .balances
+        .coins
+        .iter()
+        .find(|c| c.denom == pool_info.remote_denom)
+        .map(|c| c.amount)
+        .unwrap_or_default();
+
+    Ok((balance, balance_resp.last_submitted_local_height))
+}
+
+// called by each era step when it sends its tx, so era_recover can tell later whether it landed
+pub fn save_era_step_checkpoint(
+    deps: DepsMut<NeutronQuery>,
+    env: &Env,
+    pool_info: &PoolInfo,
+    payload: SudoPayload,
+    reply_id: u64,
+    increase: Uint128,
+    decrease: Uint128,
+) -> NeutronResult<()> {
+    let (amount, submitted_height) = query_era_step_amount(
+        deps.as_ref(),
+        pool_info,
+        payload.pool_addr.clone(),
+        payload.message.tx_type(),
+    )?;
+    let before = (submitted_height > 0).then_some(amount);
+
+    ERA_STEP_CHECKPOINTS.save(
+        deps.storage,
+        payload.pool_addr.clone(),
+        &EraStepCheckpoint {
+            payload,
+            started_at: env.block.time.seconds(),
+            before,
+            expected: before.map(|before| before.add(increase).saturating_sub(decrease)),
+            recover_height: 0,
+            reply_id,
+            packet: None,
+        },
+    )?;
+
+    Ok(())
+}
+
+// called from the reply of an era step's tx with the packet its ack will come on
+pub fn save_era_step_packet(
+    store: &mut dyn Storage,
+    reply_id: u64,
+    payload: &SudoPayload,
+    channel_id: String,
+    seq_id: u64,
+) -> StdResult<()> {
+    let Some(mut checkpoint) = ERA_STEP_CHECKPOINTS.may_load(store, payload.pool_addr.clone())?
+    else {
+        return Ok(());
+    };
+    if checkpoint.reply_id != reply_id
+        || checkpoint.payload.message.tx_type() != payload.message.tx_type()
+    {
+        return Ok(());
+    }
+    checkpoint.packet = Some((channel_id, seq_id));
+    ERA_STEP_CHECKPOINTS.save(store, payload.pool_addr.clone(), &checkpoint)
+}
+
+// moves a pool out of a *Started status whose ack never arrived
+pub fn execute_era_recover(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    pool_addr: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    if !matches!(
+        pool_info.status,
+        EraUpdateStarted | EraStakeStarted | WithdrawStarted | EraRestakeStarted
+    ) {
+        return Err(ContractError::StatusNotAllow {}.into());
+    }
+
+    let mut checkpoint = ERA_STEP_CHECKPOINTS.load(deps.storage, pool_addr.clone())?;
+    if env.block.time.seconds() <= checkpoint.started_at.add(DEFAULT_TIMEOUT_SECONDS) {
+        return Err(ContractError::EraRecoverNotExpired {}.into());
+    }
+    let (Some(before), Some(expected)) = (checkpoint.before, checkpoint.expected) else {
+        return Err(ContractError::EraRecoverNoBeforeAmount {}.into());
+    };
+
+    // the tx has landed or timed out by now, ask for fresh icq results and decide on them next call
+    if checkpoint.recover_height == 0 {
+        checkpoint.recover_height = env.block.height;
+        ERA_STEP_CHECKPOINTS.save(deps.storage, pool_addr.clone(), &checkpoint)?;
+
+        let update_pool_icq_msgs = get_update_pool_icq_msgs(
+            deps.branch(),
+            pool_addr.clone(),
+            pool_info.ica_id.clone(),
+            DEFAULT_FAST_PERIOD,
+        )?;
+
+        return Ok(Response::new()
+            .add_messages(update_pool_icq_msgs)
+            .add_attribute("action", "era_recover")
+            .add_attribute("pool", pool_addr)
+            .add_attribute("recover_height", env.block.height.to_string()));
+    }
+
+    let tx_type = checkpoint.payload.message.tx_type();
+    let (amount, submitted_height) =
+        query_era_step_amount(deps.as_ref(), &pool_info, pool_addr.clone(), tx_type)?;
+    if submitted_height <= checkpoint.recover_height {
+        return Err(ContractError::EraRecoverSubmissionHeight {}.into());
+    }
+
+    // landed if the icq amount is closer to what the step expects than to where it started
+    let landed = amount.abs_diff(expected) < amount.abs_diff(before);
+
+    if let Some((channel_id, seq_id)) = checkpoint.packet.clone() {
+        remove_sudo_payload(deps.storage, channel_id, seq_id);
+    }
+    ERA_STEP_CHECKPOINTS.remove(deps.storage, pool_addr.clone());
+
+    let resp = if landed {
+        sudo_callback(deps.into_empty(), env, checkpoint.payload)?
+    } else {
+        sudo_failed_callback(deps.into_empty(), checkpoint.payload)?
+    };
+
+    Ok(resp
+        .add_attribute("action", "era_recover")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("amount", amount)
+        .add_attribute("expected", expected)
+        .add_attribute("landed", landed.to_string()))
+}
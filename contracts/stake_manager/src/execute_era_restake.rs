@@ -1,23 +1,24 @@
-use crate::state::{INFO_OF_ICA_ID, POOLS};
+use crate::execute_era_recover::save_era_step_checkpoint;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::{allocate_stake_amount, load_validator_weights};
+use crate::query::query_delegation_by_addr;
+use crate::state::EraStatus::{EraRestakeEnded, EraRestakeStarted, WithdrawEnded};
+use crate::state::{record_era_step, INFO_OF_ICA_ID, POOLS};
 use crate::{error_conversion::ContractError, helper::gen_delegation_txs};
-use crate::{
-    helper,
-    state::EraStatus::{EraRestakeEnded, EraRestakeStarted, WithdrawEnded},
-};
 use crate::{
     helper::DEFAULT_TIMEOUT_SECONDS,
-    state::{SudoPayload, TxType},
-    tx_callback::msg_with_sudo_callback,
+    state::{SudoPayload, TxPayload},
+    tx_callback::msg_with_fee_refund,
 };
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128};
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
 };
-use std::ops::{Div, Mul, Sub};
 
 pub fn execute_era_restake(
     mut deps: DepsMut<NeutronQuery>,
+    env: Env,
     info: MessageInfo,
     pool_addr: String,
 ) -> NeutronResult<Response<NeutronMsg>> {
@@ -28,6 +29,13 @@ pub fn execute_era_restake(
         return Err(ContractError::StatusNotAllow {}.into());
     }
     pool_info.status = EraRestakeStarted;
+    record_era_step(
+        deps.storage,
+        pool_addr.clone(),
+        pool_info.era,
+        "era_restake",
+        &env,
+    )?;
 
     let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
 
@@ -39,27 +47,26 @@ pub fn execute_era_restake(
         return Ok(Response::default());
     }
 
-    let validator_count = pool_info.validator_addrs.len() as u128;
-
-    let mut msgs = vec![];
-    if validator_count == 0 {
+    if pool_info.validator_addrs.is_empty() {
         return Err(ContractError::ValidatorsEmpty {}.into());
     }
 
-    let amount_per_validator = restake_amount.div(Uint128::from(validator_count));
-    let remainder = restake_amount.sub(amount_per_validator.mul(Uint128::new(validator_count)));
-
-    for (index, validator_addr) in pool_info.validator_addrs.iter().enumerate() {
-        let mut amount_for_this_validator = amount_per_validator;
-
-        // Add the remainder to the first validator
-        if index == 0 {
-            amount_for_this_validator += remainder;
-        }
+    let weights =
+        load_validator_weights(deps.storage, pool_addr.clone(), &pool_info.validator_addrs)?;
+    let delegations = query_delegation_by_addr(
+        deps.as_ref(),
+        pool_addr.clone(),
+        pool_info.sdk_greater_or_equal_v047,
+    )?
+    .delegations;
 
+    let mut msgs = vec![];
+    for (validator_addr, amount_for_this_validator) in
+        allocate_stake_amount(&weights, &delegations, restake_amount)?
+    {
         let any_msg = gen_delegation_txs(
             pool_addr.clone(),
-            validator_addr.clone(),
+            validator_addr,
             pool_info.remote_denom.clone(),
             amount_for_this_validator,
         );
@@ -67,28 +74,34 @@ pub fn execute_era_restake(
         msgs.push(any_msg);
     }
 
-    let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
     let cosmos_msg = NeutronMsg::submit_tx(
         pool_ica_info.ctrl_connection_id.clone(),
-        pool_info.ica_id,
+        pool_info.ica_id.clone(),
         msgs,
         "".to_string(),
         DEFAULT_TIMEOUT_SECONDS,
-        ibc_fee,
+        ibc_fee.clone(),
     );
 
-    let submsg = msg_with_sudo_callback(
+    let payload = SudoPayload {
+        port_id: pool_ica_info.ctrl_port_id,
+        message: TxPayload::EraRebond {},
+        pool_addr: pool_addr.clone(),
+    };
+    let submsg = msg_with_fee_refund(deps.branch(), cosmos_msg, payload.clone(), &ibc_fee)?;
+    save_era_step_checkpoint(
         deps.branch(),
-        cosmos_msg,
-        SudoPayload {
-            port_id: pool_ica_info.ctrl_port_id,
-            // the acknowledgement later
-            message: "".to_string(),
-            pool_addr,
-            tx_type: TxType::EraRebond,
-        },
+        &env,
+        &pool_info,
+        payload,
+        submsg.id,
+        restake_amount,
+        Uint128::zero(),
     )?;
 
+    POOLS.save(deps.storage, pool_addr, &pool_info)?;
+
     Ok(Response::default().add_submessage(submsg))
 }
 
@@ -7,13 +7,21 @@ use cosmwasm_std::{Binary, Delegation, DepsMut, Env, MessageInfo, Response, Uint
 use std::vec;
 use std::{
     collections::HashSet,
-    ops::{Div, Mul, Sub},
+    ops::{Add, Sub},
 };
 
-use crate::helper::{self, STAKE_SPLIT_THRESHOLD};
+use crate::execute_era_recover::save_era_step_checkpoint;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::{
+    allocate_stake_amount, load_validator_weights, most_underweight_validator, weighted_targets,
+    MAX_ENTRIES_PER_VALIDATOR, STAKE_SPLIT_THRESHOLD,
+};
 use crate::state::EraStatus::{EraStakeEnded, EraStakeStarted, EraUpdateEnded};
-use crate::state::{SudoPayload, TxType, INFO_OF_ICA_ID, POOLS, VALIDATORS_UNBONDS_TIME};
-use crate::tx_callback::msg_with_sudo_callback;
+use crate::state::{
+    record_era_step, SudoPayload, TxPayload, INFO_OF_ICA_ID, LIQUIDITY_BUFFERS, POOLS,
+    VALIDATORS_UNBONDS_TIME,
+};
+use crate::tx_callback::msg_with_fee_refund;
 use crate::{error_conversion::ContractError, helper::gen_delegation_txs};
 use crate::{helper::DEFAULT_TIMEOUT_SECONDS, query::query_delegation_by_addr};
 use neutron_sdk::bindings::types::ProtobufAny;
@@ -40,10 +48,18 @@ pub fn execute_era_stake(
     if pool_info.status != EraUpdateEnded {
         return Err(ContractError::StatusNotAllow {}.into());
     }
+    record_era_step(
+        deps.storage,
+        pool_addr.clone(),
+        pool_info.era,
+        "era_stake",
+        &env,
+    )?;
 
     let mut msgs = vec![];
 
-    let mut msg_str = "".to_string();
+    let mut unbond_validators = vec![];
+    let mut buffer_top_up = Uint128::zero();
     if pool_info.era_snapshot.unbond >= pool_info.era_snapshot.bond {
         let unbond_amount = pool_info
             .era_snapshot
@@ -66,9 +82,15 @@ pub fn execute_era_stake(
 
         let mut op_validators = vec![];
         if unbond_amount.u128() > 0 {
+            let weights = load_validator_weights(
+                deps.storage,
+                pool_addr.clone(),
+                &pool_info.validator_addrs,
+            )?;
             let unbond_infos = allocate_unbond_amount(
                 deps.branch(),
                 env.block.time.seconds(),
+                &weights,
                 &delegations.delegations,
                 unbond_amount,
                 pool_info.unbonding_period * pool_info.era_seconds,
@@ -76,9 +98,7 @@ pub fn execute_era_stake(
             if unbond_infos.is_empty() {
                 return Err(ContractError::ValidatorForUnbondNotEnough {}.into());
             }
-            let unbond_validators: Vec<String> =
-                unbond_infos.iter().map(|u| u.validator.clone()).collect();
-            msg_str = unbond_validators.join("_");
+            unbond_validators = unbond_infos.iter().map(|u| u.validator.clone()).collect();
 
             for info in unbond_infos {
                 op_validators.push(info.validator.clone());
@@ -144,15 +164,36 @@ pub fn execute_era_stake(
             }
         }
     } else {
-        let stake_amount = pool_info.era_snapshot.bond - pool_info.era_snapshot.unbond;
-        let validator_count = pool_info.validator_addrs.len() as u128;
-        if validator_count == 0 {
+        let mut stake_amount = pool_info.era_snapshot.bond - pool_info.era_snapshot.unbond;
+        if pool_info.validator_addrs.is_empty() {
             return Err(ContractError::ValidatorsEmpty {}.into());
         }
 
+        // keep part of the new bond undelegated until the liquidity buffer reaches its target
+        if let Some(liquidity_buffer) =
+            LIQUIDITY_BUFFERS.may_load(deps.storage, pool_addr.clone())?
+        {
+            buffer_top_up = liquidity_buffer
+                .target
+                .saturating_sub(liquidity_buffer.amount)
+                .min(stake_amount);
+            stake_amount = stake_amount.sub(buffer_top_up);
+        }
+
+        let weights =
+            load_validator_weights(deps.storage, pool_addr.clone(), &pool_info.validator_addrs)?;
+        let delegations = query_delegation_by_addr(
+            deps.as_ref(),
+            pool_addr.clone(),
+            pool_info.sdk_greater_or_equal_v047,
+        )?
+        .delegations;
+
         if stake_amount < STAKE_SPLIT_THRESHOLD {
-            for (index, validator_addr) in pool_info.validator_addrs.iter().enumerate() {
-                if index == 0 {
+            let target_validator =
+                most_underweight_validator(&weights, &delegations, stake_amount)?;
+            for validator_addr in pool_info.validator_addrs.iter() {
+                if *validator_addr == target_validator && !stake_amount.is_zero() {
                     msgs.push(gen_delegation_txs(
                         pool_addr.clone(),
                         validator_addr.clone(),
@@ -180,21 +221,12 @@ pub fn execute_era_stake(
                 }
             }
         } else {
-            let amount_per_validator = stake_amount.div(Uint128::from(validator_count));
-            let remainder =
-                stake_amount.sub(amount_per_validator.mul(Uint128::new(validator_count)));
-
-            for (index, validator_addr) in pool_info.validator_addrs.iter().enumerate() {
-                let mut amount_for_this_validator = amount_per_validator;
-
-                // Add the remainder to the first validator
-                if index == 0 {
-                    amount_for_this_validator += remainder;
-                }
-
+            for (validator_addr, amount_for_this_validator) in
+                allocate_stake_amount(&weights, &delegations, stake_amount)?
+            {
                 let any_msg = gen_delegation_txs(
                     pool_addr.clone(),
-                    validator_addr.clone(),
+                    validator_addr,
                     pool_info.remote_denom.clone(),
                     amount_for_this_validator,
                 );
@@ -213,26 +245,55 @@ pub fn execute_era_stake(
 
     let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
 
-    let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
     let cosmos_msg = NeutronMsg::submit_tx(
         pool_ica_info.ctrl_connection_id,
         pool_info.ica_id.clone(),
         msgs,
         "".to_string(),
         DEFAULT_TIMEOUT_SECONDS,
-        ibc_fee,
+        ibc_fee.clone(),
     );
 
-    let submsg = msg_with_sudo_callback(
-        deps.branch(),
-        cosmos_msg,
-        SudoPayload {
-            port_id: pool_ica_info.ctrl_port_id,
-            // the acknowledgement later
-            message: msg_str,
-            pool_addr: pool_addr.clone(),
-            tx_type: TxType::EraBond,
+    let payload = SudoPayload {
+        port_id: pool_ica_info.ctrl_port_id,
+        // the acknowledgement later
+        message: TxPayload::EraBond {
+            unbond_validators,
+            buffer_top_up,
         },
+        pool_addr: pool_addr.clone(),
+    };
+    let submsg = msg_with_fee_refund(deps.branch(), cosmos_msg, payload.clone(), &ibc_fee)?;
+
+    // delegations grow by the bond left after the buffer top up, or shrink by the unbond
+    let (delegate_amount, undelegate_amount) =
+        if pool_info.era_snapshot.unbond >= pool_info.era_snapshot.bond {
+            (
+                Uint128::zero(),
+                pool_info
+                    .era_snapshot
+                    .unbond
+                    .sub(pool_info.era_snapshot.bond),
+            )
+        } else {
+            (
+                pool_info
+                    .era_snapshot
+                    .bond
+                    .sub(pool_info.era_snapshot.unbond)
+                    .sub(buffer_top_up),
+                Uint128::zero(),
+            )
+        };
+    save_era_step_checkpoint(
+        deps.branch(),
+        &env,
+        &pool_info,
+        payload,
+        submsg.id,
+        delegate_amount,
+        undelegate_amount,
     )?;
 
     pool_info.status = EraStakeStarted;
@@ -244,21 +305,13 @@ pub fn execute_era_stake(
 fn allocate_unbond_amount(
     deps: DepsMut<NeutronQuery>,
     current_time: u64,
+    weights: &[(String, u64)],
     delegations: &[Delegation],
     unbond_amount: Uint128,
     unbonding_period_seconds: u64,
 ) -> NeutronResult<Vec<ValidatorUnbondInfo>> {
-    let mut unbond_infos: Vec<ValidatorUnbondInfo> = Vec::new();
-    let mut remaining_unbond = unbond_amount;
-
-    // Sort the delegations by amount in descending order
-    let mut sorted_delegations = delegations.to_vec();
-    sorted_delegations.sort_by(|a, b| b.amount.amount.cmp(&a.amount.amount));
-    for delegation in sorted_delegations.iter() {
-        if remaining_unbond.is_zero() {
-            break;
-        }
-
+    let mut available_delegations = vec![];
+    for delegation in delegations.iter() {
         // clear timestamps
         if let Some(mut timestamps) = VALIDATORS_UNBONDS_TIME.may_load(
             deps.storage,
@@ -278,15 +331,45 @@ fn allocate_unbond_amount(
                     &timestamps,
                 )?;
             }
-            if timestamps.len() >= 7 {
+            if timestamps.len() >= MAX_ENTRIES_PER_VALIDATOR {
                 continue;
             }
         }
+        available_delegations.push(delegation.clone());
+    }
 
-        let mut current_unbond = remaining_unbond;
-        // If the current validator delegate amount is less than the remaining delegate amount, all are discharged
-        if delegation.amount.amount < remaining_unbond {
-            current_unbond = delegation.amount.amount;
+    // Validators over their target (or no longer in the pool) are unbonded first
+    let total_delegated: Uint128 = delegations.iter().map(|d| d.amount.amount).sum();
+    let targets = weighted_targets(weights, total_delegated.saturating_sub(unbond_amount))?;
+    let mut excess_delegations: Vec<(Delegation, Uint128)> = available_delegations
+        .iter()
+        .map(|delegation| {
+            let target = targets
+                .iter()
+                .find(|(validator, _)| *validator == delegation.validator)
+                .map(|(_, target)| *target)
+                .unwrap_or_default();
+            (
+                delegation.clone(),
+                delegation.amount.amount.saturating_sub(target),
+            )
+        })
+        .collect();
+    excess_delegations.sort_by(|(a, a_excess), (b, b_excess)| {
+        b_excess
+            .cmp(a_excess)
+            .then(b.amount.amount.cmp(&a.amount.amount))
+    });
+
+    let mut unbond_infos: Vec<ValidatorUnbondInfo> = Vec::new();
+    let mut remaining_unbond = unbond_amount;
+    for (delegation, excess) in excess_delegations.iter() {
+        if remaining_unbond.is_zero() {
+            break;
+        }
+        let current_unbond = remaining_unbond.min(*excess);
+        if current_unbond.is_zero() {
+            continue;
         }
 
         remaining_unbond -= current_unbond;
@@ -296,6 +379,37 @@ fn allocate_unbond_amount(
         });
     }
 
+    // Then take the rest from the largest remaining delegations
+    let mut sorted_delegations = available_delegations;
+    sorted_delegations.sort_by(|a, b| b.amount.amount.cmp(&a.amount.amount));
+    for delegation in sorted_delegations.iter() {
+        if remaining_unbond.is_zero() {
+            break;
+        }
+
+        let unbond_info = unbond_infos
+            .iter()
+            .position(|u| u.validator == delegation.validator);
+        let already_unbond = unbond_info
+            .map(|index| unbond_infos[index].unbond_amount)
+            .unwrap_or_default();
+
+        // If the current validator delegate amount is less than the remaining delegate amount, all are discharged
+        let current_unbond = remaining_unbond.min(delegation.amount.amount - already_unbond);
+        if current_unbond.is_zero() {
+            continue;
+        }
+
+        remaining_unbond -= current_unbond;
+        match unbond_info {
+            Some(index) => unbond_infos[index].unbond_amount += current_unbond,
+            None => unbond_infos.push(ValidatorUnbondInfo {
+                validator: delegation.validator.clone(),
+                unbond_amount: current_unbond,
+            }),
+        }
+    }
+
     if !remaining_unbond.is_zero() {
         return Err(ContractError::ValidatorForUnbondNotEnough {}.into());
     }
@@ -308,9 +422,16 @@ pub fn sudo_era_bond_callback(
     env: Env,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::EraBond {
+        unbond_validators,
+        buffer_top_up,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
     let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
-    if payload.message.len() > 0 {
-        let unbond_validators: Vec<String> = payload.message.split("_").map(String::from).collect();
+    if !unbond_validators.is_empty() {
         let timestamp = env.block.time.seconds();
         for unbond_validator in unbond_validators {
             let timestamps_op = VALIDATORS_UNBONDS_TIME.may_load(
@@ -331,6 +452,14 @@ pub fn sudo_era_bond_callback(
         }
     }
 
+    if !buffer_top_up.is_zero() {
+        let mut liquidity_buffer = LIQUIDITY_BUFFERS
+            .may_load(deps.storage, payload.pool_addr.clone())?
+            .unwrap_or_default();
+        liquidity_buffer.amount = liquidity_buffer.amount.add(buffer_top_up);
+        LIQUIDITY_BUFFERS.save(deps.storage, payload.pool_addr.clone(), &liquidity_buffer)?;
+    }
+
     pool_info.status = EraStakeEnded;
     pool_info.era_snapshot.last_step_height = env.block.height;
     pool_info.bond -= pool_info.era_snapshot.bond;
@@ -6,16 +6,18 @@ use neutron_sdk::{
 };
 use std::ops::{Add, Div, Sub};
 
-use crate::helper::{self, get_update_pool_icq_msgs, DEFAULT_FAST_PERIOD, DEFAULT_TIMEOUT_SECONDS};
-use crate::state::EraSnapshot;
-use crate::state::{INFO_OF_ICA_ID, POOLS};
+use crate::execute_era_recover::save_era_step_checkpoint;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::{get_update_pool_icq_msgs, DEFAULT_FAST_PERIOD, DEFAULT_TIMEOUT_SECONDS};
+use crate::state::{record_era_step, EraSnapshot};
+use crate::state::{INFO_OF_ICA_ID, LIQUIDITY_BUFFERS, POOLS, SNAPSHOT_UNSTAKE_INDEXES};
 use crate::{
     error_conversion::ContractError,
     state::EraStatus::{ActiveEnded, EraUpdateEnded, EraUpdateStarted},
 };
 use crate::{
-    state::{SudoPayload, TxType},
-    tx_callback::msg_with_sudo_callback,
+    state::{SudoPayload, TxPayload},
+    tx_callback::msg_with_fee_refund,
 };
 
 pub fn execute_era_update(
@@ -30,6 +32,11 @@ pub fn execute_era_update(
     }
     pool_info.require_era_ended()?;
     pool_info.require_update_validator_ended()?;
+    if let Some(liquidity_buffer) = LIQUIDITY_BUFFERS.may_load(deps.storage, pool_addr.clone())? {
+        if !liquidity_buffer.pending.is_zero() {
+            return Err(ContractError::InstantUnstakePending {}.into());
+        }
+    }
 
     if pool_info.active.is_zero() && pool_info.bond.is_zero() && pool_info.unbond.is_zero() {
         return Err(ContractError::StatusNotAllow {}.into());
@@ -66,6 +73,18 @@ pub fn execute_era_update(
         last_step_height: env.block.height,
         restake_amount: Uint128::zero(),
     };
+    SNAPSHOT_UNSTAKE_INDEXES.save(
+        deps.storage,
+        pool_addr.clone(),
+        &pool_info.next_unstake_index,
+    )?;
+    record_era_step(
+        deps.storage,
+        pool_addr.clone(),
+        pool_info.era,
+        "era_update",
+        &env,
+    )?;
     let rsp = Response::default().add_messages(get_update_pool_icq_msgs(
         deps.branch(),
         pool_addr.clone(),
@@ -84,7 +103,7 @@ pub fn execute_era_update(
         pool_info.ibc_denom.clone(),
     );
     // See more info here: https://docs.neutron.org/neutron/feerefunder/overview
-    let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
     let msg: NeutronMsg = NeutronMsg::IbcTransfer {
         source_port: "transfer".to_string(),
         source_channel: pool_info.channel_id_of_ibc_denom.clone(),
@@ -102,15 +121,20 @@ pub fn execute_era_update(
 
     let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
 
-    let submsg_pool_ibc_send = msg_with_sudo_callback(
+    let payload = SudoPayload {
+        port_id: pool_ica_info.ctrl_port_id,
+        pool_addr: pool_addr.clone(),
+        message: TxPayload::EraUpdate {},
+    };
+    let submsg_pool_ibc_send = msg_with_fee_refund(deps.branch(), msg, payload.clone(), &ibc_fee)?;
+    save_era_step_checkpoint(
         deps.branch(),
-        msg,
-        SudoPayload {
-            port_id: pool_ica_info.ctrl_port_id,
-            pool_addr: pool_addr.clone(),
-            message: "".to_string(),
-            tx_type: TxType::EraUpdate,
-        },
+        &env,
+        &pool_info,
+        payload,
+        submsg_pool_ibc_send.id,
+        pool_info.era_snapshot.bond,
+        Uint128::zero(),
     )?;
 
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
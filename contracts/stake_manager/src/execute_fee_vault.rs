@@ -0,0 +1,138 @@
+use std::ops::{Add, Sub};
+
+use cosmwasm_std::{coins, Addr, BankMsg, Coin, DepsMut, MessageInfo, Response, StdResult};
+use cosmwasm_std::{Storage, Uint128};
+use neutron_sdk::bindings::msg::IbcFee;
+use neutron_sdk::query::min_ibc_fee::query_min_ibc_fee;
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::helper::{self, min_ntrn_ibc_fee, total_ibc_fee, FEE_DENOM};
+use crate::state::{PacketFee, FEE_VAULTS, PACKET_FEES, POOLS};
+
+// anyone can top up a pool's fee vault
+pub fn execute_fund_fee_vault(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    // make sure the pool exists
+    POOLS.load(deps.storage, pool_addr.clone())?;
+
+    if info.funds.len() != 1 || info.funds[0].denom != FEE_DENOM || info.funds[0].amount.is_zero() {
+        return Err(ContractError::ParamsErrorFundsNotMatch {}.into());
+    }
+    let amount = info.funds[0].amount;
+
+    let vault_balance = FEE_VAULTS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    FEE_VAULTS.save(deps.storage, pool_addr.clone(), &vault_balance.add(amount))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_fee_vault")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_withdraw_fee_vault(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    receiver: Addr,
+    amount: Uint128,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+
+    if amount.is_zero() {
+        return Err(ContractError::TokenAmountZero {}.into());
+    }
+    let vault_balance = FEE_VAULTS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    if vault_balance < amount {
+        return Err(ContractError::FeeVaultInsufficient {}.into());
+    }
+    FEE_VAULTS.save(deps.storage, pool_addr.clone(), &vault_balance.sub(amount))?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: receiver.to_string(),
+            amount: coins(amount.u128(), FEE_DENOM),
+        })
+        .add_attribute("action", "withdraw_fee_vault")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("receiver", receiver)
+        .add_attribute("amount", amount))
+}
+
+// the caller pays the ibc fee when it attaches funds, otherwise the pool's fee vault does
+pub fn take_ibc_fee(
+    deps: DepsMut<NeutronQuery>,
+    info: &MessageInfo,
+    pool_addr: String,
+) -> NeutronResult<IbcFee> {
+    if !info.funds.is_empty() {
+        return helper::check_ibc_fee(deps.as_ref(), info);
+    }
+
+    let ibc_fee = min_ntrn_ibc_fee(query_min_ibc_fee(deps.as_ref())?.min_fee);
+    let total_fee = total_ibc_fee(ibc_fee.clone());
+    let vault_balance = FEE_VAULTS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    if vault_balance < total_fee {
+        return Err(ContractError::FeeVaultInsufficient {}.into());
+    }
+    FEE_VAULTS.save(deps.storage, pool_addr, &vault_balance.sub(total_fee))?;
+
+    Ok(ibc_fee)
+}
+
+pub fn packet_fee(pool_addr: String, ibc_fee: &IbcFee) -> PacketFee {
+    let amount = |fee: &[Coin]| {
+        fee.iter()
+            .filter(|c| c.denom == FEE_DENOM)
+            .map(|c| c.amount)
+            .sum::<Uint128>()
+    };
+    let recv_fee = amount(&ibc_fee.recv_fee);
+    // the relayer gets the ack fee or the timeout fee, the rest comes back
+    PacketFee {
+        pool_addr,
+        refund_on_ack: recv_fee.add(amount(&ibc_fee.timeout_fee)),
+        refund_on_timeout: recv_fee.add(amount(&ibc_fee.ack_fee)),
+    }
+}
+
+// credits the refunded part of a packet's fee to its pool's fee vault
+pub fn credit_fee_refund(
+    store: &mut dyn Storage,
+    channel_id: String,
+    seq_id: u64,
+    timed_out: bool,
+) -> StdResult<()> {
+    let key = (channel_id, seq_id);
+    let Some(packet_fee) = PACKET_FEES.may_load(store, key.clone())? else {
+        return Ok(());
+    };
+    PACKET_FEES.remove(store, key);
+
+    let refund = if timed_out {
+        packet_fee.refund_on_timeout
+    } else {
+        packet_fee.refund_on_ack
+    };
+    if refund.is_zero() {
+        return Ok(());
+    }
+    let vault_balance = FEE_VAULTS
+        .may_load(store, packet_fee.pool_addr.clone())?
+        .unwrap_or_default();
+    FEE_VAULTS.save(store, packet_fee.pool_addr, &vault_balance.add(refund))
+}
@@ -0,0 +1,811 @@
+use std::collections::HashSet;
+use std::ops::{Add, Sub};
+
+use cosmos_sdk_proto::cosmos::gov::v1beta1::{MsgVote, MsgVoteWeighted, WeightedVoteOption};
+use cosmos_sdk_proto::prost::Message;
+use cosmwasm_std::{to_json_binary, Binary, Decimal, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery, types::ProtobufAny},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::DEFAULT_TIMEOUT_SECONDS;
+use crate::state::{
+    GovProposal, GovProposalStatus, GovSignal, GovVoteMode, GovVoteOption, SudoPayload, TxPayload,
+    WeightedGovVoteOption, GOV_PROPOSALS, GOV_SIGNALS, GOV_VOTE_MODES, INFO_OF_ICA_ID, POOLS,
+};
+use crate::tx_callback::msg_with_fee_refund;
+
+// the pool admin votes for the whole pool
+pub fn execute_gov_vote(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    proposal_id: u64,
+    options: Vec<WeightedGovVoteOption>,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+    if load_gov_vote_mode(&deps, pool_addr.clone())? != GovVoteMode::Admin {
+        return Err(ContractError::GovVoteModeNotMatch {}.into());
+    }
+    check_gov_vote_options(&options)?;
+
+    submit_gov_vote(deps, info, pool_addr, proposal_id, options, false)
+}
+
+// starts collecting lsd holders' signals for a host chain proposal
+pub fn execute_open_gov_proposal(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    proposal_id: u64,
+    signal_end: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+    if load_gov_vote_mode(&deps, pool_addr.clone())? != GovVoteMode::Aggregate {
+        return Err(ContractError::GovVoteModeNotMatch {}.into());
+    }
+    if signal_end <= env.block.time.seconds() {
+        return Err(ContractError::GovSignalClosed {}.into());
+    }
+    let key = (pool_addr.clone(), proposal_id);
+    if GOV_PROPOSALS.has(deps.storage, key.clone()) {
+        return Err(ContractError::GovProposalExists {}.into());
+    }
+    GOV_PROPOSALS.save(
+        deps.storage,
+        key,
+        &GovProposal {
+            signal_end,
+            status: GovProposalStatus::Signaling,
+            tally: vec![],
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "open_gov_proposal")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("signal_end", signal_end.to_string()))
+}
+
+// escrows amount more lsd token and moves the caller's whole signal to option
+pub fn execute_signal_gov_vote(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    proposal_id: u64,
+    option: GovVoteOption,
+    amount: Uint128,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let key = (pool_addr.clone(), proposal_id);
+    let mut proposal = GOV_PROPOSALS.load(deps.storage, key.clone())?;
+    if proposal.status != GovProposalStatus::Signaling
+        || env.block.time.seconds() >= proposal.signal_end
+    {
+        return Err(ContractError::GovSignalClosed {}.into());
+    }
+
+    let signal_key = (pool_addr.clone(), proposal_id, info.sender.clone());
+    let mut signal = match GOV_SIGNALS.may_load(deps.storage, signal_key.clone())? {
+        Some(signal) => {
+            update_tally(&mut proposal, signal.option, signal.amount, false);
+            signal
+        }
+        None => GovSignal {
+            option,
+            amount: Uint128::zero(),
+        },
+    };
+    signal.option = option;
+    signal.amount = signal.amount.add(amount);
+    if signal.amount.is_zero() {
+        return Err(ContractError::TokenAmountZero {}.into());
+    }
+    update_tally(&mut proposal, signal.option, signal.amount, true);
+    GOV_PROPOSALS.save(deps.storage, key, &proposal)?;
+    GOV_SIGNALS.save(deps.storage, signal_key, &signal)?;
+
+    let mut resp = Response::new();
+    if !amount.is_zero() {
+        // escrowed so the same lsd token can't signal twice after a transfer
+        resp = resp.add_message(WasmMsg::Execute {
+            contract_addr: pool_info.lsd_token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        });
+    }
+
+    Ok(resp
+        .add_attribute("action", "signal_gov_vote")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("option", format!("{:?}", signal.option))
+        .add_attribute("amount", signal.amount))
+}
+
+// anyone can send the tally to the host chain once signaling has ended
+pub fn execute_relay_gov_vote(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    proposal_id: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let key = (pool_addr.clone(), proposal_id);
+    let mut proposal = GOV_PROPOSALS.load(deps.storage, key.clone())?;
+    if proposal.status != GovProposalStatus::Signaling {
+        return Err(ContractError::StatusNotAllow {}.into());
+    }
+    if env.block.time.seconds() < proposal.signal_end {
+        return Err(ContractError::GovSignalNotEnded {}.into());
+    }
+    let options = tally_weights(&proposal.tally)?;
+
+    proposal.status = GovProposalStatus::Relaying;
+    GOV_PROPOSALS.save(deps.storage, key, &proposal)?;
+
+    submit_gov_vote(deps, info, pool_addr, proposal_id, options, true)
+}
+
+// returns the caller's escrowed lsd token, a signal taken back before the relay leaves the tally
+pub fn execute_reclaim_gov_vote(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    proposal_id: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let key = (pool_addr.clone(), proposal_id);
+    let mut proposal = GOV_PROPOSALS.load(deps.storage, key.clone())?;
+    let signal_key = (pool_addr.clone(), proposal_id, info.sender.clone());
+    let signal = GOV_SIGNALS.load(deps.storage, signal_key.clone())?;
+    match proposal.status {
+        GovProposalStatus::Relaying => return Err(ContractError::StatusNotAllow {}.into()),
+        GovProposalStatus::Signaling => {
+            update_tally(&mut proposal, signal.option, signal.amount, false);
+            GOV_PROPOSALS.save(deps.storage, key, &proposal)?;
+        }
+        GovProposalStatus::Relayed => {}
+    }
+    GOV_SIGNALS.remove(deps.storage, signal_key);
+
+    let msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount: signal.amount,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "reclaim_gov_vote")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("amount", signal.amount))
+}
+
+fn load_gov_vote_mode(
+    deps: &DepsMut<NeutronQuery>,
+    pool_addr: String,
+) -> NeutronResult<GovVoteMode> {
+    Ok(GOV_VOTE_MODES
+        .may_load(deps.storage, pool_addr)?
+        .unwrap_or_default())
+}
+
+// options must be distinct with nonzero weights summing to one
+pub fn check_gov_vote_options(options: &[WeightedGovVoteOption]) -> NeutronResult<()> {
+    let mut seen = HashSet::new();
+    let mut total_weight = Decimal::zero();
+    for option in options {
+        if option.weight.is_zero() || !seen.insert(option.option) {
+            return Err(ContractError::InvalidGovVoteOptions {}.into());
+        }
+        total_weight = total_weight.checked_add(option.weight)?;
+    }
+    if total_weight != Decimal::one() {
+        return Err(ContractError::InvalidGovVoteOptions {}.into());
+    }
+
+    Ok(())
+}
+
+fn update_tally(proposal: &mut GovProposal, option: GovVoteOption, amount: Uint128, add: bool) {
+    match proposal.tally.iter_mut().find(|(o, _)| *o == option) {
+        Some((_, total)) if add => *total = total.add(amount),
+        Some((_, total)) => *total = total.sub(amount),
+        None if add => proposal.tally.push((option, amount)),
+        None => {}
+    }
+    proposal.tally.retain(|(_, total)| !total.is_zero());
+}
+
+// each option weighs its share of the escrowed lsd token, the rounding remainder goes to the largest
+pub fn tally_weights(
+    tally: &[(GovVoteOption, Uint128)],
+) -> NeutronResult<Vec<WeightedGovVoteOption>> {
+    let total: Uint128 = tally.iter().map(|(_, amount)| amount).sum();
+    if total.is_zero() {
+        return Err(ContractError::TokenAmountZero {}.into());
+    }
+
+    let mut options: Vec<WeightedGovVoteOption> = GovVoteOption::ALL
+        .iter()
+        .filter_map(|option| {
+            tally
+                .iter()
+                .find(|(o, amount)| o == option && !amount.is_zero())
+                .map(|(_, amount)| WeightedGovVoteOption {
+                    option: *option,
+                    weight: Decimal::from_ratio(*amount, total),
+                })
+        })
+        .collect();
+    let total_weight: Decimal = options.iter().map(|o| o.weight).sum();
+    if let Some(largest) = options.iter_mut().max_by_key(|o| o.weight) {
+        largest.weight = largest.weight.add(Decimal::one().sub(total_weight));
+    }
+    options.retain(|o| !o.weight.is_zero());
+
+    Ok(options)
+}
+
+fn submit_gov_vote(
+    mut deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    proposal_id: u64,
+    options: Vec<WeightedGovVoteOption>,
+    aggregated: bool,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+    let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+
+    let vote_msg = gen_msg_vote(pool_addr.clone(), proposal_id, &options)?;
+    let cosmos_msg = NeutronMsg::submit_tx(
+        pool_ica_info.ctrl_connection_id,
+        pool_info.ica_id.clone(),
+        vec![vote_msg],
+        "".to_string(),
+        DEFAULT_TIMEOUT_SECONDS,
+        ibc_fee.clone(),
+    );
+
+    let submsg = msg_with_fee_refund(
+        deps.branch(),
+        cosmos_msg,
+        SudoPayload {
+            port_id: pool_ica_info.ctrl_port_id,
+            message: TxPayload::GovVote {
+                proposal_id,
+                options: options.clone(),
+                aggregated,
+            },
+            pool_addr: pool_addr.clone(),
+        },
+        &ibc_fee,
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(submsg)
+        .add_attribute("action", "gov_vote")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("options", format_options(&options)))
+}
+
+fn gen_msg_vote(
+    voter: String,
+    proposal_id: u64,
+    options: &[WeightedGovVoteOption],
+) -> NeutronResult<ProtobufAny> {
+    let mut buf = Vec::new();
+    let type_url = match options {
+        [option] => {
+            let msg = MsgVote {
+                proposal_id,
+                voter,
+                option: option.option.proto_value(),
+            };
+            buf.reserve(msg.encoded_len());
+            msg.encode(&mut buf)
+                .map_err(|e| ContractError::EncodeError(e.to_string()))?;
+            "/cosmos.gov.v1beta1.MsgVote"
+        }
+        _ => {
+            let msg = MsgVoteWeighted {
+                proposal_id,
+                voter,
+                options: options
+                    .iter()
+                    .map(|o| WeightedVoteOption {
+                        option: o.option.proto_value(),
+                        // sdk.Dec goes over the wire as its 18 decimals integer
+                        weight: o.weight.atomics().to_string(),
+                    })
+                    .collect(),
+            };
+            buf.reserve(msg.encoded_len());
+            msg.encode(&mut buf)
+                .map_err(|e| ContractError::EncodeError(e.to_string()))?;
+            "/cosmos.gov.v1beta1.MsgVoteWeighted"
+        }
+    };
+
+    Ok(ProtobufAny {
+        type_url: type_url.to_string(),
+        value: Binary::from(buf),
+    })
+}
+
+fn format_options(options: &[WeightedGovVoteOption]) -> String {
+    options
+        .iter()
+        .map(|o| format!("{:?}_{}", o.option, o.weight))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn sudo_gov_vote_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::GovVote {
+        proposal_id,
+        options,
+        aggregated,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    if aggregated {
+        let key = (payload.pool_addr.clone(), proposal_id);
+        let mut proposal = GOV_PROPOSALS.load(deps.storage, key.clone())?;
+        proposal.status = GovProposalStatus::Relayed;
+        GOV_PROPOSALS.save(deps.storage, key, &proposal)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "gov_vote_callback")
+        .add_attribute("pool_addr", payload.pool_addr)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("options", format_options(&options)))
+}
+
+pub fn sudo_gov_vote_failed_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::GovVote {
+        proposal_id,
+        options,
+        aggregated,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    // the tally can be relayed again
+    if aggregated {
+        let key = (payload.pool_addr.clone(), proposal_id);
+        let mut proposal = GOV_PROPOSALS.load(deps.storage, key.clone())?;
+        proposal.status = GovProposalStatus::Signaling;
+        GOV_PROPOSALS.save(deps.storage, key, &proposal)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "gov_vote_failed_callback")
+        .add_attribute("pool_addr", payload.pool_addr)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("options", format_options(&options)))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmos_sdk_proto::cosmos::gov::v1beta1::{MsgVote, MsgVoteWeighted};
+    use cosmos_sdk_proto::prost::Message;
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, from_json, Addr, CosmosMsg, Env, SubMsg, Uint128, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+    use neutron_sdk::bindings::msg::NeutronMsg;
+
+    use super::{
+        execute_gov_vote, execute_open_gov_proposal, execute_reclaim_gov_vote,
+        execute_relay_gov_vote, execute_signal_gov_vote,
+    };
+    use crate::helper::FEE_DENOM;
+    use crate::state::{
+        GovProposal, GovProposalStatus, GovVoteMode, GovVoteOption, PoolInfo,
+        WeightedGovVoteOption, FEE_VAULTS, GOV_PROPOSALS, GOV_SIGNALS, GOV_VOTE_MODES,
+    };
+    use crate::testing::{
+        mock_neutron_dependencies, relay_packet, save_pool, NeutronDeps, ACK_FEE, ADMIN, POOL_ADDR,
+        POOL_CHANNEL_ID, TIMEOUT_FEE,
+    };
+    use crate::tx_callback::{sudo_error, sudo_response};
+
+    const PROPOSAL_ID: u64 = 42;
+
+    fn pool_with_mode(mode: GovVoteMode) -> NeutronDeps {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                lsd_token: Addr::unchecked("lsd_token"),
+                ..PoolInfo::default()
+            },
+        );
+        GOV_VOTE_MODES
+            .save(deps.as_mut().storage, POOL_ADDR.to_string(), &mode)
+            .unwrap();
+        deps
+    }
+
+    fn fee() -> Vec<cosmwasm_std::Coin> {
+        coins(ACK_FEE + TIMEOUT_FEE, FEE_DENOM)
+    }
+
+    fn weighted(options: &[(GovVoteOption, &str)]) -> Vec<WeightedGovVoteOption> {
+        options
+            .iter()
+            .map(|(option, weight)| WeightedGovVoteOption {
+                option: *option,
+                weight: weight.parse().unwrap(),
+            })
+            .collect()
+    }
+
+    // the single host chain msg of an ica tx
+    fn vote_msg(submsg: &SubMsg<NeutronMsg>) -> (String, Vec<u8>) {
+        let CosmosMsg::Custom(NeutronMsg::SubmitTx { msgs, .. }) = &submsg.msg else {
+            panic!("not an ica tx: {:?}", submsg.msg);
+        };
+        assert_eq!(msgs.len(), 1);
+        (msgs[0].type_url.clone(), msgs[0].value.to_vec())
+    }
+
+    fn lsd_token_msg(msg: &CosmosMsg<NeutronMsg>) -> Cw20ExecuteMsg {
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = msg else {
+            panic!("not a lsd token msg: {:?}", msg);
+        };
+        from_json(msg).unwrap()
+    }
+
+    fn proposal(deps: &NeutronDeps) -> GovProposal {
+        GOV_PROPOSALS
+            .load(deps.as_ref().storage, (POOL_ADDR.to_string(), PROPOSAL_ID))
+            .unwrap()
+    }
+
+    fn signal(
+        deps: &mut NeutronDeps,
+        env: &Env,
+        voter: &str,
+        option: GovVoteOption,
+        amount: u128,
+    ) -> Vec<CosmosMsg<NeutronMsg>> {
+        execute_signal_gov_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(voter, &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            option,
+            Uint128::new(amount),
+        )
+        .map(|resp| resp.messages.into_iter().map(|m| m.msg).collect())
+        .unwrap()
+    }
+
+    #[test]
+    fn test_admin_gov_vote() {
+        let mut deps = pool_with_mode(GovVoteMode::Admin);
+        let yes = weighted(&[(GovVoteOption::Yes, "1")]);
+
+        assert!(execute_gov_vote(
+            deps.as_mut(),
+            mock_info("user", &fee()),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            yes.clone(),
+        )
+        .is_err());
+        for options in [
+            weighted(&[(GovVoteOption::Yes, "0.5"), (GovVoteOption::No, "0.4")]),
+            weighted(&[(GovVoteOption::Yes, "0.5"), (GovVoteOption::Yes, "0.5")]),
+            weighted(&[(GovVoteOption::Yes, "1"), (GovVoteOption::No, "0")]),
+        ] {
+            assert!(execute_gov_vote(
+                deps.as_mut(),
+                mock_info(ADMIN, &fee()),
+                POOL_ADDR.to_string(),
+                PROPOSAL_ID,
+                options,
+            )
+            .is_err());
+        }
+        assert!(execute_open_gov_proposal(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN, &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            mock_env().block.time.seconds() + 100,
+        )
+        .is_err());
+
+        // a single option goes out as MsgVote
+        let resp = execute_gov_vote(
+            deps.as_mut(),
+            mock_info(ADMIN, &fee()),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            yes,
+        )
+        .unwrap();
+        let (type_url, value) = vote_msg(&resp.messages[0]);
+        assert_eq!(type_url, "/cosmos.gov.v1beta1.MsgVote");
+        let vote = MsgVote::decode(value.as_slice()).unwrap();
+        assert_eq!(vote.proposal_id, PROPOSAL_ID);
+        assert_eq!(vote.voter, POOL_ADDR);
+        assert_eq!(vote.option, 1);
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            1,
+        );
+        sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+
+        // the rest as MsgVoteWeighted, weights as sdk decs
+        let resp = execute_gov_vote(
+            deps.as_mut(),
+            mock_info(ADMIN, &fee()),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            weighted(&[(GovVoteOption::Yes, "0.7"), (GovVoteOption::Abstain, "0.3")]),
+        )
+        .unwrap();
+        let (type_url, value) = vote_msg(&resp.messages[0]);
+        assert_eq!(type_url, "/cosmos.gov.v1beta1.MsgVoteWeighted");
+        let vote = MsgVoteWeighted::decode(value.as_slice()).unwrap();
+        let options: Vec<(i32, String)> = vote
+            .options
+            .into_iter()
+            .map(|o| (o.option, o.weight))
+            .collect();
+        assert_eq!(
+            options,
+            vec![
+                (1, "700000000000000000".to_string()),
+                (2, "300000000000000000".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregated_gov_vote() {
+        let mut deps = pool_with_mode(GovVoteMode::Aggregate);
+        FEE_VAULTS
+            .save(
+                deps.as_mut().storage,
+                POOL_ADDR.to_string(),
+                &Uint128::new(10_000),
+            )
+            .unwrap();
+        let mut env = mock_env();
+        let signal_end = env.block.time.seconds() + 100;
+
+        assert!(execute_gov_vote(
+            deps.as_mut(),
+            mock_info(ADMIN, &fee()),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            weighted(&[(GovVoteOption::Yes, "1")]),
+        )
+        .is_err());
+        execute_open_gov_proposal(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADMIN, &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            signal_end,
+        )
+        .unwrap();
+        assert!(execute_open_gov_proposal(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADMIN, &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            signal_end,
+        )
+        .is_err());
+
+        // signals escrow lsd token, signaling again moves the whole signal
+        let msgs = signal(&mut deps, &env, "alice", GovVoteOption::Yes, 300);
+        assert_eq!(
+            lsd_token_msg(&msgs[0]),
+            Cw20ExecuteMsg::TransferFrom {
+                owner: "alice".to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: Uint128::new(300),
+            }
+        );
+        signal(&mut deps, &env, "bob", GovVoteOption::No, 100);
+        signal(&mut deps, &env, "alice", GovVoteOption::NoWithVeto, 200);
+        signal(&mut deps, &env, "carol", GovVoteOption::Yes, 100);
+        assert_eq!(
+            proposal(&deps).tally,
+            vec![
+                (GovVoteOption::No, Uint128::new(100)),
+                (GovVoteOption::NoWithVeto, Uint128::new(500)),
+                (GovVoteOption::Yes, Uint128::new(100)),
+            ]
+        );
+
+        // taken back before the relay, the signal leaves the tally
+        let resp = execute_reclaim_gov_vote(
+            deps.as_mut(),
+            mock_info("carol", &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+        )
+        .unwrap();
+        assert_eq!(
+            lsd_token_msg(&resp.messages[0].msg),
+            Cw20ExecuteMsg::Transfer {
+                recipient: "carol".to_string(),
+                amount: Uint128::new(100),
+            }
+        );
+        assert_eq!(
+            proposal(&deps).tally,
+            vec![
+                (GovVoteOption::No, Uint128::new(100)),
+                (GovVoteOption::NoWithVeto, Uint128::new(500)),
+            ]
+        );
+
+        let relay = |deps: &mut NeutronDeps, env: &Env| {
+            execute_relay_gov_vote(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("keeper", &[]),
+                POOL_ADDR.to_string(),
+                PROPOSAL_ID,
+            )
+        };
+        assert!(relay(&mut deps, &env).is_err());
+        env.block.time = env.block.time.plus_seconds(100);
+        assert!(execute_signal_gov_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("dave", &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+            GovVoteOption::Yes,
+            Uint128::new(100),
+        )
+        .is_err());
+
+        // weighted by escrowed lsd token, the rounding remainder goes to the largest
+        let resp = relay(&mut deps, &env).unwrap();
+        let (_, value) = vote_msg(&resp.messages[0]);
+        let vote = MsgVoteWeighted::decode(value.as_slice()).unwrap();
+        let options: Vec<(i32, String)> = vote
+            .options
+            .into_iter()
+            .map(|o| (o.option, o.weight))
+            .collect();
+        assert_eq!(
+            options,
+            vec![
+                (3, "166666666666666666".to_string()),
+                (4, "833333333333333334".to_string())
+            ]
+        );
+        assert_eq!(proposal(&deps).status, GovProposalStatus::Relaying);
+        // the keeper brought no fee, the vault paid
+        assert_eq!(
+            FEE_VAULTS
+                .load(deps.as_ref().storage, POOL_ADDR.to_string())
+                .unwrap(),
+            Uint128::new(10_000 - ACK_FEE - TIMEOUT_FEE)
+        );
+        assert!(relay(&mut deps, &env).is_err());
+        assert!(execute_reclaim_gov_vote(
+            deps.as_mut(),
+            mock_info("alice", &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+        )
+        .is_err());
+
+        // a failed relay can be sent again
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            1,
+        );
+        sudo_error(deps.as_mut().into_empty(), req).unwrap();
+        assert_eq!(proposal(&deps).status, GovProposalStatus::Signaling);
+        let resp = relay(&mut deps, &env).unwrap();
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            2,
+        );
+        sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        let relayed = proposal(&deps);
+        assert_eq!(relayed.status, GovProposalStatus::Relayed);
+        assert!(relay(&mut deps, &env).is_err());
+
+        // after the relay the escrow comes back and the relayed tally stays
+        let resp = execute_reclaim_gov_vote(
+            deps.as_mut(),
+            mock_info("alice", &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+        )
+        .unwrap();
+        assert_eq!(
+            lsd_token_msg(&resp.messages[0].msg),
+            Cw20ExecuteMsg::Transfer {
+                recipient: "alice".to_string(),
+                amount: Uint128::new(500),
+            }
+        );
+        assert_eq!(proposal(&deps), relayed);
+        assert!(!GOV_SIGNALS.has(
+            deps.as_ref().storage,
+            (POOL_ADDR.to_string(), PROPOSAL_ID, Addr::unchecked("alice"))
+        ));
+        assert!(execute_reclaim_gov_vote(
+            deps.as_mut(),
+            mock_info("alice", &[]),
+            POOL_ADDR.to_string(),
+            PROPOSAL_ID,
+        )
+        .is_err());
+        assert_eq!(
+            relayed.tally.iter().map(|(_, a)| *a).sum::<Uint128>(),
+            Uint128::new(600)
+        );
+    }
+}
@@ -98,7 +98,6 @@ pub fn execute_init_pool(
     pool_info.total_platform_fee = Uint128::zero();
     pool_info.total_lsd_token_amount = Uint128::zero();
     pool_info.next_unstake_index = 0;
-    pool_info.unstake_times_limit = 20;
     pool_info.unbond_commission = Uint128::zero();
     pool_info.paused = false;
     pool_info.lsm_support = false;
@@ -0,0 +1,195 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::error_conversion::ContractError;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::execute_referral::release_referral;
+use crate::helper::{gen_msg_send, CAL_BASE, DEFAULT_TIMEOUT_SECONDS};
+use crate::state::{SudoPayload, TxPayload, INFO_OF_ICA_ID, LIQUIDITY_BUFFERS, POOLS};
+use crate::tx_callback::msg_with_fee_refund;
+use cosmwasm_std::{to_json_binary, Addr, DepsMut, MessageInfo, Response, Uint128, WasmMsg};
+pub use cw20::Cw20ExecuteMsg;
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+// Before this step, need the user to authorize burn from
+pub fn execute_instant_unstake(
+    mut deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    lsd_token_amount: Uint128,
+    pool_addr: String,
+    receiver: Addr,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if lsd_token_amount == Uint128::zero() {
+        return Err(ContractError::EncodeErrLsdTokenAmountZero {}.into());
+    }
+
+    let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    if pool_info.paused {
+        return Err(ContractError::PoolIsPaused {}.into());
+    }
+    // active and the buffer are both counted by era active, keep them out of the era process
+    pool_info.require_era_ended()?;
+
+    let mut liquidity_buffer = LIQUIDITY_BUFFERS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+
+    // cal fee, it stays in the pool and accrues to lsd token holders
+    let token_amount = lsd_token_amount.mul(pool_info.rate).div(CAL_BASE);
+    let fee = token_amount
+        .mul(liquidity_buffer.instant_unstake_commission)
+        .div(CAL_BASE);
+
+    // fix precision issues
+    let receive_amount = token_amount.sub(fee).saturating_sub(Uint128::new(5));
+    if receive_amount.is_zero() {
+        return Err(ContractError::EncodeErrZeroWithdrawAmount {}.into());
+    }
+    if receive_amount > liquidity_buffer.amount {
+        return Err(ContractError::LiquidityBufferNotEnough {}.into());
+    }
+
+    // update pool info
+    pool_info.active = pool_info.active.sub(receive_amount);
+    pool_info.total_lsd_token_amount = pool_info.total_lsd_token_amount.sub(lsd_token_amount);
+    liquidity_buffer.amount = liquidity_buffer.amount.sub(receive_amount);
+    liquidity_buffer.pending = liquidity_buffer.pending.add(receive_amount);
+
+    // burn
+    let burn_msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(
+            &(Cw20ExecuteMsg::BurnFrom {
+                owner: info.sender.to_string(),
+                amount: lsd_token_amount,
+            }),
+        )?,
+        funds: vec![],
+    };
+
+    let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+    let cosmos_msg = NeutronMsg::submit_tx(
+        pool_ica_info.ctrl_connection_id.clone(),
+        pool_info.ica_id.clone(),
+        vec![gen_msg_send(
+            pool_addr.clone(),
+            receiver.to_string(),
+            pool_info.remote_denom.clone(),
+            receive_amount.to_string(),
+        )?],
+        "".to_string(),
+        DEFAULT_TIMEOUT_SECONDS,
+        ibc_fee.clone(),
+    );
+
+    let submsg = msg_with_fee_refund(
+        deps.branch(),
+        cosmos_msg,
+        SudoPayload {
+            port_id: pool_ica_info.ctrl_port_id,
+            message: TxPayload::InstantUnstake {
+                user: info.sender.clone(),
+                receiver: receiver.to_string(),
+                lsd_token_amount,
+                token_amount: receive_amount,
+            },
+            pool_addr: pool_addr.clone(),
+        },
+        &ibc_fee,
+    )?;
+
+    POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
+    LIQUIDITY_BUFFERS.save(deps.storage, pool_addr.clone(), &liquidity_buffer)?;
+    release_referral(
+        deps.storage,
+        pool_addr.clone(),
+        &info.sender,
+        lsd_token_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_message(burn_msg)
+        .add_submessage(submsg)
+        .add_attribute("action", "instant_unstake")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("from", info.sender.to_string())
+        .add_attribute("receiver", receiver.to_string())
+        .add_attribute("token_amount", receive_amount.to_string())
+        .add_attribute("lsd_token_amount", lsd_token_amount.to_string())
+        .add_attribute("fee", fee.to_string()))
+}
+
+pub fn sudo_instant_unstake_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::InstantUnstake {
+        user,
+        receiver,
+        lsd_token_amount,
+        token_amount,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut liquidity_buffer = LIQUIDITY_BUFFERS.load(deps.storage, payload.pool_addr.clone())?;
+    liquidity_buffer.pending = liquidity_buffer.pending.saturating_sub(token_amount);
+    LIQUIDITY_BUFFERS.save(deps.storage, payload.pool_addr.clone(), &liquidity_buffer)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instant_unstake_callback")
+        .add_attribute("pool", payload.pool_addr)
+        .add_attribute("from", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("token_amount", token_amount)
+        .add_attribute("lsd_token_amount", lsd_token_amount))
+}
+
+// the tokens never left the pool ica, so give them back to the buffer and mint the lsd token back
+pub fn sudo_instant_unstake_failed_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::InstantUnstake {
+        user,
+        lsd_token_amount,
+        token_amount,
+        ..
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    let mut liquidity_buffer = LIQUIDITY_BUFFERS.load(deps.storage, payload.pool_addr.clone())?;
+
+    liquidity_buffer.pending = liquidity_buffer.pending.saturating_sub(token_amount);
+    liquidity_buffer.amount = liquidity_buffer.amount.add(token_amount);
+    pool_info.active = pool_info.active.add(token_amount);
+    pool_info.total_lsd_token_amount = pool_info.total_lsd_token_amount.add(lsd_token_amount);
+
+    let msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(
+            &(Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: lsd_token_amount,
+            }),
+        )?,
+        funds: vec![],
+    };
+
+    POOLS.save(deps.storage, payload.pool_addr.clone(), &pool_info)?;
+    LIQUIDITY_BUFFERS.save(deps.storage, payload.pool_addr.clone(), &liquidity_buffer)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "instant_unstake_failed_callback")
+        .add_attribute("pool", payload.pool_addr)
+        .add_attribute("from", user)
+        .add_attribute("lsd_token_amount", lsd_token_amount))
+}
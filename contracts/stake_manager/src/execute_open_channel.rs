@@ -1,6 +1,6 @@
 use std::vec;
 
-use cosmwasm_std::{DepsMut, MessageInfo, Response};
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage};
 
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
@@ -8,6 +8,9 @@ use neutron_sdk::{
 };
 
 use crate::error_conversion::ContractError;
+use crate::execute_register_pool::parse_ctrl_port_id;
+use crate::helper::DEFAULT_TIMEOUT_SECONDS;
+use crate::state::{ClosedChannel, CLOSED_CHANNELS};
 use crate::{
     helper::get_withdraw_ica_id,
     state::{INFO_OF_ICA_ID, POOLS},
@@ -15,11 +18,12 @@ use crate::{
 
 pub fn execute_open_channel(
     deps: DepsMut<NeutronQuery>,
+    env: Env,
     info: MessageInfo,
     pool_addr: String,
     closed_channel_id: String,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let pool_info = POOLS.load(deps.storage, pool_addr)?;
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
     pool_info.authorize(&info.sender)?;
 
     let mut msgs = vec![];
@@ -32,13 +36,14 @@ pub fn execute_open_channel(
 
     let (pool_ica_info, withdraw_ica_info, _) =
         INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
-    if closed_channel_id.eq(&pool_ica_info.ctrl_channel_id.clone()) {
+    let is_pool_ica = if closed_channel_id.eq(&pool_ica_info.ctrl_channel_id.clone()) {
         let register_pool_msg = NeutronMsg::register_interchain_account(
             pool_ica_info.ctrl_connection_id.clone(),
             pool_info.ica_id.clone(),
             register_fee,
         );
         msgs.push(register_pool_msg);
+        true
     } else if closed_channel_id.eq(&withdraw_ica_info.ctrl_channel_id.clone()) {
         let register_withdraw_msg = NeutronMsg::register_interchain_account(
             withdraw_ica_info.ctrl_connection_id.clone(),
@@ -46,9 +51,290 @@ pub fn execute_open_channel(
             register_fee,
         );
         msgs.push(register_withdraw_msg);
+        false
     } else {
         return Err(ContractError::ClosedChannelIdUnmatch {}.into());
-    }
+    };
+
+    // the admin saw the channel close, txs over it wait for the open ack
+    CLOSED_CHANNELS.save(
+        deps.storage,
+        (pool_addr, closed_channel_id.clone()),
+        &ClosedChannel {
+            channel_id: closed_channel_id,
+            is_pool_ica,
+            reopen_at: env.block.time.seconds(),
+        },
+    )?;
 
     Ok(Response::default().add_messages(msgs))
 }
+
+// anyone can register the ica of a channel closed by a timeout again, the open ack brings the pool back
+pub fn execute_reopen_channel(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let closed_channels = load_closed_channels(deps.storage, pool_addr.clone())?;
+    if closed_channels.is_empty() {
+        return Err(ContractError::PoolNotDegraded {}.into());
+    }
+
+    let register_fee = if !info.funds.is_empty() {
+        Some(info.funds)
+    } else {
+        None
+    };
+
+    let (pool_ica_info, withdraw_ica_info, _) =
+        INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+    let now = env.block.time.seconds();
+    let mut msgs = vec![];
+    for mut closed_channel in closed_channels {
+        // give a handshake in flight time to finish
+        if closed_channel.reopen_at != 0 && now < closed_channel.reopen_at + DEFAULT_TIMEOUT_SECONDS
+        {
+            continue;
+        }
+        closed_channel.reopen_at = now;
+
+        let register_msg = if closed_channel.is_pool_ica {
+            NeutronMsg::register_interchain_account(
+                pool_ica_info.ctrl_connection_id.clone(),
+                pool_info.ica_id.clone(),
+                register_fee.clone(),
+            )
+        } else {
+            NeutronMsg::register_interchain_account(
+                withdraw_ica_info.ctrl_connection_id.clone(),
+                get_withdraw_ica_id(pool_info.ica_id.clone()),
+                register_fee.clone(),
+            )
+        };
+        msgs.push(register_msg);
+        CLOSED_CHANNELS.save(
+            deps.storage,
+            (pool_addr.clone(), closed_channel.channel_id.clone()),
+            &closed_channel,
+        )?;
+    }
+    if msgs.is_empty() {
+        return Err(ContractError::ChannelReopenPending {}.into());
+    }
+
+    Ok(Response::default()
+        .add_messages(msgs)
+        .add_attribute("action", "reopen_channel")
+        .add_attribute("pool", pool_addr))
+}
+
+pub fn load_closed_channels(
+    store: &dyn Storage,
+    pool_addr: String,
+) -> StdResult<Vec<ClosedChannel>> {
+    CLOSED_CHANNELS
+        .prefix(pool_addr)
+        .range(store, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, closed_channel)| closed_channel))
+        .collect()
+}
+
+// ica channels are ordered, so a timed out packet closes its channel
+pub fn record_closed_channel(
+    store: &mut dyn Storage,
+    ctrl_port_id: String,
+    channel_id: String,
+) -> NeutronResult<Option<Event>> {
+    // ibc transfers time out on unordered channels that stay open
+    let Some((ica_id, is_pool_ica)) = parse_ctrl_port_id(&ctrl_port_id) else {
+        return Ok(None);
+    };
+    let Some((pool_ica_info, withdraw_ica_info, _)) = INFO_OF_ICA_ID.may_load(store, ica_id)?
+    else {
+        return Ok(None);
+    };
+    let ctrl_channel_id = if is_pool_ica {
+        pool_ica_info.ctrl_channel_id
+    } else {
+        withdraw_ica_info.ctrl_channel_id
+    };
+    // a late timeout from a channel that was replaced already
+    if ctrl_channel_id != channel_id {
+        return Ok(None);
+    }
+    let pool_addr = pool_ica_info.ica_addr;
+    let key = (pool_addr.clone(), channel_id.clone());
+    if !POOLS.has(store, pool_addr.clone()) || CLOSED_CHANNELS.has(store, key.clone()) {
+        return Ok(None);
+    }
+    CLOSED_CHANNELS.save(
+        store,
+        key,
+        &ClosedChannel {
+            channel_id: channel_id.clone(),
+            is_pool_ica,
+            reopen_at: 0,
+        },
+    )?;
+
+    Ok(Some(
+        Event::new("channel_closed")
+            .add_attribute("pool", pool_addr)
+            .add_attribute("channel_id", channel_id),
+    ))
+}
+
+// called from the open ack of a pool or withdraw ica with the channel it replaces, the pool leaves degraded once no channel is closed
+pub fn record_reopened_channel(
+    store: &mut dyn Storage,
+    pool_addr: String,
+    replaced_channel_id: String,
+) -> NeutronResult<Option<Event>> {
+    let key = (pool_addr.clone(), replaced_channel_id);
+    if !CLOSED_CHANNELS.has(store, key.clone()) {
+        return Ok(None);
+    }
+    CLOSED_CHANNELS.remove(store, key);
+    let degraded = !load_closed_channels(store, pool_addr.clone())?.is_empty();
+
+    Ok(Some(
+        Event::new("channel_reopened")
+            .add_attribute("pool", pool_addr)
+            .add_attribute("degraded", degraded.to_string()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use neutron_sdk::bindings::msg::NeutronMsg;
+    use neutron_sdk::sudo::msg::{RequestPacket, RequestPacketTimeoutHeight};
+
+    use super::{execute_reopen_channel, load_closed_channels};
+    use crate::execute_register_pool::sudo_open_ack;
+    use crate::helper::{get_withdraw_ica_id, DEFAULT_TIMEOUT_SECONDS};
+    use crate::state::{PoolInfo, SudoPayload, TxPayload};
+    use crate::testing::{
+        ctrl_port_id, mock_ibc_fee, mock_neutron_dependencies, save_pool, ICA_ID, POOL_ADDR,
+        POOL_CHANNEL_ID,
+    };
+    use crate::tx_callback::{msg_with_sudo_callback, sudo_timeout};
+
+    fn submit_tx(ica_id: &str) -> NeutronMsg {
+        NeutronMsg::submit_tx(
+            "connection-0".to_string(),
+            ica_id.to_string(),
+            vec![],
+            "".to_string(),
+            DEFAULT_TIMEOUT_SECONDS,
+            mock_ibc_fee(),
+        )
+    }
+
+    fn payload() -> SudoPayload {
+        SudoPayload {
+            pool_addr: POOL_ADDR.to_string(),
+            port_id: ctrl_port_id(ICA_ID),
+            message: TxPayload::EraUpdate {},
+        }
+    }
+
+    #[test]
+    fn test_closed_channel_reopen() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(deps.as_mut().storage, PoolInfo::default());
+        let withdraw_ica_id = get_withdraw_ica_id(ICA_ID.to_string());
+
+        // a timeout without a payload left still closes the pool ica channel
+        let resp = sudo_timeout(
+            deps.as_mut().into_empty(),
+            RequestPacket {
+                sequence: Some(7),
+                source_port: Some(ctrl_port_id(ICA_ID)),
+                source_channel: Some(POOL_CHANNEL_ID.to_string()),
+                destination_port: None,
+                destination_channel: None,
+                data: None,
+                timeout_height: None,
+                timeout_timestamp: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(resp.events[0].ty, "channel_closed");
+        let closed_channels =
+            load_closed_channels(deps.as_ref().storage, POOL_ADDR.to_string()).unwrap();
+        assert_eq!(closed_channels.len(), 1);
+        assert!(closed_channels[0].is_pool_ica);
+
+        // only txs over the closed channel are rejected
+        assert!(msg_with_sudo_callback(deps.as_mut(), submit_tx(ICA_ID), payload()).is_err());
+        assert!(
+            msg_with_sudo_callback(deps.as_mut(), submit_tx(&withdraw_ica_id), payload()).is_ok()
+        );
+        let transfer = NeutronMsg::IbcTransfer {
+            source_port: "transfer".to_string(),
+            source_channel: "channel-0".to_string(),
+            token: coin(100, "untrn"),
+            sender: "contract".to_string(),
+            receiver: POOL_ADDR.to_string(),
+            timeout_height: RequestPacketTimeoutHeight {
+                revision_number: None,
+                revision_height: None,
+            },
+            timeout_timestamp: 0,
+            memo: "".to_string(),
+            fee: mock_ibc_fee(),
+        };
+        assert!(msg_with_sudo_callback(deps.as_mut(), transfer, payload()).is_ok());
+
+        // anyone can reopen, once per handshake window
+        let env = mock_env();
+        let resp = execute_reopen_channel(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            POOL_ADDR.to_string(),
+        )
+        .unwrap();
+        assert_eq!(resp.messages.len(), 1);
+        assert!(execute_reopen_channel(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            POOL_ADDR.to_string(),
+        )
+        .is_err());
+
+        // the open ack of the new channel lifts the closure
+        let version = format!(
+            r#"{{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-1","address":"{}","encoding":"proto3","tx_type":"sdk_multi_msg"}}"#,
+            POOL_ADDR
+        );
+        let resp = sudo_open_ack(
+            deps.as_mut().into_empty(),
+            ctrl_port_id(ICA_ID),
+            "channel-12".to_string(),
+            "channel-3".to_string(),
+            version,
+        )
+        .unwrap();
+        assert_eq!(resp.events[0].ty, "channel_reopened");
+        assert!(
+            load_closed_channels(deps.as_ref().storage, POOL_ADDR.to_string())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(msg_with_sudo_callback(deps.as_mut(), submit_tx(ICA_ID), payload()).is_ok());
+        assert!(execute_reopen_channel(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            POOL_ADDR.to_string()
+        )
+        .is_err());
+    }
+}
@@ -5,8 +5,10 @@ use neutron_sdk::{
     NeutronResult,
 };
 
-use crate::helper::deal_validators_icq_update;
-use crate::state::{INFO_OF_ICA_ID, POOLS};
+use crate::helper::{
+    check_total_validator_weight, deal_validators_icq_update, DEFAULT_VALIDATOR_WEIGHT,
+};
+use crate::state::{Role, INFO_OF_ICA_ID, POOLS, VALIDATOR_WEIGHTS};
 use crate::{error_conversion::ContractError, helper};
 
 pub fn execute_add_pool_validators(
@@ -14,9 +16,15 @@ pub fn execute_add_pool_validators(
     info: MessageInfo,
     pool_addr: String,
     validator_addr: String,
+    weight: Option<u64>,
 ) -> NeutronResult<Response<NeutronMsg>> {
     let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
-    pool_info.authorize(&info.sender)?;
+    pool_info.authorize_role(
+        deps.storage,
+        pool_addr.clone(),
+        Role::ValidatorManager,
+        &info.sender,
+    )?;
     pool_info.require_era_ended()?;
 
     if pool_info.validator_addrs.len() >= helper::VALIDATORS_LEN_LIMIT {
@@ -25,7 +33,14 @@ pub fn execute_add_pool_validators(
     if pool_info.validator_addrs.contains(&validator_addr) {
         return Err(ContractError::ValidatorAlreadyExit {}.into());
     }
-    pool_info.validator_addrs.push(validator_addr);
+    pool_info.validator_addrs.push(validator_addr.clone());
+
+    VALIDATOR_WEIGHTS.save(
+        deps.storage,
+        (pool_addr.clone(), validator_addr),
+        &weight.unwrap_or(DEFAULT_VALIDATOR_WEIGHT),
+    )?;
+    check_total_validator_weight(deps.storage, pool_addr.clone(), &pool_info.validator_addrs)?;
 
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
 
@@ -1,11 +1,14 @@
 use crate::error_conversion::ContractError;
+use crate::execute_era_rebalance::record_redelegations;
 use crate::helper;
-use crate::helper::gen_redelegate_txs;
 use crate::helper::DEFAULT_TIMEOUT_SECONDS;
+use crate::helper::{check_total_validator_weight, gen_redelegate_txs};
 use crate::query::query_delegation_by_addr;
-use crate::state::{SudoPayload, TxType, ValidatorUpdateStatus, INFO_OF_ICA_ID, POOLS};
+use crate::state::{
+    Role, SudoPayload, TxPayload, ValidatorUpdateStatus, INFO_OF_ICA_ID, POOLS, VALIDATOR_WEIGHTS,
+};
 use crate::tx_callback::msg_with_sudo_callback;
-use cosmwasm_std::{DepsMut, MessageInfo, Response};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
@@ -19,7 +22,12 @@ pub fn execute_rm_pool_validator(
     validator_addr: String,
 ) -> NeutronResult<Response<NeutronMsg>> {
     let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
-    pool_info.authorize(&info.sender)?;
+    pool_info.authorize_role(
+        deps.storage,
+        pool_addr.clone(),
+        Role::ValidatorManager,
+        &info.sender,
+    )?;
     pool_info.require_era_ended()?;
     pool_info.require_update_validator_ended()?;
 
@@ -43,6 +51,7 @@ pub fn execute_rm_pool_validator(
         .into_iter()
         .filter(|val| val.to_string() != validator_addr)
         .collect();
+    check_total_validator_weight(deps.storage, pool_addr.clone(), &left_validators)?;
     let mut rsp = Response::new();
     if let Some(to_be_redelegate_delegation) = delegations
         .delegations
@@ -52,18 +61,20 @@ pub fn execute_rm_pool_validator(
         if to_be_redelegate_delegation.amount.amount.is_zero() {
             pool_info.validator_update_status = ValidatorUpdateStatus::WaitQueryUpdate;
             pool_info.validator_addrs = left_validators;
+            VALIDATOR_WEIGHTS.remove(deps.storage, (pool_addr.clone(), validator_addr));
         } else {
             let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
             let (pool_ica_info, _, _) =
                 INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
 
+            let dst_validator = left_validators.get(0).unwrap().to_string(); // redelegate to first
             let cosmos_msg = NeutronMsg::submit_tx(
                 pool_ica_info.ctrl_connection_id.clone(),
                 pool_info.ica_id.clone(),
                 vec![gen_redelegate_txs(
                     pool_addr.clone(),
                     to_be_redelegate_delegation.validator.clone(),
-                    left_validators.get(0).unwrap().to_string(), // redelegate to first
+                    dst_validator.clone(),
                     pool_info.remote_denom.clone(),
                     to_be_redelegate_delegation.amount.amount,
                 )],
@@ -78,8 +89,10 @@ pub fn execute_rm_pool_validator(
                 SudoPayload {
                     port_id: pool_ica_info.ctrl_port_id,
                     pool_addr: pool_ica_info.ica_addr.clone(),
-                    message: validator_addr,
-                    tx_type: TxType::RmValidator,
+                    message: TxPayload::RmValidator {
+                        validator: validator_addr.clone(),
+                        redelegations: vec![(validator_addr, dst_validator)],
+                    },
                 },
             )?;
 
@@ -89,6 +102,7 @@ pub fn execute_rm_pool_validator(
     } else {
         pool_info.validator_update_status = ValidatorUpdateStatus::WaitQueryUpdate;
         pool_info.validator_addrs = left_validators;
+        VALIDATOR_WEIGHTS.remove(deps.storage, (pool_addr.clone(), validator_addr));
     }
 
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
@@ -98,13 +112,27 @@ pub fn execute_rm_pool_validator(
 
 pub fn sudo_rm_validator_callback(
     deps: DepsMut,
+    env: Env,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::RmValidator {
+        validator,
+        redelegations,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
     let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    record_redelegations(
+        deps.storage,
+        payload.pool_addr.clone(),
+        env.block.time.seconds(),
+        redelegations,
+    )?;
 
-    pool_info
-        .validator_addrs
-        .retain(|v| v.to_string() != payload.message);
+    pool_info.validator_addrs.retain(|v| *v != validator);
+    VALIDATOR_WEIGHTS.remove(deps.storage, (payload.pool_addr.clone(), validator));
     pool_info.validator_update_status = ValidatorUpdateStatus::WaitQueryUpdate;
 
     POOLS.save(deps.storage, payload.pool_addr.clone(), &pool_info)?;
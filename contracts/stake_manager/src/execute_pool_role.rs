@@ -0,0 +1,53 @@
+use cosmwasm_std::{Addr, DepsMut, MessageInfo, Response};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::state::{Role, POOLS, POOL_ROLES};
+
+pub fn execute_grant_role(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    role: Role,
+    account: Addr,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+    deps.api.addr_validate(account.as_str())?;
+
+    POOL_ROLES.save(
+        deps.storage,
+        (pool_addr.clone(), role.key(), account.clone()),
+        &true,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("role", role.key())
+        .add_attribute("account", account))
+}
+
+pub fn execute_revoke_role(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    role: Role,
+    account: Addr,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+
+    POOL_ROLES.remove(
+        deps.storage,
+        (pool_addr.clone(), role.key(), account.clone()),
+    );
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("role", role.key())
+        .add_attribute("account", account))
+}
@@ -1,14 +1,15 @@
 use crate::error_conversion::ContractError;
-use crate::helper::{self};
-use crate::state::INFO_OF_ICA_ID;
-use crate::state::{ValidatorUpdateStatus, POOLS};
+use crate::execute_era_rebalance::record_redelegations;
+use crate::helper::{self, check_total_validator_weight, DEFAULT_VALIDATOR_WEIGHT};
+use crate::state::{Role, ValidatorUpdateStatus, POOLS};
+use crate::state::{INFO_OF_ICA_ID, VALIDATOR_WEIGHTS};
 use crate::{
     helper::gen_redelegate_txs,
-    state::{SudoPayload, TxType},
+    state::{SudoPayload, TxPayload},
     tx_callback::msg_with_sudo_callback,
 };
 use crate::{helper::DEFAULT_TIMEOUT_SECONDS, query::query_delegation_by_addr};
-use cosmwasm_std::{DepsMut, MessageInfo, Response};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
@@ -21,15 +22,41 @@ pub fn execute_pool_update_validator(
     pool_addr: String,
     old_validator: String,
     new_validator: String,
+    weight: Option<u64>,
 ) -> NeutronResult<Response<NeutronMsg>> {
     let mut pool_info: crate::state::PoolInfo = POOLS.load(deps.storage, pool_addr.clone())?;
-    pool_info.authorize(&info.sender)?;
+    pool_info.authorize_role(
+        deps.storage,
+        pool_addr.clone(),
+        Role::ValidatorManager,
+        &info.sender,
+    )?;
     pool_info.require_era_ended()?;
     pool_info.require_update_validator_ended()?;
 
     if !pool_info.validator_addrs.contains(&old_validator) {
         return Err(ContractError::OldValidatorNotExist {}.into());
     }
+
+    let old_weight = VALIDATOR_WEIGHTS
+        .may_load(deps.storage, (pool_addr.clone(), old_validator.clone()))?
+        .unwrap_or(DEFAULT_VALIDATOR_WEIGHT);
+
+    // only update weight
+    if old_validator == new_validator {
+        VALIDATOR_WEIGHTS.save(
+            deps.storage,
+            (pool_addr.clone(), old_validator.clone()),
+            &weight.unwrap_or(old_weight),
+        )?;
+        check_total_validator_weight(deps.storage, pool_addr.clone(), &pool_info.validator_addrs)?;
+
+        return Ok(Response::default()
+            .add_attribute("action", "pool_update_validator_weight")
+            .add_attribute("pool", pool_addr)
+            .add_attribute("validator", old_validator)
+            .add_attribute("weight", weight.unwrap_or(old_weight).to_string()));
+    }
     if pool_info.validator_addrs.contains(&new_validator) {
         return Err(ContractError::NewValidatorAlreadyExist {}.into());
     }
@@ -45,6 +72,7 @@ pub fn execute_pool_update_validator(
     new_validators.push(new_validator.clone());
 
     let mut msgs = vec![];
+    let mut redelegations = vec![];
 
     for delegation in delegations.delegations {
         if delegation.validator != old_validator {
@@ -65,6 +93,7 @@ pub fn execute_pool_update_validator(
         );
 
         msgs.push(any_msg);
+        redelegations.push((delegation.validator, new_validator.clone()));
     }
     let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
 
@@ -87,13 +116,10 @@ pub fn execute_pool_update_validator(
             SudoPayload {
                 port_id: pool_ica_info.ctrl_port_id,
                 pool_addr: pool_ica_info.ica_addr.clone(),
-                message: new_validators
-                    .clone()
-                    .into_iter()
-                    .map(|index| index.to_string())
-                    .collect::<Vec<String>>()
-                    .join("_"),
-                tx_type: TxType::UpdateValidator,
+                message: TxPayload::UpdateValidator {
+                    new_validators: new_validators.clone(),
+                    redelegations,
+                },
             },
         )?;
 
@@ -104,6 +130,14 @@ pub fn execute_pool_update_validator(
         pool_info.validator_update_status = ValidatorUpdateStatus::WaitQueryUpdate;
     }
 
+    VALIDATOR_WEIGHTS.remove(deps.storage, (pool_addr.clone(), old_validator));
+    VALIDATOR_WEIGHTS.save(
+        deps.storage,
+        (pool_addr.clone(), new_validator),
+        &weight.unwrap_or(old_weight),
+    )?;
+    check_total_validator_weight(deps.storage, pool_addr.clone(), &new_validators)?;
+
     pool_info.validator_addrs = new_validators;
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
 
@@ -112,11 +146,24 @@ pub fn execute_pool_update_validator(
 
 pub fn sudo_update_validator_callback(
     deps: DepsMut,
+    env: Env,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    let TxPayload::UpdateValidator {
+        new_validators,
+        redelegations,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
 
-    let new_validators: Vec<String> = payload.message.split('_').map(String::from).collect();
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    record_redelegations(
+        deps.storage,
+        payload.pool_addr.clone(),
+        env.block.time.seconds(),
+        redelegations,
+    )?;
 
     pool_info.validator_addrs = new_validators;
     pool_info.validator_update_status = ValidatorUpdateStatus::WaitQueryUpdate;
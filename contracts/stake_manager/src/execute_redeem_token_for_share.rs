@@ -1,11 +1,12 @@
+use crate::execute_fee_vault::take_ibc_fee;
 use crate::{
     error_conversion::ContractError,
-    helper::{self, redeem_token_for_share_msg, DEFAULT_TIMEOUT_SECONDS},
+    helper::{redeem_token_for_share_msg, DEFAULT_TIMEOUT_SECONDS},
     state::POOLS,
 };
 use crate::{
-    state::{SudoPayload, TxType, INFO_OF_ICA_ID},
-    tx_callback::msg_with_sudo_callback,
+    state::{SudoPayload, TxPayload, INFO_OF_ICA_ID},
+    tx_callback::msg_with_fee_refund,
 };
 use cosmwasm_std::{DepsMut, MessageInfo, Response};
 use neutron_sdk::{
@@ -49,8 +50,8 @@ pub fn execute_redeem_token_for_share(
         ));
     }
 
-    let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
-    let submsg = msg_with_sudo_callback(
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+    let submsg = msg_with_fee_refund(
         deps.branch(),
         NeutronMsg::submit_tx(
             pool_ica_info.ctrl_connection_id,
@@ -58,15 +59,15 @@ pub fn execute_redeem_token_for_share(
             msgs,
             "".to_string(),
             DEFAULT_TIMEOUT_SECONDS,
-            ibc_fee,
+            ibc_fee.clone(),
         ),
         SudoPayload {
             port_id: pool_ica_info.ctrl_port_id,
             // the acknowledgement later
-            message: denoms.join(","),
+            message: TxPayload::RedeemTokenForShare { denoms },
             pool_addr: pool_addr.clone(),
-            tx_type: TxType::RedeemTokenForShare,
         },
+        &ibc_fee,
     )?;
 
     POOLS.save(deps.storage, pool_addr, &pool_info)?;
@@ -78,9 +79,14 @@ pub fn sudo_redeem_token_for_share_callback(
     deps: DepsMut,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    let TxPayload::RedeemTokenForShare {
+        denoms: will_removed_denoms,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
 
-    let will_removed_denoms: Vec<String> = payload.message.split(",").map(String::from).collect();
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
 
     pool_info
         .share_tokens
@@ -99,9 +105,14 @@ pub fn sudo_redeem_token_for_share_failed_callback(
     deps: DepsMut,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    let TxPayload::RedeemTokenForShare {
+        denoms: will_removed_denoms,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
 
-    let will_removed_denoms: Vec<String> = payload.message.split(",").map(String::from).collect();
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
 
     pool_info
         .redeemming_share_token_denom
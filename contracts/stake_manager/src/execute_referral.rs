@@ -0,0 +1,482 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use cosmwasm_std::{to_json_binary, Addr, DepsMut, MessageInfo, Response, Storage, Uint128};
+use cosmwasm_std::{Decimal, Order, StdResult, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::helper::CAL_BASE;
+use crate::state::{ReferralInfo, ReferralPool, ReferredStake, POOLS, REFERRALS, REFERRAL_CODES};
+use crate::state::{REFERRAL_POOLS, REFERRED_STAKES};
+
+pub const REFERRAL_CODE_LEN_LIMIT: usize = 32;
+
+pub fn execute_register_referral_code(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    code: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if code.is_empty() || code.len() > REFERRAL_CODE_LEN_LIMIT {
+        return Err(ContractError::InvalidReferralCode {}.into());
+    }
+    if REFERRAL_CODES.has(deps.storage, code.clone()) {
+        return Err(ContractError::ReferralCodeTaken {}.into());
+    }
+    REFERRAL_CODES.save(deps.storage, code.clone(), &info.sender)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_referral_code")
+        .add_attribute("code", code)
+        .add_attribute("owner", info.sender))
+}
+
+// sends the code's referral fees of the pool to its owner
+pub fn execute_claim_referral_fee(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    code: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if REFERRAL_CODES.load(deps.storage, code.clone())? != info.sender {
+        return Err(ContractError::Unauthorized {}.into());
+    }
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let referral_pool = REFERRAL_POOLS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+
+    let key = (pool_addr.clone(), code.clone());
+    let mut referral = REFERRALS.load(deps.storage, key.clone())?;
+    settle_referral_fee(&mut referral, &referral_pool);
+    let amount = referral.unclaimed_fee;
+    if amount.is_zero() {
+        return Err(ContractError::NoReferralFee {}.into());
+    }
+    referral.unclaimed_fee = Uint128::zero();
+    referral.claimed_fee = referral.claimed_fee.add(amount);
+    REFERRALS.save(deps.storage, key, &referral)?;
+
+    let msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "claim_referral_fee")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("code", code)
+        .add_attribute("amount", amount))
+}
+
+// a code can't be used by its owner, neither as the staker nor as the sender
+pub fn check_referral_code(
+    store: &dyn Storage,
+    code: &Option<String>,
+    staker: &str,
+    sender: &Addr,
+) -> NeutronResult<()> {
+    if let Some(code) = code {
+        let owner = REFERRAL_CODES
+            .may_load(store, code.clone())?
+            .ok_or(ContractError::ReferralCodeNotFound {})?;
+        if owner == staker || owner == sender {
+            return Err(ContractError::SelfReferral {}.into());
+        }
+    }
+
+    Ok(())
+}
+
+// adds a stake brought by the referral code to its totals and to the staker's part of them
+pub fn record_referral(
+    store: &mut dyn Storage,
+    pool_addr: String,
+    code: String,
+    staker: Addr,
+    staked: Uint128,
+    lsd_minted: Uint128,
+) -> StdResult<()> {
+    let mut referral_pool = REFERRAL_POOLS
+        .may_load(store, pool_addr.clone())?
+        .unwrap_or_default();
+    let key = (pool_addr.clone(), code.clone());
+    let mut referral = REFERRALS.may_load(store, key.clone())?.unwrap_or_default();
+
+    settle_referral_fee(&mut referral, &referral_pool);
+    referral.staked = referral.staked.add(staked);
+    referral.lsd_minted = referral.lsd_minted.add(lsd_minted);
+    referral_pool.total_staked = referral_pool.total_staked.add(staked);
+
+    let stake_key = (pool_addr.clone(), staker, code);
+    let mut referred_stake = REFERRED_STAKES
+        .may_load(store, stake_key.clone())?
+        .unwrap_or_default();
+    referred_stake.staked = referred_stake.staked.add(staked);
+    referred_stake.lsd_token_amount = referred_stake.lsd_token_amount.add(lsd_minted);
+
+    REFERRED_STAKES.save(store, stake_key, &referred_stake)?;
+    REFERRALS.save(store, key, &referral)?;
+    REFERRAL_POOLS.save(store, pool_addr, &referral_pool)
+}
+
+// takes the referred stake behind burnt lsd token out of the totals, in proportion to the lsd
+// token, so staking and unstaking again doesn't grow a code's share. The staker's referred lsd
+// token counts as burnt first.
+pub fn release_referral(
+    store: &mut dyn Storage,
+    pool_addr: String,
+    staker: &Addr,
+    lsd_burnt: Uint128,
+) -> StdResult<()> {
+    let referred_stakes = REFERRED_STAKES
+        .prefix((pool_addr.clone(), staker.clone()))
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, ReferredStake)>>>()?;
+    if referred_stakes.is_empty() {
+        return Ok(());
+    }
+
+    let mut referral_pool = REFERRAL_POOLS
+        .may_load(store, pool_addr.clone())?
+        .unwrap_or_default();
+    let mut lsd_left = lsd_burnt;
+    for (code, mut referred_stake) in referred_stakes {
+        if lsd_left.is_zero() {
+            break;
+        }
+        let lsd_released = lsd_left.min(referred_stake.lsd_token_amount);
+        let released = referred_stake
+            .staked
+            .multiply_ratio(lsd_released, referred_stake.lsd_token_amount);
+        lsd_left = lsd_left.sub(lsd_released);
+
+        let key = (pool_addr.clone(), code.clone());
+        let mut referral = REFERRALS.load(store, key.clone())?;
+        settle_referral_fee(&mut referral, &referral_pool);
+        referral.staked = referral.staked.sub(released);
+        referral_pool.total_staked = referral_pool.total_staked.sub(released);
+        REFERRALS.save(store, key, &referral)?;
+
+        let stake_key = (pool_addr.clone(), staker.clone(), code);
+        referred_stake.staked = referred_stake.staked.sub(released);
+        referred_stake.lsd_token_amount = referred_stake.lsd_token_amount.sub(lsd_released);
+        if referred_stake.lsd_token_amount.is_zero() {
+            REFERRED_STAKES.remove(store, stake_key);
+        } else {
+            REFERRED_STAKES.save(store, stake_key, &referred_stake)?;
+        }
+    }
+
+    REFERRAL_POOLS.save(store, pool_addr, &referral_pool)
+}
+
+// takes the referrers' share out of the platform fee, returns it
+pub fn distribute_referral_fee(
+    store: &mut dyn Storage,
+    pool_addr: String,
+    platform_fee: Uint128,
+) -> StdResult<Uint128> {
+    let Some(mut referral_pool) = REFERRAL_POOLS.may_load(store, pool_addr.clone())? else {
+        return Ok(Uint128::zero());
+    };
+    if referral_pool.total_staked.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let referral_fee = platform_fee.mul(referral_pool.fee_commission).div(CAL_BASE);
+    if referral_fee.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    referral_pool.fee_index = referral_pool.fee_index.add(Decimal::from_ratio(
+        referral_fee,
+        referral_pool.total_staked,
+    ));
+    REFERRAL_POOLS.save(store, pool_addr, &referral_pool)?;
+
+    Ok(referral_fee)
+}
+
+pub fn settle_referral_fee(referral: &mut ReferralInfo, referral_pool: &ReferralPool) {
+    let accrued = referral
+        .staked
+        .mul(referral_pool.fee_index.sub(referral.fee_index));
+    referral.unclaimed_fee = referral.unclaimed_fee.add(accrued);
+    referral.fee_index = referral_pool.fee_index;
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, from_json, Addr, CosmosMsg, Decimal, Uint128, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+
+    use super::{execute_claim_referral_fee, execute_register_referral_code};
+    use crate::execute_era_active::execute_era_active;
+    use crate::execute_stake::execute_stake;
+    use crate::execute_unstake::execute_unstake;
+    use crate::state::EraStatus::EraRestakeEnded;
+    use crate::state::{PoolInfo, ReferralPool, POOLS, REFERRALS, REFERRAL_POOLS, REFERRED_STAKES};
+    use crate::testing::{
+        delegations_kv, mock_neutron_dependencies, mock_neutron_querier, register_pool_queries,
+        save_pool, validators_kv, NeutronDeps, DELEGATIONS_QUERY_ID, POOL_ADDR,
+        VALIDATORS_QUERY_ID,
+    };
+
+    fn stake(deps: &mut NeutronDeps, user: &str, amount: u128, referral: Option<&str>) {
+        execute_stake(
+            deps.as_mut(),
+            mock_env(),
+            user.to_string(),
+            POOL_ADDR.to_string(),
+            mock_info(user, &coins(amount, "ibc/atom")),
+            referral.map(|code| code.to_string()),
+        )
+        .unwrap();
+    }
+
+    fn lsd_token_msg(msg: &CosmosMsg<neutron_sdk::bindings::msg::NeutronMsg>) -> Cw20ExecuteMsg {
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = msg else {
+            panic!("not a lsd token msg: {:?}", msg);
+        };
+        from_json(msg).unwrap()
+    }
+
+    #[test]
+    fn test_referral_fee_sharing() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                era: 5,
+                status: EraRestakeEnded,
+                rate: Uint128::new(1_000_000),
+                platform_fee_commission: Uint128::new(100_000),
+                platform_fee_receiver: Addr::unchecked("platform"),
+                lsd_token: Addr::unchecked("lsd_token"),
+                ibc_denom: "ibc/atom".to_string(),
+                ..PoolInfo::default()
+            },
+        );
+        register_pool_queries(deps.as_mut().storage);
+        // referrers get a fifth of the platform fee
+        REFERRAL_POOLS
+            .save(
+                deps.as_mut().storage,
+                POOL_ADDR.to_string(),
+                &ReferralPool {
+                    fee_commission: Uint128::new(200_000),
+                    ..ReferralPool::default()
+                },
+            )
+            .unwrap();
+        for code in ["alice", "bob"] {
+            execute_register_referral_code(deps.as_mut(), mock_info(code, &[]), code.to_string())
+                .unwrap();
+        }
+        assert!(execute_register_referral_code(
+            deps.as_mut(),
+            mock_info("mallory", &[]),
+            "alice".to_string()
+        )
+        .is_err());
+
+        assert!(execute_stake(
+            deps.as_mut(),
+            mock_env(),
+            "user".to_string(),
+            POOL_ADDR.to_string(),
+            mock_info("user", &coins(100, "ibc/atom")),
+            Some("nobody".to_string()),
+        )
+        .is_err());
+        stake(&mut deps, "user1", 3_000, Some("alice"));
+        stake(&mut deps, "user2", 1_000, Some("bob"));
+        stake(&mut deps, "user3", 6_000, None);
+        let referral = |deps: &NeutronDeps, code: &str| {
+            REFERRALS
+                .load(
+                    deps.as_ref().storage,
+                    (POOL_ADDR.to_string(), code.to_string()),
+                )
+                .unwrap()
+        };
+        assert_eq!(referral(&deps, "alice").staked, Uint128::new(3_000));
+        assert_eq!(referral(&deps, "alice").lsd_minted, Uint128::new(3_000));
+        assert_eq!(referral(&deps, "bob").staked, Uint128::new(1_000));
+        let referral_pool = REFERRAL_POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(referral_pool.total_staked, Uint128::new(4_000));
+
+        // 1000 reward: 100 lsd platform fee, 20 of it for the referrers
+        let mut pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        pool_info.era_snapshot.active = Uint128::new(10_000);
+        POOLS
+            .save(deps.as_mut().storage, POOL_ADDR.to_string(), &pool_info)
+            .unwrap();
+        deps.querier = mock_neutron_querier(vec![
+            (
+                DELEGATIONS_QUERY_ID,
+                1,
+                delegations_kv("uatom", &[("val1", 11_000, 11_000)]),
+            ),
+            (
+                VALIDATORS_QUERY_ID,
+                1,
+                validators_kv(&[("val1", 11_000, 11_000)]),
+            ),
+        ]);
+        let env = mock_env();
+        let resp = execute_era_active(deps.as_mut(), env.clone(), POOL_ADDR.to_string()).unwrap();
+        assert_eq!(
+            lsd_token_msg(&resp.messages[0].msg),
+            Cw20ExecuteMsg::Mint {
+                recipient: "platform".to_string(),
+                amount: Uint128::new(80),
+            }
+        );
+        assert_eq!(
+            lsd_token_msg(&resp.messages[1].msg),
+            Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: Uint128::new(20),
+            }
+        );
+        let referral_pool = REFERRAL_POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(
+            referral_pool.fee_index,
+            Decimal::from_ratio(20u128, 4_000u128)
+        );
+
+        // stake brought after the fee doesn't share in it
+        stake(&mut deps, "user4", 1_000, Some("bob"));
+
+        // shares in proportion to the stake brought
+        assert!(execute_claim_referral_fee(
+            deps.as_mut(),
+            mock_info("alice", &[]),
+            POOL_ADDR.to_string(),
+            "bob".to_string()
+        )
+        .is_err());
+        for (code, fee) in [("alice", 15), ("bob", 5)] {
+            let resp = execute_claim_referral_fee(
+                deps.as_mut(),
+                mock_info(code, &[]),
+                POOL_ADDR.to_string(),
+                code.to_string(),
+            )
+            .unwrap();
+            assert_eq!(
+                lsd_token_msg(&resp.messages[0].msg),
+                Cw20ExecuteMsg::Transfer {
+                    recipient: code.to_string(),
+                    amount: Uint128::new(fee),
+                }
+            );
+            assert_eq!(referral(&deps, code).claimed_fee, Uint128::new(fee));
+            assert!(referral(&deps, code).unclaimed_fee.is_zero());
+        }
+        assert_eq!(referral(&deps, "bob").staked, Uint128::new(2_000));
+        assert!(execute_claim_referral_fee(
+            deps.as_mut(),
+            mock_info("alice", &[]),
+            POOL_ADDR.to_string(),
+            "alice".to_string()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_referral_churn() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                era: 5,
+                rate: Uint128::new(1_000_000),
+                lsd_token: Addr::unchecked("lsd_token"),
+                ibc_denom: "ibc/atom".to_string(),
+                ..PoolInfo::default()
+            },
+        );
+        execute_register_referral_code(deps.as_mut(), mock_info("alice", &[]), "alice".to_string())
+            .unwrap();
+
+        // the owner can't use their own code, neither as staker nor as sender
+        for (staker, sender) in [("alice", "user"), ("user", "alice")] {
+            assert!(execute_stake(
+                deps.as_mut(),
+                mock_env(),
+                staker.to_string(),
+                POOL_ADDR.to_string(),
+                mock_info(sender, &coins(1_000, "ibc/atom")),
+                Some("alice".to_string()),
+            )
+            .is_err());
+        }
+
+        // staking and unstaking in a loop leaves the share where one stake puts it
+        let unstake = |deps: &mut NeutronDeps, lsd_token_amount: u128| {
+            execute_unstake(
+                deps.as_mut(),
+                mock_info("user", &[]),
+                Uint128::new(lsd_token_amount),
+                POOL_ADDR.to_string(),
+            )
+            .unwrap();
+        };
+        for _ in 0..3 {
+            stake(&mut deps, "user", 1_000, Some("alice"));
+            unstake(&mut deps, 1_000);
+        }
+        stake(&mut deps, "user", 1_000, Some("alice"));
+        let referral = |deps: &NeutronDeps| {
+            REFERRALS
+                .load(
+                    deps.as_ref().storage,
+                    (POOL_ADDR.to_string(), "alice".to_string()),
+                )
+                .unwrap()
+        };
+        let total_staked = |deps: &NeutronDeps| {
+            REFERRAL_POOLS
+                .load(deps.as_ref().storage, POOL_ADDR.to_string())
+                .unwrap()
+                .total_staked
+        };
+        assert_eq!(referral(&deps).staked, Uint128::new(1_000));
+        assert_eq!(total_staked(&deps), Uint128::new(1_000));
+
+        // lsd token staked without the code is burnt after the referred part
+        stake(&mut deps, "user", 500, None);
+        unstake(&mut deps, 600);
+        assert_eq!(referral(&deps).staked, Uint128::new(400));
+        assert_eq!(total_staked(&deps), Uint128::new(400));
+        unstake(&mut deps, 900);
+        assert!(referral(&deps).staked.is_zero());
+        assert!(total_staked(&deps).is_zero());
+        assert!(!REFERRED_STAKES.has(
+            deps.as_ref().storage,
+            (
+                POOL_ADDR.to_string(),
+                Addr::unchecked("user"),
+                "alice".to_string()
+            )
+        ));
+    }
+}
@@ -11,6 +11,7 @@ use neutron_sdk::{
 };
 
 use crate::error_conversion::ContractError;
+use crate::execute_open_channel::record_reopened_channel;
 use crate::helper;
 use crate::state::ICA_ID_OF_CREATOR;
 use crate::{
@@ -120,26 +121,19 @@ pub fn sudo_open_ack(
         serde_json_wasm::from_str(counterparty_version.as_str())
             .map_err(|_| ContractError::CantParseCounterpartyVersion {})?;
 
-    let port_id_parts: Vec<String> = ctrl_port_id.split('.').map(String::from).collect();
-    if port_id_parts.len() != 2 {
-        return Err(ContractError::CounterpartyVersionNotMatch {}.into());
-    }
-
-    let ica_id_raw = port_id_parts.get(1).unwrap();
-    let mut is_pool = true;
-    let ica_id = if ica_id_raw.contains(ICA_WITHDRAW_SUFIX) {
-        is_pool = false;
-        ica_id_raw
-            .strip_suffix(ICA_WITHDRAW_SUFIX)
-            .unwrap()
-            .to_string()
-    } else {
-        ica_id_raw.clone()
-    };
+    let (ica_id, is_pool) =
+        parse_ctrl_port_id(&ctrl_port_id).ok_or(ContractError::CounterpartyVersionNotMatch {})?;
 
     let (mut pool_ica_info, mut withdraw_ica_info, admin) =
         INFO_OF_ICA_ID.load(deps.storage, ica_id.clone())?;
 
+    // a new channel for a registered ica replaces one that was closed
+    let replaced_channel_id = if is_pool {
+        pool_ica_info.ctrl_channel_id.clone()
+    } else {
+        withdraw_ica_info.ctrl_channel_id.clone()
+    };
+
     if is_pool {
         pool_ica_info.ctrl_channel_id = ctrl_channel_id;
         pool_ica_info.ctrl_port_id = ctrl_port_id;
@@ -167,11 +161,35 @@ pub fn sudo_open_ack(
         POOLS.save(deps.storage, pool_ica_info.ica_addr.clone(), &pool_info)?;
     }
 
+    // the ica keeps its address on a new channel, so a pool degraded by a closed channel recovers here
+    let mut resp = Response::default();
+    if let Some(event) = record_reopened_channel(
+        deps.storage,
+        pool_ica_info.ica_addr.clone(),
+        replaced_channel_id,
+    )? {
+        resp = resp.add_event(event);
+    }
+
     INFO_OF_ICA_ID.save(
         deps.storage,
         ica_id.clone(),
         &(pool_ica_info, withdraw_ica_info, admin),
     )?;
 
-    return Ok(Response::default());
+    Ok(resp)
+}
+
+// splits a ctrl port id into the pool's ica id and whether it is the pool ica or the withdraw ica
+pub fn parse_ctrl_port_id(ctrl_port_id: &str) -> Option<(String, bool)> {
+    let port_id_parts: Vec<&str> = ctrl_port_id.split('.').collect();
+    if port_id_parts.len() != 2 {
+        return None;
+    }
+
+    let ica_id_raw = port_id_parts[1];
+    match ica_id_raw.strip_suffix(ICA_WITHDRAW_SUFIX) {
+        Some(ica_id) => Some((ica_id.to_string(), false)),
+        None => Some((ica_id_raw.to_string(), true)),
+    }
 }
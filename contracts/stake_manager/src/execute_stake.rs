@@ -1,12 +1,14 @@
 use std::ops::{Add, Div, Mul};
 use std::vec;
 
-use cosmwasm_std::{to_json_binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, WasmMsg};
+use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, DepsMut, Env, MessageInfo, Response, WasmMsg};
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
 };
 
+use crate::execute_referral::{check_referral_code, record_referral};
+use crate::helper::derive_intermediate_sender;
 use crate::state::POOLS;
 use crate::{error_conversion::ContractError, helper::CAL_BASE};
 pub use cw20::Cw20ExecuteMsg;
@@ -16,7 +18,9 @@ pub fn execute_stake(
     neutron_address: String,
     pool_addr: String,
     info: MessageInfo,
+    referral: Option<String>,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    check_referral_code(deps.storage, &referral, &neutron_address, &info.sender)?;
     let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
     if pool_info.paused {
         return Err(ContractError::PoolIsPaused {}.into());
@@ -50,7 +54,20 @@ pub fn execute_stake(
 
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
 
-    Ok(Response::new()
+    let mut resp = Response::new();
+    if let Some(referral) = referral {
+        record_referral(
+            deps.storage,
+            pool_addr.clone(),
+            referral.clone(),
+            Addr::unchecked(neutron_address.clone()),
+            token_amount,
+            lsd_token_amount,
+        )?;
+        resp = resp.add_attribute("referral", referral);
+    }
+
+    Ok(resp
         .add_message(CosmosMsg::Wasm(msg))
         .add_attribute("action", "stake")
         .add_attribute("pool", pool_addr)
@@ -58,3 +75,147 @@ pub fn execute_stake(
         .add_attribute("token_amount", token_amount)
         .add_attribute("lsd_token_amount", lsd_token_amount))
 }
+
+// Called by ibc hooks for an ics-20 transfer of remote_denom from the host chain with memo
+// {"wasm":{"contract":"<stake manager>","msg":{"stake_from_host":{...}}}}.
+pub fn execute_stake_from_host(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    neutron_address: String,
+    pool_addr: String,
+    host_sender: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+
+    // only ibc hooks executing a transfer from host_sender over the pool's channel gets this sender
+    let intermediate_sender = derive_intermediate_sender(
+        deps.as_ref(),
+        &pool_info.channel_id_of_ibc_denom,
+        &host_sender,
+    )?;
+    if info.sender != intermediate_sender {
+        return Err(ContractError::IbcHooksSenderNotMatch {}.into());
+    }
+    deps.api.addr_validate(&neutron_address)?;
+
+    // nothing refunds here: an error, e.g. a paused pool or less than minimal_stake, fails the
+    // transfer with an error ack and the transfer module on the host chain refunds host_sender
+    Ok(
+        execute_stake(deps, env, neutron_address, pool_addr, info, None)?
+            .add_attribute("host_sender", host_sender),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info, MockQuerier, MockStorage};
+    use cosmwasm_std::{coins, from_json, Addr, CosmosMsg, OwnedDeps, Response, Uint128, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+    use neutron_sdk::bindings::{msg::NeutronMsg, query::NeutronQuery};
+    use neutron_sdk::{NeutronError, NeutronResult};
+
+    use super::execute_stake_from_host;
+    use crate::error_conversion::ContractError;
+    use crate::helper::derive_intermediate_sender;
+    use crate::state::{PoolInfo, POOLS};
+    use crate::testing::{mock_neutron_dependencies_with_api, save_pool, HexApi, POOL_ADDR};
+
+    const HOST_SENDER: &str = "cosmos1host";
+
+    type HexDeps = OwnedDeps<MockStorage, HexApi, MockQuerier<NeutronQuery>, NeutronQuery>;
+
+    fn stake_from_host(
+        deps: &mut HexDeps,
+        sender: &str,
+        amount: u128,
+        host_sender: &str,
+    ) -> NeutronResult<Response<NeutronMsg>> {
+        execute_stake_from_host(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender, &coins(amount, "ibc/atom")),
+            "staker".to_string(),
+            POOL_ADDR.to_string(),
+            host_sender.to_string(),
+        )
+    }
+
+    fn assert_err<T: std::fmt::Debug>(result: NeutronResult<T>, error: ContractError) {
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            NeutronError::from(error).to_string()
+        );
+    }
+
+    #[test]
+    fn test_stake_from_host() {
+        let mut deps = mock_neutron_dependencies_with_api(HexApi::default());
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                rate: Uint128::new(1_000_000),
+                minimal_stake: Uint128::new(100),
+                lsd_token: Addr::unchecked("lsd_token"),
+                ibc_denom: "ibc/atom".to_string(),
+                channel_id_of_ibc_denom: "channel-0".to_string(),
+                ..PoolInfo::default()
+            },
+        );
+        // ibc hooks executes as the sender derived from the receiving channel and the host sender
+        let sender = derive_intermediate_sender(deps.as_ref(), "channel-0", HOST_SENDER).unwrap();
+
+        let resp = stake_from_host(&mut deps, sender.as_str(), 1_000, HOST_SENDER).unwrap();
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &resp.messages[0].msg else {
+            panic!("not a lsd token msg: {:?}", resp.messages[0].msg);
+        };
+        assert_eq!(
+            from_json::<Cw20ExecuteMsg>(msg).unwrap(),
+            Cw20ExecuteMsg::Mint {
+                recipient: "staker".to_string(),
+                amount: Uint128::new(1_000),
+            }
+        );
+        assert!(resp
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "host_sender" && attr.value == HOST_SENDER));
+        let pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(pool_info.active, Uint128::new(1_000));
+
+        // anyone else, another host sender or another channel doesn't match
+        assert_err(
+            stake_from_host(&mut deps, "anyone", 1_000, HOST_SENDER),
+            ContractError::IbcHooksSenderNotMatch {},
+        );
+        assert_err(
+            stake_from_host(&mut deps, sender.as_str(), 1_000, "cosmos1other"),
+            ContractError::IbcHooksSenderNotMatch {},
+        );
+        let other_channel_sender =
+            derive_intermediate_sender(deps.as_ref(), "channel-1", HOST_SENDER).unwrap();
+        assert_err(
+            stake_from_host(&mut deps, other_channel_sender.as_str(), 1_000, HOST_SENDER),
+            ContractError::IbcHooksSenderNotMatch {},
+        );
+
+        // these errors fail the ack, which refunds the transfer on the host chain
+        assert_err(
+            stake_from_host(&mut deps, sender.as_str(), 99, HOST_SENDER),
+            ContractError::LessThanMinimalStake {},
+        );
+        let mut pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        pool_info.paused = true;
+        POOLS
+            .save(deps.as_mut().storage, POOL_ADDR.to_string(), &pool_info)
+            .unwrap();
+        assert_err(
+            stake_from_host(&mut deps, sender.as_str(), 1_000, HOST_SENDER),
+            ContractError::PoolIsPaused {},
+        );
+    }
+}
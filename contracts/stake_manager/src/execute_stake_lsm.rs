@@ -1,13 +1,15 @@
+use crate::execute_referral::{check_referral_code, record_referral};
 use crate::{
     error_conversion::ContractError,
     helper::DEFAULT_TIMEOUT_SECONDS,
     helper::{min_ntrn_ibc_fee, query_denom_trace_from_ibc_denom, CAL_BASE},
     query::query_validator_by_addr,
-    state::{SudoPayload, TxType, INFO_OF_ICA_ID, POOLS},
+    state::{SudoPayload, TxPayload, INFO_OF_ICA_ID, POOLS},
     tx_callback::msg_with_sudo_callback,
 };
 use cosmwasm_std::{
-    coins, to_json_binary, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, Uint128, WasmMsg,
+    coins, to_json_binary, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, Uint128,
+    WasmMsg,
 };
 pub use cw20::Cw20ExecuteMsg;
 use neutron_sdk::{
@@ -27,7 +29,9 @@ pub fn execute_stake_lsm(
     info: MessageInfo,
     neutron_address: String,
     pool_addr: String,
+    referral: Option<String>,
 ) -> NeutronResult<Response<NeutronMsg>> {
+    check_referral_code(deps.storage, &referral, &neutron_address, &info.sender)?;
     let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
     if !pool_info.lsm_support {
         return Err(ContractError::LsmStakeNotSupport {}.into());
@@ -119,16 +123,15 @@ pub fn execute_stake_lsm(
             SudoPayload {
                 port_id: pool_ica_info.ctrl_port_id,
                 // the acknowledgement later
-                message: format!(
-                    "{}_{}_{}_{}_{}",
-                    neutron_address,
+                message: TxPayload::StakeLsm {
+                    staker: neutron_address,
                     token_amount,
                     share_token_amount,
-                    share_token_ibc_denom.clone(),
-                    share_token_denom.clone(),
-                ),
+                    share_token_ibc_denom: share_token_ibc_denom.clone(),
+                    share_token_denom: share_token_denom.clone(),
+                    referral,
+                },
                 pool_addr: pool_addr.clone(),
-                tx_type: TxType::StakeLsm,
             },
         )?;
     } else {
@@ -142,35 +145,23 @@ pub fn sudo_stake_lsm_callback(
     deps: DepsMut,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let parts: Vec<String> = payload.message.split('_').map(String::from).collect();
-    if parts.len() != 5 {
-        return Err(ContractError::UnsupportedMessage(payload.message).into());
-    }
-
-    let staker_neutron_addr = parts.get(0).unwrap();
-    let token_amount_str = parts.get(1).unwrap();
-    let share_token_amount_str = parts.get(2).unwrap();
-    let share_token_denom = parts.get(4).unwrap();
-
-    let token_amount = match token_amount_str.parse::<u128>() {
-        Ok(amount) => amount,
-        Err(_) => {
-            return Err(ContractError::UnsupportedMessage(payload.message).into());
-        }
-    };
-    let share_token_amount = match share_token_amount_str.parse::<u128>() {
-        Ok(amount) => amount,
-        Err(_) => {
-            return Err(ContractError::UnsupportedMessage(payload.message).into());
-        }
+    let TxPayload::StakeLsm {
+        staker: staker_neutron_addr,
+        token_amount,
+        share_token_amount,
+        share_token_denom,
+        referral,
+        ..
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
     };
 
     let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
 
     // cal
-    let token_amount_use = Uint128::new(token_amount);
-    pool_info.active = pool_info.active.add(token_amount_use);
-    let lsd_token_amount = token_amount_use.mul(CAL_BASE).div(pool_info.rate);
+    pool_info.active = pool_info.active.add(token_amount);
+    let lsd_token_amount = token_amount.mul(CAL_BASE).div(pool_info.rate);
 
     // mint
     let msg = WasmMsg::Execute {
@@ -186,42 +177,49 @@ pub fn sudo_stake_lsm_callback(
     pool_info.total_lsd_token_amount = pool_info.total_lsd_token_amount.add(lsd_token_amount);
 
     pool_info.share_tokens.push(Coin {
-        denom: share_token_denom.to_string(),
-        amount: Uint128::new(share_token_amount),
+        denom: share_token_denom,
+        amount: share_token_amount,
     });
 
     // pool_info.share_tokens
     POOLS.save(deps.storage, payload.pool_addr.clone(), &pool_info)?;
 
-    Ok(Response::new()
+    let mut resp = Response::new();
+    if let Some(referral) = referral {
+        record_referral(
+            deps.storage,
+            payload.pool_addr.clone(),
+            referral.clone(),
+            Addr::unchecked(staker_neutron_addr.clone()),
+            token_amount,
+            lsd_token_amount,
+        )?;
+        resp = resp.add_attribute("referral", referral);
+    }
+
+    Ok(resp
         .add_message(msg)
         .add_attribute("action", "stake_lsm")
         .add_attribute("pool", payload.pool_addr)
         .add_attribute("staker", staker_neutron_addr)
-        .add_attribute("token_amount", token_amount_use)
+        .add_attribute("token_amount", token_amount)
         .add_attribute("lsd_token_amount", lsd_token_amount))
 }
 
 pub fn sudo_stake_lsm_failed_callback(payload: SudoPayload) -> NeutronResult<Response<NeutronMsg>> {
-    let parts: Vec<String> = payload.message.split('_').map(String::from).collect();
-    if parts.len() != 5 {
-        return Err(ContractError::UnsupportedMessage(payload.message).into());
-    }
-
-    let staker_neutron_addr = parts.get(0).unwrap();
-    let share_token_amount_str = parts.get(2).unwrap();
-    let share_token_ibc_denom = parts.get(3).unwrap();
-
-    let share_token_amount = match share_token_amount_str.parse::<u128>() {
-        Ok(amount) => amount,
-        Err(_) => {
-            return Err(ContractError::UnsupportedMessage(payload.message).into());
-        }
+    let TxPayload::StakeLsm {
+        staker: staker_neutron_addr,
+        share_token_amount,
+        share_token_ibc_denom,
+        ..
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
     };
 
     let msg = BankMsg::Send {
-        to_address: staker_neutron_addr.to_string(),
-        amount: coins(share_token_amount, share_token_ibc_denom),
+        to_address: staker_neutron_addr,
+        amount: coins(share_token_amount.u128(), share_token_ibc_denom),
     };
 
     Ok(Response::new().add_message(msg))
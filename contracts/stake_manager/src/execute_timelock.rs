@@ -0,0 +1,140 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::execute_admin_transfer_funds::admin_transfer_funds;
+use crate::execute_config_pool::{authorize_config_pool, config_pool};
+use crate::msg::TimelockOperation;
+use crate::state::{load_timelock_delay, TimelockedOperation, NEXT_TIMELOCK_ID, POOLS};
+use crate::state::{TIMELOCKED_OPERATIONS, TIMELOCK_DELAYS};
+
+pub fn execute_queue_operation(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    operation: TimelockOperation,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    match &operation {
+        TimelockOperation::ConfigPool(param) => {
+            if param.pool_addr != pool_addr {
+                return Err(ContractError::Unauthorized {}.into());
+            }
+            authorize_config_pool(deps.storage, &pool_info, param, &info.sender)?;
+        }
+        TimelockOperation::AdminTransferFunds { .. }
+        | TimelockOperation::SetTimelockDelay { .. } => {
+            pool_info.authorize(&info.sender)?;
+        }
+    }
+
+    let id = NEXT_TIMELOCK_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_TIMELOCK_ID.save(deps.storage, &(id + 1))?;
+
+    let eta = env
+        .block
+        .time
+        .seconds()
+        .saturating_add(load_timelock_delay(deps.storage, pool_addr.clone())?);
+    TIMELOCKED_OPERATIONS.save(
+        deps.storage,
+        (pool_addr.clone(), id),
+        &TimelockedOperation {
+            id,
+            pool_addr: pool_addr.clone(),
+            operation,
+            proposer: info.sender.clone(),
+            eta,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "queue_operation")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("proposer", info.sender)
+        .add_attribute("id", id.to_string())
+        .add_attribute("eta", eta.to_string()))
+}
+
+pub fn execute_cancel_operation(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    id: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let operation = TIMELOCKED_OPERATIONS.load(deps.storage, (pool_addr.clone(), id))?;
+    if info.sender != operation.proposer {
+        pool_info.authorize(&info.sender)?;
+    }
+
+    TIMELOCKED_OPERATIONS.remove(deps.storage, (pool_addr.clone(), id));
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_operation")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn execute_execute_operation(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    id: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let operation = TIMELOCKED_OPERATIONS.load(deps.storage, (pool_addr.clone(), id))?;
+    if env.block.time.seconds() < operation.eta {
+        return Err(ContractError::TimelockNotExpired {}.into());
+    }
+    TIMELOCKED_OPERATIONS.remove(deps.storage, (pool_addr.clone(), id));
+
+    // runs on behalf of the proposer, so a revoked role or a changed admin can't execute it,
+    // the funds sent along still pay the ibc fee
+    let proposer_info = MessageInfo {
+        sender: operation.proposer,
+        funds: info.funds,
+    };
+    let resp = match operation.operation {
+        TimelockOperation::AdminTransferFunds { receiver, amount } => {
+            admin_transfer_funds(deps, proposer_info, pool_addr.clone(), receiver, amount)?
+        }
+        TimelockOperation::ConfigPool(param) => config_pool(deps, proposer_info, env, *param)?,
+        TimelockOperation::SetTimelockDelay { delay_seconds } => {
+            let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+            pool_info.authorize(&proposer_info.sender)?;
+            TIMELOCK_DELAYS.save(deps.storage, pool_addr.clone(), &delay_seconds)?;
+            Response::new().add_attribute("delay_seconds", delay_seconds.to_string())
+        }
+    };
+
+    Ok(resp
+        .add_attribute("action", "execute_operation")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn execute_config_timelock_delay(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    delay_seconds: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+
+    // shortening the delay would let a queued operation skip it
+    if delay_seconds < load_timelock_delay(deps.storage, pool_addr.clone())? {
+        return Err(ContractError::TimelockRequired {}.into());
+    }
+    TIMELOCK_DELAYS.save(deps.storage, pool_addr.clone(), &delay_seconds)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "config_timelock_delay")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("delay_seconds", delay_seconds.to_string()))
+}
@@ -1,8 +1,11 @@
 use std::ops::{Div, Mul, Sub};
 use std::vec;
 
+use crate::execute_referral::release_referral;
+use crate::helper::platform_fee_shares;
 use crate::state::{
-    UnstakeInfo, WithdrawStatus, POOLS, UNSTAKES_INDEX_FOR_USER, UNSTAKES_OF_INDEX,
+    UnstakeInfo, WithdrawStatus, POOLS, TICKETED_UNSTAKES, UNSTAKES_OF_INDEX, UNSTAKES_OF_USER,
+    UNSTAKE_INDEX_OF_ERA, UNSTAKE_TICKETS,
 };
 use crate::{error_conversion::ContractError, helper::CAL_BASE};
 use cosmwasm_std::{to_json_binary, CosmosMsg, DepsMut, MessageInfo, Response, Uint128, WasmMsg};
@@ -11,6 +14,7 @@ use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
 };
+use unstake_ticket::msg::TicketExtension;
 
 // Before this step, need the user to authorize burn from
 pub fn execute_unstake(
@@ -29,17 +33,6 @@ pub fn execute_unstake(
         return Err(ContractError::PoolIsPaused {}.into());
     }
 
-    let mut unstakes_index_for_user = UNSTAKES_INDEX_FOR_USER
-        .load(deps.storage, (info.sender.clone(), pool_addr.clone()))
-        .unwrap_or_else(|_| vec![]);
-
-    let unstake_count = unstakes_index_for_user.len() as u64;
-
-    let unstake_limit = pool_info.unstake_times_limit;
-    if unstake_count >= unstake_limit {
-        return Err(ContractError::EncodeErrUnstakeTimesLimitReached {}.into());
-    }
-
     let mut rsp = Response::new();
     // cal fee
     let mut will_burn_lsd_token_amount = lsd_token_amount;
@@ -50,19 +43,23 @@ pub fn execute_unstake(
         will_burn_lsd_token_amount = lsd_token_amount.sub(cms_fee);
 
         if cms_fee.u128() > 0 {
-            let transfer_cms_fee_msg = WasmMsg::Execute {
-                contract_addr: pool_info.lsd_token.to_string(),
-                msg: to_json_binary(
-                    &(Cw20ExecuteMsg::TransferFrom {
-                        owner: info.sender.to_string(),
-                        recipient: pool_info.platform_fee_receiver.to_string(),
-                        amount: cms_fee,
-                    }),
-                )?,
-                funds: vec![],
-            };
-
-            rsp = rsp.add_message(transfer_cms_fee_msg);
+            for (recipient, amount) in
+                platform_fee_shares(deps.storage, &pool_info, pool_addr.clone(), cms_fee)?
+            {
+                let transfer_cms_fee_msg = WasmMsg::Execute {
+                    contract_addr: pool_info.lsd_token.to_string(),
+                    msg: to_json_binary(
+                        &(Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: recipient.to_string(),
+                            amount,
+                        }),
+                    )?,
+                    funds: vec![],
+                };
+
+                rsp = rsp.add_message(transfer_cms_fee_msg);
+            }
         }
     }
     if will_burn_lsd_token_amount.is_zero() {
@@ -73,7 +70,6 @@ pub fn execute_unstake(
     let token_amount = will_burn_lsd_token_amount.mul(pool_info.rate).div(CAL_BASE);
 
     // update pool info
-    pool_info.next_unstake_index += 1;
     pool_info.unbond += token_amount;
     pool_info.active -= token_amount;
 
@@ -102,18 +98,49 @@ pub fn execute_unstake(
         return Err(ContractError::EncodeErrZeroWithdrawAmount {}.into());
     }
 
-    // update unstake info
-    let will_use_unstake_index = pool_info.next_unstake_index;
-    let unstake_info = UnstakeInfo {
-        era: pool_info.era,
-        pool_addr: pool_addr.clone(),
-        unstaker: info.sender.to_string(),
-        amount: receive_amount,
-        status: WithdrawStatus::Default,
-        index: will_use_unstake_index,
+    // update unstake info, unstakes in the same era add up in one claim
+    // a ticket can change hands, so ticketed unstakes are never merged
+    let unstake_ticket = UNSTAKE_TICKETS.may_load(deps.storage, pool_addr.clone())?;
+    let era_key = (info.sender.clone(), pool_addr.clone(), pool_info.era);
+    let era_unstake = match UNSTAKE_INDEX_OF_ERA.may_load(deps.storage, era_key.clone())? {
+        Some(index) if unstake_ticket.is_none() => UNSTAKES_OF_INDEX
+            .may_load(deps.storage, (pool_addr.clone(), index))?
+            .filter(|unstake_info| unstake_info.status == WithdrawStatus::Default),
+        _ => None,
     };
+    let unstake_info = match era_unstake {
+        Some(mut unstake_info) => {
+            unstake_info.amount += receive_amount;
+            unstake_info.unbond_amount += token_amount;
+            unstake_info
+        }
+        None => {
+            pool_info.next_unstake_index += 1;
+            UNSTAKES_OF_USER.save(
+                deps.storage,
+                (
+                    info.sender.clone(),
+                    pool_addr.clone(),
+                    (pool_info.era, pool_info.next_unstake_index),
+                ),
+                &(),
+            )?;
+            if unstake_ticket.is_none() {
+                UNSTAKE_INDEX_OF_ERA.save(deps.storage, era_key, &pool_info.next_unstake_index)?;
+            }
 
-    unstakes_index_for_user.push(will_use_unstake_index);
+            UnstakeInfo {
+                era: pool_info.era,
+                pool_addr: pool_addr.clone(),
+                unstaker: info.sender.to_string(),
+                amount: receive_amount,
+                status: WithdrawStatus::Default,
+                index: pool_info.next_unstake_index,
+                unbond_amount: token_amount,
+            }
+        }
+    };
+    let will_use_unstake_index = unstake_info.index;
 
     UNSTAKES_OF_INDEX.save(
         deps.storage,
@@ -121,12 +148,35 @@ pub fn execute_unstake(
         &unstake_info,
     )?;
     POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
-    UNSTAKES_INDEX_FOR_USER.save(
+    release_referral(
         deps.storage,
-        (info.sender.clone(), pool_addr.clone()),
-        &unstakes_index_for_user,
+        pool_addr.clone(),
+        &info.sender,
+        lsd_token_amount,
     )?;
 
+    if let Some(unstake_ticket) = unstake_ticket {
+        TICKETED_UNSTAKES.save(
+            deps.storage,
+            (pool_addr.clone(), will_use_unstake_index),
+            &unstake_ticket,
+        )?;
+        rsp = rsp.add_message(WasmMsg::Execute {
+            contract_addr: unstake_ticket.to_string(),
+            msg: to_json_binary(&unstake_ticket::msg::ExecuteMsg::Mint {
+                token_id: will_use_unstake_index.to_string(),
+                owner: info.sender.to_string(),
+                token_uri: None,
+                extension: TicketExtension {
+                    pool_addr: pool_addr.clone(),
+                    era: unstake_info.era,
+                    amount: unstake_info.amount,
+                },
+            })?,
+            funds: vec![],
+        });
+    }
+
     // send event
     Ok(rsp
         .add_message(CosmosMsg::Wasm(burn_msg))
@@ -137,3 +187,173 @@ pub fn execute_unstake(
         .add_attribute("lsd_token_amount", lsd_token_amount.to_string())
         .add_attribute("unstake_index", will_use_unstake_index.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, Addr, Order, StdResult, Uint128};
+
+    use super::execute_unstake;
+    use crate::execute_withdraw::execute_withdraw_eras;
+    use crate::helper::FEE_DENOM;
+    use crate::state::{
+        PoolInfo, WithdrawStatus, POOLS, UNSTAKES_OF_INDEX, UNSTAKES_OF_USER, UNSTAKE_INDEX_OF_ERA,
+        UNSTAKE_TICKETS,
+    };
+    use crate::testing::{
+        mock_neutron_dependencies, relay_packet, save_pool, submitted_msg_sends, NeutronDeps,
+        ACK_FEE, POOL_ADDR, POOL_CHANNEL_ID, TIMEOUT_FEE,
+    };
+    use crate::tx_callback::sudo_response;
+
+    fn unstake(deps: &mut NeutronDeps, user: &str, lsd_token_amount: u128) -> String {
+        let resp = execute_unstake(
+            deps.as_mut(),
+            mock_info(user, &[]),
+            Uint128::new(lsd_token_amount),
+            POOL_ADDR.to_string(),
+        )
+        .unwrap();
+        resp.attributes
+            .iter()
+            .find(|attr| attr.key == "unstake_index")
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    fn set_era(deps: &mut NeutronDeps, era: u64) {
+        let mut pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        pool_info.era = era;
+        POOLS
+            .save(deps.as_mut().storage, POOL_ADDR.to_string(), &pool_info)
+            .unwrap();
+    }
+
+    fn user_indexes(deps: &NeutronDeps, user: &str) -> Vec<u64> {
+        UNSTAKES_OF_USER
+            .prefix((Addr::unchecked(user), POOL_ADDR.to_string()))
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .map(|key| key.map(|(_, unstake_index)| unstake_index))
+            .collect::<StdResult<Vec<u64>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unstakes_combined_per_era() {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                lsd_token: Addr::unchecked("lsd_token"),
+                remote_denom: "uatom".to_string(),
+                rate: Uint128::new(1_000_000),
+                era: 3,
+                unbonding_period: 2,
+                active: Uint128::new(10_000),
+                total_lsd_token_amount: Uint128::new(10_000),
+                ..PoolInfo::default()
+            },
+        );
+
+        // each unstake keeps its own precision margin back
+        for lsd_token_amount in [100, 200, 300] {
+            assert_eq!(unstake(&mut deps, "user", lsd_token_amount), "1");
+        }
+        assert_eq!(unstake(&mut deps, "other", 100), "2");
+        let unstake_info = UNSTAKES_OF_INDEX
+            .load(deps.as_ref().storage, (POOL_ADDR.to_string(), 1))
+            .unwrap();
+        assert_eq!(unstake_info.era, 3);
+        assert_eq!(unstake_info.unstaker, "user");
+        assert_eq!(unstake_info.amount, Uint128::new(585));
+        assert_eq!(unstake_info.unbond_amount, Uint128::new(600));
+        assert_eq!(user_indexes(&deps, "user"), vec![1]);
+        assert_eq!(user_indexes(&deps, "other"), vec![2]);
+
+        // a new era opens a new claim
+        set_era(&mut deps, 4);
+        assert_eq!(unstake(&mut deps, "user", 50), "3");
+        assert_eq!(user_indexes(&deps, "user"), vec![1, 3]);
+        let pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(pool_info.unbond, Uint128::new(750));
+        assert_eq!(pool_info.active, Uint128::new(9_250));
+        assert_eq!(pool_info.total_lsd_token_amount, Uint128::new(9_250));
+
+        // withdraw claims eras 3..=3 only, era 4 isn't matured yet
+        set_era(&mut deps, 5);
+        let resp = execute_withdraw_eras(
+            deps.as_mut(),
+            mock_info("user", &coins(ACK_FEE + TIMEOUT_FEE, FEE_DENOM)),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+            0,
+            3,
+        )
+        .unwrap();
+        let sends = submitted_msg_sends(&resp.messages[0]);
+        assert_eq!(sends[0].to_address, "cosmos1receiver");
+        assert_eq!(sends[0].amount[0].amount, "585");
+        let unstake_info = UNSTAKES_OF_INDEX
+            .load(deps.as_ref().storage, (POOL_ADDR.to_string(), 1))
+            .unwrap();
+        assert_eq!(unstake_info.status, WithdrawStatus::Pending);
+        assert!(execute_withdraw_eras(
+            deps.as_mut(),
+            mock_info("user", &coins(ACK_FEE + TIMEOUT_FEE, FEE_DENOM)),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+            4,
+            10,
+        )
+        .is_err());
+
+        // a pending claim isn't added to
+        set_era(&mut deps, 3);
+        assert_eq!(unstake(&mut deps, "user", 100), "4");
+        set_era(&mut deps, 5);
+
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            1,
+        );
+        sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        assert!(!UNSTAKES_OF_INDEX.has(deps.as_ref().storage, (POOL_ADDR.to_string(), 1)));
+        // listed in era order
+        assert_eq!(user_indexes(&deps, "user"), vec![4, 3]);
+        let era_index = |deps: &NeutronDeps, era: u64| {
+            UNSTAKE_INDEX_OF_ERA
+                .may_load(
+                    deps.as_ref().storage,
+                    (Addr::unchecked("user"), POOL_ADDR.to_string(), era),
+                )
+                .unwrap()
+        };
+        assert_eq!(era_index(&deps, 3), Some(4));
+        assert_eq!(era_index(&deps, 4), Some(3));
+
+        // ticketed unstakes can change hands, each gets its own claim
+        UNSTAKE_TICKETS
+            .save(
+                deps.as_mut().storage,
+                POOL_ADDR.to_string(),
+                &Addr::unchecked("ticket"),
+            )
+            .unwrap();
+        assert_eq!(unstake(&mut deps, "user", 100), "5");
+        assert_eq!(unstake(&mut deps, "user", 100), "6");
+        assert_eq!(era_index(&deps, 5), None);
+    }
+}
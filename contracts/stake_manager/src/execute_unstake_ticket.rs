@@ -0,0 +1,62 @@
+use cosmwasm_std::{instantiate2_address, to_json_binary, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{StdError, WasmMsg};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronError, NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::state::{POOLS, UNSTAKE_TICKETS, UNSTAKE_TICKET_CODE_ID};
+
+// instantiates the pool's unstake ticket contract, unstakes of the pool are minted into it from then on
+pub fn execute_enable_unstake_ticket(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    name: String,
+    symbol: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    pool_info.authorize(&info.sender)?;
+
+    if UNSTAKE_TICKETS.has(deps.storage, pool_addr.clone()) {
+        return Err(ContractError::UnstakeTicketEnabled {}.into());
+    }
+    let code_id = UNSTAKE_TICKET_CODE_ID
+        .may_load(deps.storage)?
+        .ok_or(ContractError::UnstakeTicketCodeIdNotSet {})?;
+
+    let salt = &pool_addr[..40];
+    let code_info = deps.querier.query_wasm_code_info(code_id)?;
+    let creator_cannonical = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let i2_address =
+        instantiate2_address(&code_info.checksum, &creator_cannonical, salt.as_bytes())
+            .map_err(|e| ContractError::Instantiate2AddressFailed(e.to_string()))?;
+    let contract_addr = deps
+        .api
+        .addr_humanize(&i2_address)
+        .map_err(NeutronError::Std)?;
+
+    UNSTAKE_TICKETS.save(deps.storage, pool_addr.clone(), &contract_addr)?;
+
+    let instantiate_msg = WasmMsg::Instantiate2 {
+        admin: Option::from(info.sender.to_string()),
+        code_id,
+        msg: to_json_binary(&unstake_ticket::msg::InstantiateMsg {
+            name: name.clone(),
+            symbol,
+            minter: env.contract.address.to_string(),
+        })
+        .map_err(StdError::from)?,
+        funds: vec![],
+        label: name,
+        salt: salt.as_bytes().into(),
+    };
+
+    Ok(Response::new()
+        .add_message(instantiate_msg)
+        .add_attribute("action", "enable_unstake_ticket")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("unstake_ticket", contract_addr))
+}
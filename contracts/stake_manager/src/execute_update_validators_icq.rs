@@ -8,7 +8,7 @@ use neutron_sdk::{
 use crate::error_conversion::ContractError;
 use crate::helper::deal_validators_icq_update;
 use crate::state::INFO_OF_ICA_ID;
-use crate::state::{ValidatorUpdateStatus, POOLS};
+use crate::state::{Role, ValidatorUpdateStatus, POOLS};
 
 pub fn execute_update_validators_icq(
     deps: DepsMut<NeutronQuery>,
@@ -17,7 +17,12 @@ pub fn execute_update_validators_icq(
     pool_addr: String,
 ) -> NeutronResult<Response<NeutronMsg>> {
     let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
-    pool_info.authorize(&info.sender)?;
+    pool_info.authorize_role(
+        deps.storage,
+        pool_addr.clone(),
+        Role::ValidatorManager,
+        &info.sender,
+    )?;
 
     if pool_info.validator_update_status != ValidatorUpdateStatus::WaitQueryUpdate {
         return Err(ContractError::StatusNotAllow {}.into());
@@ -0,0 +1,854 @@
+use std::ops::{Add, Sub};
+
+use cosmwasm_std::{coins, to_json_binary, Addr, BankMsg, Deps, DepsMut, Env, MessageInfo, Order};
+use cosmwasm_std::{Response, StdResult, Storage, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+use crate::error_conversion::ContractError;
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::helper::{gen_msg_send, get_update_pool_icq_msgs, get_withdraw_ica_id};
+use crate::helper::{DEFAULT_FAST_PERIOD, DEFAULT_TIMEOUT_SECONDS};
+use crate::query::query_balance_by_addr;
+use crate::state::{PoolInfo, SudoPayload, TxPayload, WindDown, WindDownStatus, WithdrawStatus};
+use crate::state::{INFO_OF_ICA_ID, POOLS, UNSTAKES_OF_INDEX, WIND_DOWNS};
+use crate::tx_callback::msg_with_fee_refund;
+
+// called when admin_unbond_all is acked, a retried unbond all restarts the wait
+pub fn start_wind_down(store: &mut dyn Storage, env: &Env, pool_addr: String) -> StdResult<()> {
+    if let Some(wind_down) = WIND_DOWNS.may_load(store, pool_addr.clone())? {
+        if wind_down.status != WindDownStatus::Unbonding {
+            return Ok(());
+        }
+    }
+    let pool_info = POOLS.load(store, pool_addr.clone())?;
+
+    WIND_DOWNS.save(
+        store,
+        pool_addr,
+        &WindDown {
+            status: WindDownStatus::Unbonding,
+            unbonded_at: env.block.time.seconds()
+                + pool_info.unbonding_period * pool_info.era_seconds,
+            snapshot_height: 0,
+            sweeping: false,
+            host_amount: Uint128::zero(),
+            neutron_amount: Uint128::zero(),
+            lsd_supply: Uint128::zero(),
+            exiting_lsd: Uint128::zero(),
+            exited_lsd: Uint128::zero(),
+        },
+    )
+}
+
+// open unstakes can be withdrawn at once when the pool winds down, their eras will never come
+pub fn unstakes_released(store: &dyn Storage, pool_addr: String) -> StdResult<bool> {
+    Ok(WIND_DOWNS
+        .may_load(store, pool_addr)?
+        .is_some_and(|wind_down| wind_down.status != WindDownStatus::Unbonding))
+}
+
+// anyone can take the exit snapshot once the unbonding is over, in two calls like era_recover,
+// with a sweep of the withdraw ica in between when it holds rewards
+pub fn execute_wind_down_snapshot(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let mut wind_down = WIND_DOWNS
+        .may_load(deps.storage, pool_addr.clone())?
+        .ok_or(ContractError::WindDownNotStarted {})?;
+    if wind_down.status != WindDownStatus::Unbonding {
+        return Err(ContractError::StatusNotAllow {}.into());
+    }
+    if env.block.time.seconds() < wind_down.unbonded_at {
+        return Err(ContractError::WindDownUnbonding {}.into());
+    }
+    if wind_down.sweeping {
+        return Err(ContractError::WindDownSweeping {}.into());
+    }
+
+    // ask for ica balances that include the unbonded tokens and decide on them next call
+    if wind_down.snapshot_height == 0 {
+        wind_down.snapshot_height = env.block.height;
+        WIND_DOWNS.save(deps.storage, pool_addr.clone(), &wind_down)?;
+
+        let update_pool_icq_msgs = get_update_pool_icq_msgs(
+            deps.branch(),
+            pool_addr.clone(),
+            pool_info.ica_id.clone(),
+            DEFAULT_FAST_PERIOD,
+        )?;
+
+        return Ok(Response::new()
+            .add_messages(update_pool_icq_msgs)
+            .add_attribute("action", "wind_down_snapshot")
+            .add_attribute("pool", pool_addr)
+            .add_attribute("snapshot_height", env.block.height.to_string()));
+    }
+
+    // open unstakes are paid by withdraw as before, a pending one may or may not have left yet
+    let mut reserved = Uint128::zero();
+    for item in UNSTAKES_OF_INDEX.prefix(pool_addr.clone()).range(
+        deps.storage,
+        None,
+        None,
+        Order::Ascending,
+    ) {
+        let (_, unstake_info) = item?;
+        if unstake_info.status == WithdrawStatus::Pending {
+            return Err(ContractError::WindDownPendingWithdraw {}.into());
+        }
+        reserved = reserved.add(unstake_info.amount);
+    }
+
+    let (pool_ica_info, withdraw_ica_info, _) =
+        INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+    let balance = fresh_balance(deps.as_ref(), &pool_info, &wind_down, pool_addr.clone())?;
+    let withdraw_balance = fresh_balance(
+        deps.as_ref(),
+        &pool_info,
+        &wind_down,
+        withdraw_ica_info.ica_addr.clone(),
+    )?;
+
+    // exits are paid by the pool ica, so the rewards on the withdraw ica move there first
+    if !withdraw_balance.is_zero() {
+        let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+        let cosmos_msg = NeutronMsg::submit_tx(
+            withdraw_ica_info.ctrl_connection_id.clone(),
+            get_withdraw_ica_id(pool_info.ica_id.clone()),
+            vec![gen_msg_send(
+                withdraw_ica_info.ica_addr.clone(),
+                pool_addr.clone(),
+                pool_info.remote_denom.clone(),
+                withdraw_balance.to_string(),
+            )?],
+            "".to_string(),
+            DEFAULT_TIMEOUT_SECONDS,
+            ibc_fee.clone(),
+        );
+        let submsg = msg_with_fee_refund(
+            deps.branch(),
+            cosmos_msg,
+            SudoPayload {
+                port_id: withdraw_ica_info.ctrl_port_id,
+                message: TxPayload::WindDownSweep {
+                    amount: withdraw_balance,
+                },
+                pool_addr: pool_addr.clone(),
+            },
+            &ibc_fee,
+        )?;
+
+        wind_down.sweeping = true;
+        WIND_DOWNS.save(deps.storage, pool_addr.clone(), &wind_down)?;
+
+        return Ok(Response::new()
+            .add_submessage(submsg)
+            .add_attribute("action", "wind_down_sweep")
+            .add_attribute("pool", pool_addr)
+            .add_attribute("from", withdraw_ica_info.ica_addr)
+            .add_attribute("to", pool_ica_info.ica_addr)
+            .add_attribute("amount", withdraw_balance));
+    }
+
+    wind_down.status = WindDownStatus::Exiting;
+    wind_down.host_amount = balance.saturating_sub(reserved);
+    wind_down.neutron_amount = pool_info.bond;
+    wind_down.lsd_supply = pool_info.total_lsd_token_amount;
+    if wind_down.lsd_supply.is_zero() {
+        wind_down.status = WindDownStatus::Closed;
+    }
+    WIND_DOWNS.save(deps.storage, pool_addr.clone(), &wind_down)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "wind_down_snapshot")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("host_amount", wind_down.host_amount)
+        .add_attribute("neutron_amount", wind_down.neutron_amount)
+        .add_attribute("reserved", reserved)
+        .add_attribute("lsd_supply", wind_down.lsd_supply))
+}
+
+// the remote denom balance of an ica from a balances icq result submitted after the snapshot height
+fn fresh_balance(
+    deps: Deps<NeutronQuery>,
+    pool_info: &PoolInfo,
+    wind_down: &WindDown,
+    ica_addr: String,
+) -> NeutronResult<Uint128> {
+    let balance_resp = query_balance_by_addr(deps, ica_addr, pool_info.sdk_greater_or_equal_v047)?;
+    if balance_resp.last_submitted_local_height <= wind_down.snapshot_height {
+        return Err(ContractError::WindDownSnapshotHeight {}.into());
+    }
+
+    Ok(balance_resp
+        .balances
+        .coins
+        .iter()
+        .find(|c| c.denom == pool_info.remote_denom)
+        .map(|c| c.amount)
+        .unwrap_or_default())
+}
+
+// the swept rewards are on the pool ica now, the snapshot waits for a balance that shows them
+pub fn sudo_wind_down_sweep_callback(
+    deps: DepsMut,
+    env: Env,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::WindDownSweep { amount } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut wind_down = WIND_DOWNS.load(deps.storage, payload.pool_addr.clone())?;
+    wind_down.sweeping = false;
+    wind_down.snapshot_height = env.block.height;
+    WIND_DOWNS.save(deps.storage, payload.pool_addr.clone(), &wind_down)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "wind_down_sweep_callback")
+        .add_attribute("pool", payload.pool_addr)
+        .add_attribute("amount", amount))
+}
+
+// the next snapshot call sees the withdraw ica balance again and retries the sweep
+pub fn sudo_wind_down_sweep_failed_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let mut wind_down = WIND_DOWNS.load(deps.storage, payload.pool_addr.clone())?;
+    wind_down.sweeping = false;
+    WIND_DOWNS.save(deps.storage, payload.pool_addr.clone(), &wind_down)?;
+
+    Ok(Response::new())
+}
+
+// Before this step, need the user to authorize burn from
+pub fn execute_wind_down_exit(
+    mut deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    lsd_token_amount: Uint128,
+    receiver: Addr,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if lsd_token_amount.is_zero() {
+        return Err(ContractError::EncodeErrLsdTokenAmountZero {}.into());
+    }
+
+    let mut pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let mut wind_down = WIND_DOWNS
+        .may_load(deps.storage, pool_addr.clone())?
+        .ok_or(ContractError::WindDownNotStarted {})?;
+    if wind_down.status != WindDownStatus::Exiting {
+        return Err(ContractError::StatusNotAllow {}.into());
+    }
+
+    // every lsd token gets the same share of the snapshot, whenever it exits
+    let token_amount = wind_down
+        .host_amount
+        .multiply_ratio(lsd_token_amount, wind_down.lsd_supply);
+    let neutron_amount = wind_down
+        .neutron_amount
+        .multiply_ratio(lsd_token_amount, wind_down.lsd_supply);
+    if token_amount.is_zero() {
+        return Err(ContractError::EncodeErrZeroWithdrawAmount {}.into());
+    }
+
+    wind_down.exiting_lsd = wind_down.exiting_lsd.add(lsd_token_amount);
+    pool_info.total_lsd_token_amount = pool_info.total_lsd_token_amount.sub(lsd_token_amount);
+
+    let burn_msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::BurnFrom {
+            owner: info.sender.to_string(),
+            amount: lsd_token_amount,
+        })?,
+        funds: vec![],
+    };
+
+    let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+    let cosmos_msg = NeutronMsg::submit_tx(
+        pool_ica_info.ctrl_connection_id.clone(),
+        pool_info.ica_id.clone(),
+        vec![gen_msg_send(
+            pool_addr.clone(),
+            receiver.to_string(),
+            pool_info.remote_denom.clone(),
+            token_amount.to_string(),
+        )?],
+        "".to_string(),
+        DEFAULT_TIMEOUT_SECONDS,
+        ibc_fee.clone(),
+    );
+
+    let submsg = msg_with_fee_refund(
+        deps.branch(),
+        cosmos_msg,
+        SudoPayload {
+            port_id: pool_ica_info.ctrl_port_id,
+            message: TxPayload::WindDownExit {
+                user: info.sender.clone(),
+                receiver: receiver.to_string(),
+                lsd_token_amount,
+                token_amount,
+                neutron_amount,
+            },
+            pool_addr: pool_addr.clone(),
+        },
+        &ibc_fee,
+    )?;
+
+    POOLS.save(deps.storage, pool_addr.clone(), &pool_info)?;
+    WIND_DOWNS.save(deps.storage, pool_addr.clone(), &wind_down)?;
+
+    Ok(Response::new()
+        .add_message(burn_msg)
+        .add_submessage(submsg)
+        .add_attribute("action", "wind_down_exit")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("from", info.sender)
+        .add_attribute("receiver", receiver)
+        .add_attribute("lsd_token_amount", lsd_token_amount)
+        .add_attribute("token_amount", token_amount)
+        .add_attribute("neutron_amount", neutron_amount))
+}
+
+// the stake left on neutron is paid once the host chain part has arrived
+pub fn sudo_wind_down_exit_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::WindDownExit {
+        user,
+        receiver,
+        lsd_token_amount,
+        token_amount,
+        neutron_amount,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    let mut wind_down = WIND_DOWNS.load(deps.storage, payload.pool_addr.clone())?;
+    wind_down.exiting_lsd = wind_down.exiting_lsd.sub(lsd_token_amount);
+    wind_down.exited_lsd = wind_down.exited_lsd.add(lsd_token_amount);
+    if wind_down.exited_lsd >= wind_down.lsd_supply {
+        wind_down.status = WindDownStatus::Closed;
+    }
+    WIND_DOWNS.save(deps.storage, payload.pool_addr.clone(), &wind_down)?;
+
+    let mut resp = Response::new();
+    if !neutron_amount.is_zero() {
+        resp = resp.add_message(BankMsg::Send {
+            to_address: user.to_string(),
+            amount: coins(neutron_amount.u128(), pool_info.ibc_denom),
+        });
+    }
+
+    Ok(resp
+        .add_attribute("action", "wind_down_exit_callback")
+        .add_attribute("pool", payload.pool_addr)
+        .add_attribute("from", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("token_amount", token_amount)
+        .add_attribute("lsd_token_amount", lsd_token_amount)
+        .add_attribute(
+            "pool_closed",
+            (wind_down.status == WindDownStatus::Closed).to_string(),
+        ))
+}
+
+// nothing left the pool ica, so mint the lsd token back
+pub fn sudo_wind_down_exit_failed_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::WindDownExit {
+        user,
+        lsd_token_amount,
+        ..
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut pool_info = POOLS.load(deps.storage, payload.pool_addr.clone())?;
+    let mut wind_down = WIND_DOWNS.load(deps.storage, payload.pool_addr.clone())?;
+    wind_down.exiting_lsd = wind_down.exiting_lsd.sub(lsd_token_amount);
+    pool_info.total_lsd_token_amount = pool_info.total_lsd_token_amount.add(lsd_token_amount);
+
+    let msg = WasmMsg::Execute {
+        contract_addr: pool_info.lsd_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+            recipient: user.to_string(),
+            amount: lsd_token_amount,
+        })?,
+        funds: vec![],
+    };
+
+    POOLS.save(deps.storage, payload.pool_addr.clone(), &pool_info)?;
+    WIND_DOWNS.save(deps.storage, payload.pool_addr.clone(), &wind_down)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "wind_down_exit_failed_callback")
+        .add_attribute("pool", payload.pool_addr)
+        .add_attribute("from", user)
+        .add_attribute("lsd_token_amount", lsd_token_amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info, MockQuerier};
+    use cosmwasm_std::{
+        coins, from_json, Addr, BankMsg, CosmosMsg, Env, Response, Uint128, WasmMsg,
+    };
+    use cw20::Cw20ExecuteMsg;
+    use neutron_sdk::bindings::{msg::NeutronMsg, query::NeutronQuery};
+    use neutron_sdk::NeutronResult;
+
+    use super::{execute_wind_down_exit, execute_wind_down_snapshot, start_wind_down};
+    use crate::helper::FEE_DENOM;
+    use crate::state::{
+        PoolInfo, UnstakeInfo, WindDownStatus, WithdrawStatus, FEE_VAULTS, POOLS,
+        UNSTAKES_OF_INDEX, WIND_DOWNS,
+    };
+    use crate::testing::{
+        balances_kv, mock_neutron_dependencies, mock_neutron_querier, register_pool_queries,
+        relay_packet, save_pool, submitted_msg_sends, NeutronDeps, ACK_FEE, POOL_ADDR,
+        POOL_BALANCES_QUERY_ID, POOL_CHANNEL_ID, TIMEOUT_FEE, WITHDRAW_ADDR,
+        WITHDRAW_BALANCES_QUERY_ID, WITHDRAW_CHANNEL_ID,
+    };
+    use crate::tx_callback::{sudo_error, sudo_response};
+
+    const DAY: u64 = 86400;
+
+    fn lsd_token_msg(msg: &CosmosMsg<NeutronMsg>) -> Cw20ExecuteMsg {
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = msg else {
+            panic!("not a lsd token msg: {:?}", msg);
+        };
+        from_json(msg).unwrap()
+    }
+
+    // 3000 lsd on a pool ica holding 10000 uatom, 1000 of it for an open unstake, and 500 stake on neutron
+    fn exiting_pool() -> NeutronDeps {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                era_seconds: DAY,
+                unbonding_period: 4,
+                bond: Uint128::new(500),
+                total_lsd_token_amount: Uint128::new(3_000),
+                lsd_token: Addr::unchecked("lsd_token"),
+                remote_denom: "uatom".to_string(),
+                ibc_denom: "ibc/ATOM".to_string(),
+                ..PoolInfo::default()
+            },
+        );
+        register_pool_queries(deps.as_mut().storage);
+        UNSTAKES_OF_INDEX
+            .save(
+                deps.as_mut().storage,
+                (POOL_ADDR.to_string(), 0),
+                &UnstakeInfo {
+                    era: 1,
+                    pool_addr: POOL_ADDR.to_string(),
+                    unstaker: "unstaker".to_string(),
+                    amount: Uint128::new(1_000),
+                    status: WithdrawStatus::Default,
+                    index: 0,
+                    unbond_amount: Uint128::new(1_000),
+                },
+            )
+            .unwrap();
+        FEE_VAULTS
+            .save(
+                deps.as_mut().storage,
+                POOL_ADDR.to_string(),
+                &Uint128::new(10_000),
+            )
+            .unwrap();
+
+        let mut env = mock_env();
+        start_wind_down(deps.as_mut().storage, &env, POOL_ADDR.to_string()).unwrap();
+        env.block.time = env.block.time.plus_seconds(4 * DAY);
+        snapshot(&mut deps, &env).unwrap();
+        deps.querier = balances_at(env.block.height + 1, 10_000, 0);
+        snapshot(&mut deps, &env).unwrap();
+        deps
+    }
+
+    fn snapshot(deps: &mut NeutronDeps, env: &Env) -> NeutronResult<Response<NeutronMsg>> {
+        execute_wind_down_snapshot(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("keeper", &[]),
+            POOL_ADDR.to_string(),
+        )
+    }
+
+    // uatom balances of the pool and withdraw icas, submitted at height
+    fn balances_at(
+        height: u64,
+        pool_amount: u128,
+        withdraw_amount: u128,
+    ) -> MockQuerier<NeutronQuery> {
+        mock_neutron_querier(vec![
+            (
+                POOL_BALANCES_QUERY_ID,
+                height,
+                balances_kv(&[("uatom", pool_amount), ("uother", 7)]),
+            ),
+            (
+                WITHDRAW_BALANCES_QUERY_ID,
+                height,
+                balances_kv(&[("uatom", withdraw_amount)]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_wind_down_snapshot() {
+        let mut deps = exiting_pool();
+        // rebuild the unbonding pool and walk the snapshot again
+        WIND_DOWNS.remove(deps.as_mut().storage, POOL_ADDR.to_string());
+        let mut env = mock_env();
+        start_wind_down(deps.as_mut().storage, &env, POOL_ADDR.to_string()).unwrap();
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.status, WindDownStatus::Unbonding);
+        assert_eq!(wind_down.unbonded_at, env.block.time.seconds() + 4 * DAY);
+
+        // the unbonding isn't over
+        env.block.time = env.block.time.plus_seconds(4 * DAY - 1);
+        assert!(snapshot(&mut deps, &env).is_err());
+
+        // the first call asks the icqs for a fresh balance
+        env.block.time = env.block.time.plus_seconds(1);
+        let resp = snapshot(&mut deps, &env).unwrap();
+        assert_eq!(resp.messages.len(), 4);
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.status, WindDownStatus::Unbonding);
+        assert_eq!(wind_down.snapshot_height, env.block.height);
+
+        // a result from before the request isn't used
+        deps.querier = balances_at(env.block.height, 10_000, 0);
+        env.block.height += 10;
+        assert!(snapshot(&mut deps, &env).is_err());
+
+        // nor one of the withdraw ica from before it
+        deps.querier = mock_neutron_querier(vec![
+            (
+                POOL_BALANCES_QUERY_ID,
+                env.block.height,
+                balances_kv(&[("uatom", 10_000)]),
+            ),
+            (
+                WITHDRAW_BALANCES_QUERY_ID,
+                env.block.height - 10,
+                balances_kv(&[]),
+            ),
+        ]);
+        assert!(snapshot(&mut deps, &env).is_err());
+
+        deps.querier = balances_at(env.block.height, 10_000, 0);
+        snapshot(&mut deps, &env).unwrap();
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.status, WindDownStatus::Exiting);
+        // the open unstake keeps its part
+        assert_eq!(wind_down.host_amount, Uint128::new(9_000));
+        assert_eq!(wind_down.neutron_amount, Uint128::new(500));
+        assert_eq!(wind_down.lsd_supply, Uint128::new(3_000));
+
+        // taken once
+        assert!(snapshot(&mut deps, &env).is_err());
+    }
+
+    // the unbonding is over and the snapshot has asked for fresh balances
+    fn snapshot_requested() -> (NeutronDeps, Env) {
+        let mut deps = exiting_pool();
+        WIND_DOWNS.remove(deps.as_mut().storage, POOL_ADDR.to_string());
+        let mut env = mock_env();
+        start_wind_down(deps.as_mut().storage, &env, POOL_ADDR.to_string()).unwrap();
+        env.block.time = env.block.time.plus_seconds(4 * DAY);
+        snapshot(&mut deps, &env).unwrap();
+        env.block.height += 1;
+        (deps, env)
+    }
+
+    #[test]
+    fn test_wind_down_snapshot_sweeps_withdraw_ica() {
+        let (mut deps, mut env) = snapshot_requested();
+
+        // the rewards on the withdraw ica go to the pool ica before the snapshot
+        deps.querier = balances_at(env.block.height, 10_000, 300);
+        let resp = snapshot(&mut deps, &env).unwrap();
+        let sends = submitted_msg_sends(&resp.messages[0]);
+        assert_eq!(sends[0].from_address, WITHDRAW_ADDR);
+        assert_eq!(sends[0].to_address, POOL_ADDR);
+        assert_eq!(sends[0].amount[0].amount, "300");
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.status, WindDownStatus::Unbonding);
+        assert!(wind_down.sweeping);
+        // the fee came from the vault
+        assert!(
+            FEE_VAULTS
+                .load(deps.as_ref().storage, POOL_ADDR.to_string())
+                .unwrap()
+                < Uint128::new(10_000)
+        );
+
+        // nothing happens while the sweep is in flight
+        assert!(snapshot(&mut deps, &env).is_err());
+
+        // a failed sweep is retried by the next call
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            WITHDRAW_CHANNEL_ID,
+            1,
+        );
+        sudo_error(deps.as_mut().into_empty(), req).unwrap();
+        let resp = snapshot(&mut deps, &env).unwrap();
+        assert_eq!(
+            submitted_msg_sends(&resp.messages[0])[0].amount[0].amount,
+            "300"
+        );
+
+        // once acked the snapshot waits for balances after the sweep
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            WITHDRAW_CHANNEL_ID,
+            2,
+        );
+        let mut ack_env = mock_env();
+        ack_env.block.height = env.block.height + 5;
+        sudo_response(
+            deps.as_mut().into_empty(),
+            ack_env.clone(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert!(!wind_down.sweeping);
+        assert_eq!(wind_down.snapshot_height, ack_env.block.height);
+        env.block.height = ack_env.block.height + 1;
+        assert!(snapshot(&mut deps, &env).is_err());
+
+        deps.querier = balances_at(env.block.height, 10_300, 0);
+        snapshot(&mut deps, &env).unwrap();
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.status, WindDownStatus::Exiting);
+        assert_eq!(wind_down.host_amount, Uint128::new(9_300));
+    }
+
+    #[test]
+    fn test_wind_down_snapshot_waits_for_pending_withdraw() {
+        let (mut deps, env) = snapshot_requested();
+        deps.querier = balances_at(env.block.height, 10_000, 0);
+
+        // the withdraw of the open unstake is in flight
+        let key = (POOL_ADDR.to_string(), 0);
+        let mut unstake_info = UNSTAKES_OF_INDEX
+            .load(deps.as_ref().storage, key.clone())
+            .unwrap();
+        unstake_info.status = WithdrawStatus::Pending;
+        UNSTAKES_OF_INDEX
+            .save(deps.as_mut().storage, key, &unstake_info)
+            .unwrap();
+        assert!(snapshot(&mut deps, &env).is_err());
+
+        // it was paid: the record is gone and so are its tokens
+        UNSTAKES_OF_INDEX.remove(deps.as_mut().storage, (POOL_ADDR.to_string(), 0));
+        deps.querier = balances_at(env.block.height, 9_000, 0);
+        snapshot(&mut deps, &env).unwrap();
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.host_amount, Uint128::new(9_000));
+    }
+
+    #[test]
+    fn test_wind_down_exit() {
+        let mut deps = exiting_pool();
+
+        let resp = execute_wind_down_exit(
+            deps.as_mut(),
+            mock_info("user1", &[]),
+            POOL_ADDR.to_string(),
+            Uint128::new(1_000),
+            Addr::unchecked("cosmos1receiver"),
+        )
+        .unwrap();
+        assert_eq!(
+            lsd_token_msg(&resp.messages[0].msg),
+            Cw20ExecuteMsg::BurnFrom {
+                owner: "user1".to_string(),
+                amount: Uint128::new(1_000),
+            }
+        );
+        let sends = submitted_msg_sends(&resp.messages[1]);
+        assert_eq!(sends.len(), 1);
+        assert_eq!(sends[0].from_address, POOL_ADDR);
+        assert_eq!(sends[0].to_address, "cosmos1receiver");
+        assert_eq!(sends[0].amount[0].denom, "uatom");
+        assert_eq!(sends[0].amount[0].amount, "3000");
+        // the fee vault paid the ica tx
+        assert_eq!(
+            FEE_VAULTS
+                .load(deps.as_ref().storage, POOL_ADDR.to_string())
+                .unwrap(),
+            Uint128::new(10_000 - ACK_FEE - TIMEOUT_FEE)
+        );
+        let pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(pool_info.total_lsd_token_amount, Uint128::new(2_000));
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.exiting_lsd, Uint128::new(1_000));
+
+        // the neutron part follows the ack, 500 * 1000 / 3000
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[1],
+            POOL_CHANNEL_ID,
+            1,
+        );
+        let resp = sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            resp.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user1".to_string(),
+                amount: coins(166, "ibc/ATOM"),
+            })
+        );
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.exiting_lsd, Uint128::zero());
+        assert_eq!(wind_down.exited_lsd, Uint128::new(1_000));
+        assert_eq!(wind_down.status, WindDownStatus::Exiting);
+        // the timeout fee came back
+        assert_eq!(
+            FEE_VAULTS
+                .load(deps.as_ref().storage, POOL_ADDR.to_string())
+                .unwrap(),
+            Uint128::new(10_000 - ACK_FEE)
+        );
+
+        // a failed exit mints the lsd token back and keeps the share for a retry
+        let resp = execute_wind_down_exit(
+            deps.as_mut(),
+            mock_info("user2", &coins(ACK_FEE + TIMEOUT_FEE, FEE_DENOM)),
+            POOL_ADDR.to_string(),
+            Uint128::new(2_000),
+            Addr::unchecked("cosmos1receiver2"),
+        )
+        .unwrap();
+        assert_eq!(
+            submitted_msg_sends(&resp.messages[1])[0].amount[0].amount,
+            "6000"
+        );
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[1],
+            POOL_CHANNEL_ID,
+            2,
+        );
+        let resp = sudo_error(deps.as_mut().into_empty(), req).unwrap();
+        assert_eq!(
+            lsd_token_msg(&resp.messages[0].msg),
+            Cw20ExecuteMsg::Mint {
+                recipient: "user2".to_string(),
+                amount: Uint128::new(2_000),
+            }
+        );
+        let pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(pool_info.total_lsd_token_amount, Uint128::new(2_000));
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.exiting_lsd, Uint128::zero());
+        assert_eq!(wind_down.exited_lsd, Uint128::new(1_000));
+
+        // the last exit gets the same share and closes the pool
+        let resp = execute_wind_down_exit(
+            deps.as_mut(),
+            mock_info("user2", &[]),
+            POOL_ADDR.to_string(),
+            Uint128::new(2_000),
+            Addr::unchecked("cosmos1receiver2"),
+        )
+        .unwrap();
+        assert_eq!(
+            submitted_msg_sends(&resp.messages[1])[0].amount[0].amount,
+            "6000"
+        );
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[1],
+            POOL_CHANNEL_ID,
+            3,
+        );
+        let resp = sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            resp.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user2".to_string(),
+                amount: coins(333, "ibc/ATOM"),
+            })
+        );
+        let wind_down = WIND_DOWNS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        assert_eq!(wind_down.exited_lsd, Uint128::new(3_000));
+        assert_eq!(wind_down.status, WindDownStatus::Closed);
+        assert!(execute_wind_down_exit(
+            deps.as_mut(),
+            mock_info("user3", &[]),
+            POOL_ADDR.to_string(),
+            Uint128::new(1),
+            Addr::unchecked("cosmos1receiver3"),
+        )
+        .is_err());
+    }
+}
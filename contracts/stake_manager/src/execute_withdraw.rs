@@ -1,72 +1,127 @@
-use crate::helper::{self, gen_msg_send};
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::execute_wind_down::unstakes_released;
+use crate::helper::{gen_msg_send, WITHDRAW_UNSTAKES_LIMIT};
 use crate::state::{
-    SudoPayload, TxType, WithdrawStatus, INFO_OF_ICA_ID, POOLS, UNSTAKES_INDEX_FOR_USER,
-    UNSTAKES_OF_INDEX,
+    PoolInfo, SudoPayload, TxPayload, UnstakeInfo, WithdrawStatus, INFO_OF_ICA_ID, POOLS,
+    TICKETED_UNSTAKES, UNSTAKES_OF_INDEX, UNSTAKES_OF_USER, UNSTAKE_INDEX_OF_ERA,
+    WITHDRAW_RECEIVERS,
 };
-use crate::tx_callback::msg_with_sudo_callback;
+use crate::tx_callback::msg_with_fee_refund;
 use crate::{error_conversion::ContractError, helper::DEFAULT_TIMEOUT_SECONDS};
-use cosmwasm_std::{Addr, DepsMut, MessageInfo, Response, Uint128};
+use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, DepsMut, MessageInfo, Order, Response};
+use cosmwasm_std::{QuerierWrapper, StdResult, Storage, Uint128, WasmMsg};
+use cw_storage_plus::Bound;
 use neutron_sdk::{
     bindings::{msg::NeutronMsg, query::NeutronQuery},
     NeutronResult,
 };
 use std::vec;
+use unstake_ticket::msg::OwnerOfResponse;
 
 pub fn execute_withdraw(
-    mut deps: DepsMut<NeutronQuery>,
+    deps: DepsMut<NeutronQuery>,
     info: MessageInfo,
     pool_addr: String,
     receiver: Addr,
     unstake_index_list: Vec<u64>,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    if unstake_index_list.is_empty() {
-        return Err(ContractError::EmptyUnstakeList {}.into());
-    }
+    let user = info.sender.clone();
+    withdraw(deps, info, pool_addr, user, receiver, unstake_index_list)
+}
 
+// withdraws every matured unstake of the caller
+pub fn execute_withdraw_all(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    receiver: Addr,
+) -> NeutronResult<Response<NeutronMsg>> {
     let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let unstake_index_list = withdrawable_unstake_indexes(
+        deps.storage,
+        &pool_info,
+        pool_addr.clone(),
+        &info.sender,
+        0,
+        u64::MAX,
+    )?;
 
-    let mut total_withdraw_amount = Uint128::zero();
-    for unstake_index in unstake_index_list.clone() {
-        let mut unstake_info =
-            UNSTAKES_OF_INDEX.load(deps.storage, (pool_addr.clone(), unstake_index))?;
+    let user = info.sender.clone();
+    withdraw(deps, info, pool_addr, user, receiver, unstake_index_list)
+}
 
-        if unstake_info.pool_addr != pool_addr {
-            return Err(ContractError::UnstakeIndexPoolNotMatch(unstake_index).into());
-        }
+// anyone can pay the matured unstakes of user to the receiver the user set
+pub fn execute_withdraw_for(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    user: Addr,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let receiver = WITHDRAW_RECEIVERS
+        .may_load(deps.storage, (user.clone(), pool_addr.clone()))?
+        .ok_or(ContractError::WithdrawReceiverNotSet {})?;
 
-        if unstake_info.unstaker != info.sender {
-            return Err(ContractError::UnstakeIndexUnstakerNotMatch(unstake_index).into());
-        }
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let unstake_index_list = withdrawable_unstake_indexes(
+        deps.storage,
+        &pool_info,
+        pool_addr.clone(),
+        &user,
+        0,
+        u64::MAX,
+    )?;
 
-        if unstake_info.status == WithdrawStatus::Pending {
-            return Err(ContractError::UnstakeIndexStatusNotMatch(unstake_index).into());
-        }
-        if unstake_info.era + pool_info.unbonding_period > pool_info.era {
-            return Err(ContractError::UnstakeIndexNotWithdrawable(unstake_index).into());
-        }
+    withdraw(deps, info, pool_addr, user, receiver, unstake_index_list)
+}
 
-        total_withdraw_amount += unstake_info.amount;
+pub fn execute_set_withdraw_receiver(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    receiver: Option<Addr>,
+) -> NeutronResult<Response<NeutronMsg>> {
+    POOLS.load(deps.storage, pool_addr.clone())?;
 
-        unstake_info.status = WithdrawStatus::Pending;
-        UNSTAKES_OF_INDEX.save(
-            deps.storage,
-            (pool_addr.clone(), unstake_index),
-            &unstake_info,
-        )?;
+    let key = (info.sender.clone(), pool_addr.clone());
+    match &receiver {
+        Some(receiver) => WITHDRAW_RECEIVERS.save(deps.storage, key, receiver)?,
+        None => WITHDRAW_RECEIVERS.remove(deps.storage, key),
     }
 
-    if total_withdraw_amount.is_zero() {
-        return Err(ContractError::EncodeErrZeroWithdrawAmount {}.into());
+    Ok(Response::new()
+        .add_attribute("action", "set_withdraw_receiver")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("user", info.sender)
+        .add_attribute(
+            "receiver",
+            receiver.map(|r| r.to_string()).unwrap_or_default(),
+        ))
+}
+
+fn withdraw(
+    mut deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    user: Addr,
+    receiver: Addr,
+    unstake_index_list: Vec<u64>,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if unstake_index_list.is_empty() {
+        return Err(ContractError::EmptyUnstakeList {}.into());
     }
 
-    let unstake_index_list_str = unstake_index_list
-        .iter()
-        .map(|index| index.to_string())
-        .collect::<Vec<String>>()
-        .join("_");
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let total_withdraw_amount = lock_withdrawable_unstakes(
+        deps.storage,
+        &deps.querier,
+        &pool_info,
+        pool_addr.clone(),
+        &user,
+        &unstake_index_list,
+    )?;
 
     let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
-    let ibc_fee = helper::check_ibc_fee(deps.as_ref(), &info)?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
     let cosmos_msg = NeutronMsg::submit_tx(
         pool_ica_info.ctrl_connection_id.clone(),
         pool_info.ica_id.clone(),
@@ -78,71 +133,81 @@ pub fn execute_withdraw(
         )?],
         "".to_string(),
         DEFAULT_TIMEOUT_SECONDS,
-        ibc_fee,
+        ibc_fee.clone(),
     );
 
     // We use a submessage here because we need the process message reply to save
     // the outgoing IBC packet identifier for later.
-    let submsg = msg_with_sudo_callback(
+    let submsg = msg_with_fee_refund(
         deps.branch(),
         cosmos_msg,
         SudoPayload {
             port_id: pool_ica_info.ctrl_port_id,
-            message: format!(
-                "{}_{}_{}_{}",
-                total_withdraw_amount, info.sender, receiver, unstake_index_list_str
-            ),
+            message: TxPayload::UserWithdraw {
+                amount: total_withdraw_amount,
+                user,
+                receiver: receiver.to_string(),
+                unstake_index_list,
+            },
             pool_addr: pool_addr.clone(),
-            tx_type: TxType::UserWithdraw,
         },
+        &ibc_fee,
     )?;
 
     Ok(Response::new().add_submessage(submsg))
 }
 
+// claims the unstakes of eras start_era..=end_era
+pub fn execute_withdraw_eras(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    pool_addr: String,
+    receiver: Addr,
+    start_era: u64,
+    end_era: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let unstake_index_list = withdrawable_unstake_indexes(
+        deps.storage,
+        &pool_info,
+        pool_addr.clone(),
+        &info.sender,
+        start_era,
+        end_era,
+    )?;
+
+    execute_withdraw(deps, info, pool_addr, receiver, unstake_index_list)
+}
+
 pub fn sudo_withdraw_callback(
     deps: DepsMut,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let parts: Vec<String> = payload.message.split('_').map(String::from).collect();
-    if parts.len() <= 3 {
-        return Err(ContractError::UnsupportedMessage(payload.message).into());
-    }
-    let total_withdraw_amount = parts.get(0).unwrap();
-    let user_addr = Addr::unchecked(parts.get(1).unwrap());
-    let receiver = parts.get(2).unwrap();
-    let unstake_index_list_str = parts
+    let TxPayload::UserWithdraw {
+        amount: total_withdraw_amount,
+        user: user_addr,
+        receiver,
+        unstake_index_list,
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let burn_msgs = remove_unstakes(
+        deps.storage,
+        payload.pool_addr.clone(),
+        &user_addr,
+        &unstake_index_list,
+    )?;
+
+    let unstake_index_list_str = unstake_index_list
         .iter()
-        .skip(3)
-        .cloned()
+        .map(|index| index.to_string())
         .collect::<Vec<String>>()
         .join("_");
 
-    // retrieve unstake index list
-    if let Some(index_list) = parts.get(3..parts.len()) {
-        if let Some(mut unstakes) = UNSTAKES_INDEX_FOR_USER
-            .may_load(deps.storage, (user_addr.clone(), payload.pool_addr.clone()))?
-        {
-            unstakes.retain(|unstake_index| {
-                if index_list.contains(&unstake_index.to_string()) {
-                    UNSTAKES_OF_INDEX
-                        .remove(deps.storage, (payload.pool_addr.clone(), *unstake_index));
-                    return false;
-                }
-
-                true
-            });
-
-            // Remove the unstake index element
-            UNSTAKES_INDEX_FOR_USER.save(
-                deps.storage,
-                (user_addr.clone(), payload.pool_addr.clone()),
-                &unstakes,
-            )?;
-        }
-    }
-
     Ok(Response::new()
+        .add_messages(burn_msgs)
         .add_attribute("action", "withdraw")
         .add_attribute("from", user_addr)
         .add_attribute("pool", payload.pool_addr.clone())
@@ -155,22 +220,474 @@ pub fn sudo_withdraw_failed_callback(
     deps: DepsMut,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    let parts: Vec<String> = payload.message.split('_').map(String::from).collect();
+    let TxPayload::UserWithdraw {
+        unstake_index_list, ..
+    } = payload.message.clone()
+    else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    reset_unstakes(deps.storage, payload.pool_addr, &unstake_index_list)?;
+
+    Ok(Response::new())
+}
 
-    // skip withdraw_amount and user_addr and reviver
-    for index_str in parts.iter().skip(3) {
-        let index = index_str.parse::<u64>().unwrap();
+// unstakes of user in eras start_era..=end_era that are matured and not pending,
+// at most WITHDRAW_UNSTAKES_LIMIT of them
+pub fn withdrawable_unstake_indexes(
+    storage: &dyn Storage,
+    pool_info: &PoolInfo,
+    pool_addr: String,
+    user: &Addr,
+    start_era: u64,
+    end_era: u64,
+) -> StdResult<Vec<u64>> {
+    let end_era = if unstakes_released(storage, pool_addr.clone())? {
+        end_era
+    } else {
+        match pool_info.era.checked_sub(pool_info.unbonding_period) {
+            Some(matured_era) => end_era.min(matured_era),
+            None => return Ok(vec![]),
+        }
+    };
+    if start_era > end_era {
+        return Ok(vec![]);
+    }
+
+    let mut unstake_index_list = vec![];
+    for key in UNSTAKES_OF_USER
+        .prefix((user.clone(), pool_addr.clone()))
+        .keys(
+            storage,
+            Some(Bound::inclusive((start_era, 0))),
+            Some(Bound::inclusive((end_era, u64::MAX))),
+            Order::Ascending,
+        )
+    {
+        let (_, unstake_index) = key?;
+        // ticketed unstakes are withdrawn by the ticket owner
+        if TICKETED_UNSTAKES.has(storage, (pool_addr.clone(), unstake_index)) {
+            continue;
+        }
+        let unstake_info = UNSTAKES_OF_INDEX.load(storage, (pool_addr.clone(), unstake_index))?;
+        if unstake_info.status == WithdrawStatus::Default {
+            unstake_index_list.push(unstake_index);
+            if unstake_index_list.len() >= WITHDRAW_UNSTAKES_LIMIT {
+                break;
+            }
+        }
+    }
+
+    Ok(unstake_index_list)
+}
+
+// a ticketed unstake belongs to whoever holds the ticket
+pub fn unstake_owner(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper<NeutronQuery>,
+    unstake_info: &UnstakeInfo,
+) -> StdResult<String> {
+    let key = (unstake_info.pool_addr.clone(), unstake_info.index);
+    match TICKETED_UNSTAKES.may_load(storage, key)? {
+        Some(unstake_ticket) => Ok(querier
+            .query_wasm_smart::<OwnerOfResponse>(
+                unstake_ticket,
+                &unstake_ticket::msg::QueryMsg::OwnerOf {
+                    token_id: unstake_info.index.to_string(),
+                    include_expired: None,
+                },
+            )?
+            .owner),
+        None => Ok(unstake_info.unstaker.clone()),
+    }
+}
+
+// checks the unstakes can be withdrawn by user and marks them pending, returns the total amount
+pub fn lock_withdrawable_unstakes(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper<NeutronQuery>,
+    pool_info: &PoolInfo,
+    pool_addr: String,
+    user: &Addr,
+    unstake_index_list: &[u64],
+) -> NeutronResult<Uint128> {
+    let released = unstakes_released(storage, pool_addr.clone())?;
+    let mut total_withdraw_amount = Uint128::zero();
+    for unstake_index in unstake_index_list.iter().copied() {
         let mut unstake_info =
-            UNSTAKES_OF_INDEX.load(deps.storage, (payload.pool_addr.clone(), index))?;
+            UNSTAKES_OF_INDEX.load(storage, (pool_addr.clone(), unstake_index))?;
+
+        if unstake_info.pool_addr != pool_addr {
+            return Err(ContractError::UnstakeIndexPoolNotMatch(unstake_index).into());
+        }
+
+        if unstake_owner(storage, querier, &unstake_info)? != *user {
+            return Err(ContractError::UnstakeIndexUnstakerNotMatch(unstake_index).into());
+        }
+
+        if unstake_info.status == WithdrawStatus::Pending {
+            return Err(ContractError::UnstakeIndexStatusNotMatch(unstake_index).into());
+        }
+        if unstake_info.era + pool_info.unbonding_period > pool_info.era && !released {
+            return Err(ContractError::UnstakeIndexNotWithdrawable(unstake_index).into());
+        }
+
+        total_withdraw_amount += unstake_info.amount;
+
+        unstake_info.status = WithdrawStatus::Pending;
+        UNSTAKES_OF_INDEX.save(storage, (pool_addr.clone(), unstake_index), &unstake_info)?;
+    }
+
+    if total_withdraw_amount.is_zero() {
+        return Err(ContractError::EncodeErrZeroWithdrawAmount {}.into());
+    }
+
+    Ok(total_withdraw_amount)
+}
+
+pub fn remove_unstakes(
+    storage: &mut dyn Storage,
+    pool_addr: String,
+    user: &Addr,
+    unstake_index_list: &[u64],
+) -> StdResult<Vec<CosmosMsg<NeutronMsg>>> {
+    let mut burn_msgs = vec![];
+    for unstake_index in unstake_index_list.iter().copied() {
+        let Some(unstake_info) =
+            UNSTAKES_OF_INDEX.may_load(storage, (pool_addr.clone(), unstake_index))?
+        else {
+            continue;
+        };
+        let unstake_ticket =
+            TICKETED_UNSTAKES.may_load(storage, (pool_addr.clone(), unstake_index))?;
+        if unstake_ticket.is_none() && unstake_info.unstaker != user.as_str() {
+            continue;
+        }
+
+        // the record stays listed under the unstaker even after its ticket was sold
+        let unstaker = Addr::unchecked(unstake_info.unstaker);
+        let era_key = (unstaker.clone(), pool_addr.clone(), unstake_info.era);
+        if UNSTAKE_INDEX_OF_ERA.may_load(storage, era_key.clone())? == Some(unstake_index) {
+            UNSTAKE_INDEX_OF_ERA.remove(storage, era_key);
+        }
+        UNSTAKES_OF_INDEX.remove(storage, (pool_addr.clone(), unstake_index));
+
+        UNSTAKES_OF_USER.remove(
+            storage,
+            (
+                unstaker,
+                pool_addr.clone(),
+                (unstake_info.era, unstake_index),
+            ),
+        );
+
+        if let Some(unstake_ticket) = unstake_ticket {
+            TICKETED_UNSTAKES.remove(storage, (pool_addr.clone(), unstake_index));
+            burn_msgs.push(
+                WasmMsg::Execute {
+                    contract_addr: unstake_ticket.to_string(),
+                    msg: to_json_binary(&unstake_ticket::msg::ExecuteMsg::Burn {
+                        token_id: unstake_index.to_string(),
+                    })?,
+                    funds: vec![],
+                }
+                .into(),
+            );
+        }
+    }
+
+    Ok(burn_msgs)
+}
+
+pub fn reset_unstakes(
+    storage: &mut dyn Storage,
+    pool_addr: String,
+    unstake_index_list: &[u64],
+) -> StdResult<()> {
+    for index in unstake_index_list.iter().copied() {
+        let mut unstake_info = UNSTAKES_OF_INDEX.load(storage, (pool_addr.clone(), index))?;
 
         unstake_info.status = WithdrawStatus::Default;
 
-        UNSTAKES_OF_INDEX.save(
-            deps.storage,
-            (payload.pool_addr.clone(), index),
-            &unstake_info,
-        )?;
+        UNSTAKES_OF_INDEX.save(storage, (pool_addr.clone(), index), &unstake_info)?;
     }
 
-    Ok(Response::new())
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, Addr, Uint128};
+
+    use super::{execute_set_withdraw_receiver, execute_withdraw_all, execute_withdraw_for};
+    use crate::helper::{FEE_DENOM, WITHDRAW_UNSTAKES_LIMIT};
+    use crate::state::{
+        PoolInfo, UnstakeInfo, WithdrawStatus, POOLS, UNSTAKES_OF_INDEX, UNSTAKES_OF_USER,
+    };
+    use crate::testing::{
+        mock_neutron_dependencies, relay_packet, save_pool, submitted_msg_sends, NeutronDeps,
+        ACK_FEE, POOL_ADDR, POOL_CHANNEL_ID, TIMEOUT_FEE,
+    };
+    use crate::tx_callback::{sudo_error, sudo_response};
+
+    fn save_unstake(deps: &mut NeutronDeps, index: u64, user: &str, era: u64, amount: u128) {
+        UNSTAKES_OF_INDEX
+            .save(
+                deps.as_mut().storage,
+                (POOL_ADDR.to_string(), index),
+                &UnstakeInfo {
+                    era,
+                    pool_addr: POOL_ADDR.to_string(),
+                    unstaker: user.to_string(),
+                    amount: Uint128::new(amount),
+                    status: WithdrawStatus::Default,
+                    index,
+                    unbond_amount: Uint128::new(amount + 5),
+                },
+            )
+            .unwrap();
+        UNSTAKES_OF_USER
+            .save(
+                deps.as_mut().storage,
+                (Addr::unchecked(user), POOL_ADDR.to_string(), (era, index)),
+                &(),
+            )
+            .unwrap();
+    }
+
+    fn status(deps: &NeutronDeps, index: u64) -> Option<WithdrawStatus> {
+        UNSTAKES_OF_INDEX
+            .may_load(deps.as_ref().storage, (POOL_ADDR.to_string(), index))
+            .unwrap()
+            .map(|unstake_info| unstake_info.status)
+    }
+
+    // era 10, unbonding 4: the unstakes of user from eras 5 and 6 are matured, era 7 isn't
+    fn pool_with_unstakes() -> NeutronDeps {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                remote_denom: "uatom".to_string(),
+                era: 10,
+                unbonding_period: 4,
+                ..PoolInfo::default()
+            },
+        );
+        save_unstake(&mut deps, 1, "user", 5, 100);
+        save_unstake(&mut deps, 2, "other", 5, 1_000);
+        save_unstake(&mut deps, 3, "user", 6, 200);
+        save_unstake(&mut deps, 4, "user", 7, 400);
+        deps
+    }
+
+    fn fee() -> Vec<cosmwasm_std::Coin> {
+        coins(ACK_FEE + TIMEOUT_FEE, FEE_DENOM)
+    }
+
+    #[test]
+    fn test_withdraw_all() {
+        let mut deps = pool_with_unstakes();
+
+        let resp = execute_withdraw_all(
+            deps.as_mut(),
+            mock_info("user", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+        )
+        .unwrap();
+        let sends = submitted_msg_sends(&resp.messages[0]);
+        assert_eq!(sends[0].from_address, POOL_ADDR);
+        assert_eq!(sends[0].to_address, "cosmos1receiver");
+        assert_eq!(sends[0].amount[0].amount, "300");
+        assert_eq!(status(&deps, 1), Some(WithdrawStatus::Pending));
+        assert_eq!(status(&deps, 2), Some(WithdrawStatus::Default));
+        assert_eq!(status(&deps, 3), Some(WithdrawStatus::Pending));
+        assert_eq!(status(&deps, 4), Some(WithdrawStatus::Default));
+
+        // pending claims aren't collected again
+        assert!(execute_withdraw_all(
+            deps.as_mut(),
+            mock_info("user", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+        )
+        .is_err());
+
+        // a failed send leaves the claims for another try
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            1,
+        );
+        sudo_error(deps.as_mut().into_empty(), req).unwrap();
+        assert_eq!(status(&deps, 1), Some(WithdrawStatus::Default));
+        assert_eq!(status(&deps, 3), Some(WithdrawStatus::Default));
+
+        // era 7 has matured meanwhile
+        let mut pool_info = POOLS
+            .load(deps.as_ref().storage, POOL_ADDR.to_string())
+            .unwrap();
+        pool_info.era = 11;
+        POOLS
+            .save(deps.as_mut().storage, POOL_ADDR.to_string(), &pool_info)
+            .unwrap();
+        let resp = execute_withdraw_all(
+            deps.as_mut(),
+            mock_info("user", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+        )
+        .unwrap();
+        assert_eq!(
+            submitted_msg_sends(&resp.messages[0])[0].amount[0].amount,
+            "700"
+        );
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            2,
+        );
+        sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        for index in [1, 3, 4] {
+            assert_eq!(status(&deps, index), None);
+        }
+        assert_eq!(status(&deps, 2), Some(WithdrawStatus::Default));
+        assert!(UNSTAKES_OF_USER
+            .prefix((Addr::unchecked("user"), POOL_ADDR.to_string()))
+            .is_empty(deps.as_ref().storage));
+    }
+
+    #[test]
+    fn test_withdraw_all_bounded() {
+        let mut deps = pool_with_unstakes();
+        for index in 5..(5 + WITHDRAW_UNSTAKES_LIMIT as u64) {
+            save_unstake(&mut deps, index, "user", 6, 1);
+        }
+
+        // one claim takes the oldest eras first, the rest waits for the next one
+        let resp = execute_withdraw_all(
+            deps.as_mut(),
+            mock_info("user", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+        )
+        .unwrap();
+        assert_eq!(
+            submitted_msg_sends(&resp.messages[0])[0].amount[0].amount,
+            (100 + 200 + WITHDRAW_UNSTAKES_LIMIT - 2).to_string()
+        );
+        assert_eq!(status(&deps, 3), Some(WithdrawStatus::Pending));
+        assert_eq!(
+            status(&deps, 2 + WITHDRAW_UNSTAKES_LIMIT as u64),
+            Some(WithdrawStatus::Pending)
+        );
+        assert_eq!(
+            status(&deps, 3 + WITHDRAW_UNSTAKES_LIMIT as u64),
+            Some(WithdrawStatus::Default)
+        );
+
+        let resp = execute_withdraw_all(
+            deps.as_mut(),
+            mock_info("user", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("cosmos1receiver"),
+        )
+        .unwrap();
+        assert_eq!(
+            submitted_msg_sends(&resp.messages[0])[0].amount[0].amount,
+            "2"
+        );
+    }
+
+    #[test]
+    fn test_withdraw_for() {
+        let mut deps = pool_with_unstakes();
+
+        // the user has to choose the receiver first
+        assert!(execute_withdraw_for(
+            deps.as_mut(),
+            mock_info("keeper", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("user"),
+        )
+        .is_err());
+        execute_set_withdraw_receiver(
+            deps.as_mut(),
+            mock_info("user", &[]),
+            POOL_ADDR.to_string(),
+            Some(Addr::unchecked("cosmos1chosen")),
+        )
+        .unwrap();
+
+        // anyone triggers it, the tokens still go where the user said
+        let resp = execute_withdraw_for(
+            deps.as_mut(),
+            mock_info("keeper", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("user"),
+        )
+        .unwrap();
+        let sends = submitted_msg_sends(&resp.messages[0]);
+        assert_eq!(sends[0].to_address, "cosmos1chosen");
+        assert_eq!(sends[0].amount[0].amount, "300");
+
+        let req = relay_packet(
+            deps.as_mut().into_empty(),
+            &resp.messages[0],
+            POOL_CHANNEL_ID,
+            1,
+        );
+        sudo_response(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            req,
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(status(&deps, 1), None);
+        assert_eq!(status(&deps, 3), None);
+        assert_eq!(status(&deps, 4), Some(WithdrawStatus::Default));
+        assert_eq!(status(&deps, 2), Some(WithdrawStatus::Default));
+
+        // nothing matured is left for the user, and a cleared receiver stops payouts
+        assert!(execute_withdraw_for(
+            deps.as_mut(),
+            mock_info("keeper", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("user"),
+        )
+        .is_err());
+        execute_set_withdraw_receiver(
+            deps.as_mut(),
+            mock_info("other", &[]),
+            POOL_ADDR.to_string(),
+            Some(Addr::unchecked("cosmos1other")),
+        )
+        .unwrap();
+        execute_set_withdraw_receiver(
+            deps.as_mut(),
+            mock_info("other", &[]),
+            POOL_ADDR.to_string(),
+            None,
+        )
+        .unwrap();
+        assert!(execute_withdraw_for(
+            deps.as_mut(),
+            mock_info("keeper", &fee()),
+            POOL_ADDR.to_string(),
+            Addr::unchecked("other"),
+        )
+        .is_err());
+        assert_eq!(status(&deps, 2), Some(WithdrawStatus::Default));
+    }
 }
@@ -0,0 +1,318 @@
+use crate::execute_fee_vault::take_ibc_fee;
+use crate::execute_withdraw::{lock_withdrawable_unstakes, remove_unstakes, reset_unstakes};
+use crate::helper::{
+    derive_intermediate_sender, gen_ibc_transfer_msg, IBC_TRANSFER_TIMEOUT_SECONDS,
+};
+use crate::msg::ExecuteMsg;
+use crate::state::{
+    NeutronWithdrawInfo, SudoPayload, TxPayload, HOST_CHANNEL_ID_OF_IBC_DENOM, INFO_OF_ICA_ID,
+    NEUTRON_WITHDRAWS, NEXT_NEUTRON_WITHDRAW_ID, POOLS,
+};
+use crate::tx_callback::msg_with_fee_refund;
+use crate::{error_conversion::ContractError, helper::DEFAULT_TIMEOUT_SECONDS};
+use cosmwasm_std::{
+    coin, coins, to_json_string, Addr, BankMsg, DepsMut, Env, MessageInfo, Response, Storage,
+};
+use neutron_sdk::{
+    bindings::{msg::NeutronMsg, query::NeutronQuery},
+    NeutronResult,
+};
+
+pub fn execute_withdraw_to_neutron(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    pool_addr: String,
+    receiver: Addr,
+    unstake_index_list: Vec<u64>,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if unstake_index_list.is_empty() {
+        return Err(ContractError::EmptyUnstakeList {}.into());
+    }
+
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let host_channel_id = HOST_CHANNEL_ID_OF_IBC_DENOM
+        .may_load(deps.storage, pool_addr.clone())?
+        .ok_or(ContractError::HostChannelIdNotSet {})?;
+    deps.api.addr_validate(receiver.as_str())?;
+
+    let total_withdraw_amount = lock_withdrawable_unstakes(
+        deps.storage,
+        &deps.querier,
+        &pool_info,
+        pool_addr.clone(),
+        &info.sender,
+        &unstake_index_list,
+    )?;
+
+    let withdraw_id = NEXT_NEUTRON_WITHDRAW_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    NEXT_NEUTRON_WITHDRAW_ID.save(deps.storage, &(withdraw_id + 1))?;
+
+    let timeout_timestamp = env.block.time.plus_seconds(IBC_TRANSFER_TIMEOUT_SECONDS);
+    NEUTRON_WITHDRAWS.save(
+        deps.storage,
+        withdraw_id,
+        &NeutronWithdrawInfo {
+            pool_addr: pool_addr.clone(),
+            unstaker: info.sender.clone(),
+            receiver: receiver.clone(),
+            amount: total_withdraw_amount,
+            unstake_index_list,
+            timeout_timestamp: timeout_timestamp.seconds(),
+            ica_acked: false,
+            transfer_received: false,
+        },
+    )?;
+
+    // the tokens are sent to this contract, ibc hooks then calls it to forward them to receiver
+    let memo = format!(
+        r#"{{"wasm":{{"contract":"{}","msg":{}}}}}"#,
+        env.contract.address,
+        to_json_string(&ExecuteMsg::WithdrawToNeutronReceived { withdraw_id })?
+    );
+
+    let (pool_ica_info, _, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+    let ibc_fee = take_ibc_fee(deps.branch(), &info, pool_addr.clone())?;
+    let cosmos_msg = NeutronMsg::submit_tx(
+        pool_ica_info.ctrl_connection_id.clone(),
+        pool_info.ica_id.clone(),
+        vec![gen_ibc_transfer_msg(
+            host_channel_id,
+            pool_addr.clone(),
+            env.contract.address.to_string(),
+            coin(total_withdraw_amount.u128(), pool_info.remote_denom),
+            timeout_timestamp.nanos(),
+            memo,
+        )],
+        "".to_string(),
+        DEFAULT_TIMEOUT_SECONDS,
+        ibc_fee.clone(),
+    );
+
+    let submsg = msg_with_fee_refund(
+        deps.branch(),
+        cosmos_msg,
+        SudoPayload {
+            port_id: pool_ica_info.ctrl_port_id,
+            message: TxPayload::UserWithdrawToNeutron { withdraw_id },
+            pool_addr: pool_addr.clone(),
+        },
+        &ibc_fee,
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(submsg)
+        .add_attribute("action", "withdraw_to_neutron")
+        .add_attribute("pool", pool_addr)
+        .add_attribute("from", info.sender)
+        .add_attribute("receiver", receiver)
+        .add_attribute("withdraw_id", withdraw_id.to_string())
+        .add_attribute("amount", total_withdraw_amount))
+}
+
+pub fn execute_withdraw_to_neutron_received(
+    deps: DepsMut<NeutronQuery>,
+    info: MessageInfo,
+    withdraw_id: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let mut withdraw_info = NEUTRON_WITHDRAWS.load(deps.storage, withdraw_id)?;
+    if withdraw_info.transfer_received {
+        return Err(ContractError::StatusNotAllow {}.into());
+    }
+
+    let pool_info = POOLS.load(deps.storage, withdraw_info.pool_addr.clone())?;
+    // only ibc hooks executing the pool ica's transfer gets this sender, it lands on channel_id_of_ibc_denom
+    let intermediate_sender = derive_intermediate_sender(
+        deps.as_ref(),
+        &pool_info.channel_id_of_ibc_denom,
+        &withdraw_info.pool_addr,
+    )?;
+    if info.sender != intermediate_sender {
+        return Err(ContractError::IbcHooksSenderNotMatch {}.into());
+    }
+    let funds = coins(withdraw_info.amount.u128(), pool_info.ibc_denom);
+    if info.funds != funds {
+        return Err(ContractError::NeutronWithdrawFundsNotMatch(withdraw_id).into());
+    }
+
+    withdraw_info.transfer_received = true;
+    let msg = BankMsg::Send {
+        to_address: withdraw_info.receiver.to_string(),
+        amount: funds,
+    };
+
+    Ok(
+        finish_neutron_withdraw(deps.storage, withdraw_id, withdraw_info)?
+            .add_message(msg)
+            .add_attribute("action", "withdraw_to_neutron_received"),
+    )
+}
+
+pub fn execute_withdraw_to_neutron_expired(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    withdraw_id: u64,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let withdraw_info = NEUTRON_WITHDRAWS.load(deps.storage, withdraw_id)?;
+    // before the ica ack the failed callback takes care of it
+    if !withdraw_info.ica_acked
+        || withdraw_info.transfer_received
+        || env.block.time.seconds() <= withdraw_info.timeout_timestamp
+    {
+        return Err(ContractError::NeutronWithdrawNotExpired(withdraw_id).into());
+    }
+
+    // the transfer is refunded to the pool ica, so the unstakes can be withdrawn again
+    reset_unstakes(
+        deps.storage,
+        withdraw_info.pool_addr.clone(),
+        &withdraw_info.unstake_index_list,
+    )?;
+    NEUTRON_WITHDRAWS.remove(deps.storage, withdraw_id);
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_to_neutron_expired")
+        .add_attribute("pool", withdraw_info.pool_addr)
+        .add_attribute("withdraw_id", withdraw_id.to_string()))
+}
+
+pub fn sudo_withdraw_to_neutron_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::UserWithdrawToNeutron { withdraw_id } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut withdraw_info = NEUTRON_WITHDRAWS.load(deps.storage, withdraw_id)?;
+    withdraw_info.ica_acked = true;
+
+    Ok(
+        finish_neutron_withdraw(deps.storage, withdraw_id, withdraw_info)?
+            .add_attribute("action", "withdraw_to_neutron_callback"),
+    )
+}
+
+pub fn sudo_withdraw_to_neutron_failed_callback(
+    deps: DepsMut,
+    payload: SudoPayload,
+) -> NeutronResult<Response<NeutronMsg>> {
+    let TxPayload::UserWithdrawToNeutron { withdraw_id } = payload.message.clone() else {
+        return Err(payload.message.unsupported().into());
+    };
+
+    let mut withdraw_info = NEUTRON_WITHDRAWS.load(deps.storage, withdraw_id)?;
+    // the transfer already arrived, so the ica tx did go through
+    if withdraw_info.transfer_received {
+        withdraw_info.ica_acked = true;
+        return finish_neutron_withdraw(deps.storage, withdraw_id, withdraw_info);
+    }
+
+    reset_unstakes(
+        deps.storage,
+        payload.pool_addr,
+        &withdraw_info.unstake_index_list,
+    )?;
+    NEUTRON_WITHDRAWS.remove(deps.storage, withdraw_id);
+
+    Ok(Response::new())
+}
+
+// unstakes are only removed once both the ica ack and the ibc transfer have arrived
+fn finish_neutron_withdraw(
+    storage: &mut dyn Storage,
+    withdraw_id: u64,
+    withdraw_info: NeutronWithdrawInfo,
+) -> NeutronResult<Response<NeutronMsg>> {
+    if !withdraw_info.ica_acked || !withdraw_info.transfer_received {
+        NEUTRON_WITHDRAWS.save(storage, withdraw_id, &withdraw_info)?;
+        return Ok(Response::new().add_attribute("withdraw_id", withdraw_id.to_string()));
+    }
+
+    let burn_msgs = remove_unstakes(
+        storage,
+        withdraw_info.pool_addr.clone(),
+        &withdraw_info.unstaker,
+        &withdraw_info.unstake_index_list,
+    )?;
+    NEUTRON_WITHDRAWS.remove(storage, withdraw_id);
+
+    let unstake_index_list_str = withdraw_info
+        .unstake_index_list
+        .iter()
+        .map(|index| index.to_string())
+        .collect::<Vec<String>>()
+        .join("_");
+
+    Ok(Response::new()
+        .add_messages(burn_msgs)
+        .add_attribute("withdraw_id", withdraw_id.to_string())
+        .add_attribute("from", withdraw_info.unstaker)
+        .add_attribute("pool", withdraw_info.pool_addr)
+        .add_attribute("receiver", withdraw_info.receiver)
+        .add_attribute("unstake_index_list", unstake_index_list_str)
+        .add_attribute("amount", withdraw_info.amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{coins, Addr, Uint128};
+
+    use super::execute_withdraw_to_neutron_received;
+    use crate::helper::derive_intermediate_sender;
+    use crate::state::{NeutronWithdrawInfo, PoolInfo, NEUTRON_WITHDRAWS};
+    use crate::testing::{mock_neutron_dependencies_with_api, save_pool, HexApi, POOL_ADDR};
+
+    #[test]
+    fn test_withdraw_to_neutron_received_sender() {
+        let mut deps = mock_neutron_dependencies_with_api(HexApi::default());
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                ibc_denom: "ibc/atom".to_string(),
+                channel_id_of_ibc_denom: "channel-0".to_string(),
+                ..PoolInfo::default()
+            },
+        );
+        let withdraw_info = NeutronWithdrawInfo {
+            pool_addr: POOL_ADDR.to_string(),
+            unstaker: Addr::unchecked("user"),
+            receiver: Addr::unchecked("receiver"),
+            amount: Uint128::new(100),
+            unstake_index_list: vec![1],
+            timeout_timestamp: 0,
+            ica_acked: false,
+            transfer_received: false,
+        };
+        NEUTRON_WITHDRAWS
+            .save(deps.as_mut().storage, 0, &withdraw_info)
+            .unwrap();
+        let funds = coins(100, "ibc/atom");
+
+        // anyone else sending the funds can't mark the transfer received
+        assert!(execute_withdraw_to_neutron_received(
+            deps.as_mut(),
+            mock_info("anyone", &funds),
+            0
+        )
+        .is_err());
+
+        let intermediate_sender =
+            derive_intermediate_sender(deps.as_ref(), "channel-0", POOL_ADDR).unwrap();
+        execute_withdraw_to_neutron_received(
+            deps.as_mut(),
+            mock_info(intermediate_sender.as_str(), &funds),
+            0,
+        )
+        .unwrap();
+        assert!(
+            NEUTRON_WITHDRAWS
+                .load(deps.as_ref().storage, 0)
+                .unwrap()
+                .transfer_received
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use crate::query_callback::register_query_submsg;
+use crate::state::{FeeRecipient, PLATFORM_FEE_RECIPIENTS};
 use crate::state::{
-    IcaInfo, PoolInfo, QueryKind, SudoPayload, TxType, DECIMALS, ERA_RATE, POOLS, TOTAL_STACK_FEE,
+    IcaInfo, PoolInfo, QueryKind, SudoPayload, TxPayload, DECIMALS, ERA_RATE, POOLS,
+    TOTAL_STACK_FEE, VALIDATOR_WEIGHTS,
 };
 use crate::state::{ADDRESS_TO_REPLY_ID, INFO_OF_ICA_ID, REPLY_ID_TO_QUERY_ID};
 use crate::tx_callback::msg_with_sudo_callback;
@@ -11,8 +13,10 @@ use cosmos_sdk_proto::cosmos::distribution::v1beta1::MsgSetWithdrawAddress;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate};
 use cosmos_sdk_proto::prost::Message;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{instantiate2_address, to_json_binary, SubMsg, Uint64, WasmMsg};
-use cosmwasm_std::{Binary, Deps, DepsMut, QueryRequest, StdResult, Uint128};
+use cosmwasm_std::{
+    instantiate2_address, to_json_binary, Delegation, Storage, SubMsg, Uint64, WasmMsg,
+};
+use cosmwasm_std::{Addr, Binary, CanonicalAddr, Deps, DepsMut, QueryRequest, StdResult, Uint128};
 use cosmwasm_std::{Env, MessageInfo, Response};
 use cw20::MinterResponse;
 use neutron_sdk::bindings::msg::{IbcFee, NeutronMsg};
@@ -28,6 +32,7 @@ use neutron_sdk::NeutronError;
 use neutron_sdk::NeutronResult;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::ops::Add;
 
 pub const FEE_DENOM: &str = "untrn";
@@ -40,11 +45,23 @@ pub const MIN_ERA_SECONDS: u64 = 28800; //8h
 pub const MAX_ERA_SECONDS: u64 = 86400; //24h
 pub const VALIDATORS_LEN_LIMIT: usize = 16;
 pub const STAKE_SPLIT_THRESHOLD: Uint128 = Uint128::new(10_000);
+pub const DEFAULT_VALIDATOR_WEIGHT: u64 = 100;
+pub const DEFAULT_QUERY_LIMIT: u32 = 10;
+pub const DEFAULT_TIMELOCK_DELAY_SECONDS: u64 = 2 * 86400;
+pub const SECONDS_PER_YEAR: u64 = 365 * 86400;
+pub const MAX_QUERY_LIMIT: u32 = 30;
+pub const REBALANCE_MSGS_LIMIT: usize = 8;
+pub const WITHDRAW_UNSTAKES_LIMIT: usize = 50;
+pub const MAX_ENTRIES_PER_VALIDATOR: usize = 7;
+pub const BPS_BASE: u64 = 10_000;
 
 // Default timeout for SubmitTX is 30h
 pub const DEFAULT_TIMEOUT_SECONDS: u64 = 30 * 60 * 60;
 pub const DEFAULT_UPDATE_PERIOD: u64 = 86400;
 pub const DEFAULT_FAST_PERIOD: u64 = 60;
+// leaves the ica packet time to reach the host chain before the transfer it carries times out
+pub const IBC_TRANSFER_TIMEOUT_SECONDS: u64 = 2 * DEFAULT_TIMEOUT_SECONDS;
+pub const IBC_HOOKS_SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
 
 pub const REPLY_ID_RANGE_START: u64 = 1_000_000_000;
 pub const REPLY_ID_RANGE_SIZE: u64 = 1_000_000;
@@ -160,7 +177,7 @@ pub fn gen_redelegate_txs(
 
     // Put the serialized Delegate message to a types.Any protobuf message.
     ProtobufAny {
-        type_url: "/cosmos.staking.v1beta1.BeginRedelegate".to_string(),
+        type_url: "/cosmos.staking.v1beta1.MsgBeginRedelegate".to_string(),
         value: Binary::from(buf),
     }
 }
@@ -496,9 +513,10 @@ pub fn set_withdraw_sub_msg(
         cosmos_msg,
         SudoPayload {
             port_id: pool_ica_info.ctrl_port_id,
-            message: withdraw_ica_info.ica_addr,
+            message: TxPayload::SetWithdrawAddr {
+                withdraw_addr: withdraw_ica_info.ica_addr,
+            },
             pool_addr: pool_ica_info.ica_addr.clone(),
-            tx_type: TxType::SetWithdrawAddr,
         },
     )?;
     Ok(submsg_set_withdraw)
@@ -639,3 +657,340 @@ pub fn gen_msg_send(
         value: Binary::from(buf),
     })
 }
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgTransfer {
+    #[prost(string, tag = "1")]
+    pub source_port: String,
+    #[prost(string, tag = "2")]
+    pub source_channel: String,
+    #[prost(message, optional, tag = "3")]
+    pub token: Option<RawCoin>,
+    #[prost(string, tag = "4")]
+    pub sender: String,
+    #[prost(string, tag = "5")]
+    pub receiver: String,
+    #[prost(uint64, tag = "7")]
+    pub timeout_timestamp: u64,
+    #[prost(string, tag = "8")]
+    pub memo: String,
+}
+
+// ibc transfer sent by an ica on the host chain
+pub fn gen_ibc_transfer_msg(
+    source_channel: String,
+    sender: String,
+    receiver: String,
+    token: cosmwasm_std::Coin,
+    timeout_timestamp: u64,
+    memo: String,
+) -> ProtobufAny {
+    let msg = MsgTransfer {
+        source_port: "transfer".to_string(),
+        source_channel,
+        token: Some(token.into()),
+        sender,
+        receiver,
+        timeout_timestamp,
+        memo,
+    };
+
+    ProtobufAny {
+        type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(),
+        value: Binary::from(msg.encode_to_vec()),
+    }
+}
+
+// the sender ibc hooks uses when it executes a contract for original_sender's transfer over channel
+pub fn derive_intermediate_sender(
+    deps: Deps<NeutronQuery>,
+    channel: &str,
+    original_sender: &str,
+) -> StdResult<Addr> {
+    let type_hash = Sha256::digest(IBC_HOOKS_SENDER_PREFIX.as_bytes());
+    let sender_hash = Sha256::new()
+        .chain_update(type_hash)
+        .chain_update(format!("{}/{}", channel, original_sender).as_bytes())
+        .finalize();
+
+    deps.api
+        .addr_humanize(&CanonicalAddr::from(sender_hash.as_slice()))
+}
+
+pub fn check_fee_recipients(recipients: &[FeeRecipient]) -> NeutronResult<()> {
+    let mut total_bps = 0u64;
+    for (i, recipient) in recipients.iter().enumerate() {
+        if recipient.bps == 0
+            || recipients[..i]
+                .iter()
+                .any(|other| other.receiver == recipient.receiver)
+        {
+            return Err(ContractError::InvalidFeeRecipients {}.into());
+        }
+        total_bps = total_bps.saturating_add(recipient.bps);
+    }
+    if total_bps != BPS_BASE {
+        return Err(ContractError::InvalidFeeRecipients {}.into());
+    }
+
+    Ok(())
+}
+
+// splits amount by basis points, the rounding remainder goes to the first recipient
+pub fn split_fee(recipients: &[FeeRecipient], amount: Uint128) -> Vec<(Addr, Uint128)> {
+    let mut shares: Vec<(Addr, Uint128)> = recipients
+        .iter()
+        .map(|recipient| {
+            (
+                recipient.receiver.clone(),
+                amount.multiply_ratio(recipient.bps, BPS_BASE),
+            )
+        })
+        .collect();
+    let distributed: Uint128 = shares.iter().map(|(_, share)| *share).sum();
+    if let Some((_, first)) = shares.first_mut() {
+        *first += amount - distributed;
+    }
+    shares.retain(|(_, share)| !share.is_zero());
+
+    shares
+}
+
+pub fn platform_fee_shares(
+    store: &dyn Storage,
+    pool_info: &PoolInfo,
+    pool_addr: String,
+    amount: Uint128,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+    let recipients = PLATFORM_FEE_RECIPIENTS
+        .may_load(store, pool_addr)?
+        .unwrap_or_default();
+    if recipients.is_empty() {
+        return Ok(vec![(pool_info.platform_fee_receiver.clone(), amount)]);
+    }
+
+    Ok(split_fee(&recipients, amount))
+}
+
+pub fn load_validator_weights(
+    store: &dyn Storage,
+    pool_addr: String,
+    validator_addrs: &[String],
+) -> StdResult<Vec<(String, u64)>> {
+    validator_addrs
+        .iter()
+        .map(|validator| {
+            let weight = VALIDATOR_WEIGHTS
+                .may_load(store, (pool_addr.clone(), validator.clone()))?
+                .unwrap_or(DEFAULT_VALIDATOR_WEIGHT);
+            Ok((validator.clone(), weight))
+        })
+        .collect()
+}
+
+pub fn check_total_validator_weight(
+    store: &dyn Storage,
+    pool_addr: String,
+    validator_addrs: &[String],
+) -> NeutronResult<()> {
+    let weights = load_validator_weights(store, pool_addr, validator_addrs)?;
+    if weights.iter().all(|(_, weight)| *weight == 0) {
+        return Err(ContractError::TotalValidatorWeightZero {}.into());
+    }
+    Ok(())
+}
+
+pub fn delegated_amount(delegations: &[Delegation], validator: &str) -> Uint128 {
+    delegations
+        .iter()
+        .filter(|d| d.validator == validator)
+        .map(|d| d.amount.amount)
+        .sum()
+}
+
+// target delegation of every weighted validator when the pool total becomes `target_total`
+pub fn weighted_targets(
+    weights: &[(String, u64)],
+    target_total: Uint128,
+) -> NeutronResult<Vec<(String, Uint128)>> {
+    let total_weight: u64 = weights.iter().map(|(_, w)| *w).sum();
+    if total_weight == 0 {
+        return Err(ContractError::TotalValidatorWeightZero {}.into());
+    }
+
+    Ok(weights
+        .iter()
+        .map(|(validator, weight)| {
+            (
+                validator.clone(),
+                target_total.multiply_ratio(*weight, total_weight),
+            )
+        })
+        .collect())
+}
+
+// how much every validator is below its target once `amount` is added to the pool
+pub fn weighted_deficits(
+    weights: &[(String, u64)],
+    delegations: &[Delegation],
+    amount: Uint128,
+) -> NeutronResult<Vec<(String, Uint128)>> {
+    let total_delegated: Uint128 = delegations.iter().map(|d| d.amount.amount).sum();
+    let targets = weighted_targets(weights, total_delegated.add(amount))?;
+
+    Ok(targets
+        .into_iter()
+        .map(|(validator, target)| {
+            let current = delegated_amount(delegations, &validator);
+            (validator, target.saturating_sub(current))
+        })
+        .collect())
+}
+
+// split `amount` over the validators in proportion to their deficits, so delegations move toward the target weights
+pub fn allocate_stake_amount(
+    weights: &[(String, u64)],
+    delegations: &[Delegation],
+    amount: Uint128,
+) -> NeutronResult<Vec<(String, Uint128)>> {
+    let mut deficits = weighted_deficits(weights, delegations, amount)?;
+    let total_deficit: Uint128 = deficits.iter().map(|(_, d)| *d).sum();
+    if total_deficit.is_zero() {
+        // already on target, fall back to a plain weighted split
+        deficits = weighted_targets(weights, amount)?;
+    }
+    let total_deficit: Uint128 = deficits.iter().map(|(_, d)| *d).sum();
+
+    let mut allocations: Vec<(String, Uint128)> = deficits
+        .iter()
+        .map(|(validator, deficit)| {
+            (
+                validator.clone(),
+                amount.multiply_ratio(*deficit, total_deficit),
+            )
+        })
+        .collect();
+
+    // Add the remainder to the validator with the largest deficit
+    let allocated: Uint128 = allocations.iter().map(|(_, a)| *a).sum();
+    let remainder = amount.saturating_sub(allocated);
+    if let Some(index) = deficits
+        .iter()
+        .enumerate()
+        .max_by(|(ia, (_, a)), (ib, (_, b))| a.cmp(b).then(ib.cmp(ia)))
+        .map(|(index, _)| index)
+    {
+        allocations[index].1 += remainder;
+    }
+
+    allocations.retain(|(_, a)| !a.is_zero());
+    Ok(allocations)
+}
+
+// the validator that is furthest below its target once `amount` is added to the pool,
+// validators with weight 0 are being phased out and never picked
+pub fn most_underweight_validator(
+    weights: &[(String, u64)],
+    delegations: &[Delegation],
+    amount: Uint128,
+) -> NeutronResult<String> {
+    weighted_deficits(weights, delegations, amount)?
+        .into_iter()
+        .zip(weights)
+        .filter(|(_, (_, weight))| *weight > 0)
+        .map(|(deficit, _)| deficit)
+        .enumerate()
+        .max_by(|(ia, (_, a)), (ib, (_, b))| a.cmp(b).then(ib.cmp(ia)))
+        .map(|(_, (validator, _))| validator)
+        .ok_or_else(|| ContractError::ValidatorsEmpty {}.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate_stake_amount, most_underweight_validator, split_fee};
+    use crate::state::FeeRecipient;
+    use cosmwasm_std::{coin, Addr, Delegation, Uint128};
+
+    fn delegation(validator: &str, amount: u128) -> Delegation {
+        Delegation {
+            delegator: Addr::unchecked("pool"),
+            validator: validator.to_string(),
+            amount: coin(amount, "uatom"),
+        }
+    }
+
+    #[test]
+    fn test_allocate_stake_amount() {
+        let weights = vec![
+            ("val1".to_string(), 100),
+            ("val2".to_string(), 300),
+            ("val3".to_string(), 0),
+        ];
+
+        // fresh pool: split by weight only
+        let allocations = allocate_stake_amount(&weights, &[], Uint128::new(1001)).unwrap();
+        assert_eq!(
+            allocations,
+            vec![
+                ("val1".to_string(), Uint128::new(250)),
+                ("val2".to_string(), Uint128::new(751)),
+            ]
+        );
+
+        // val1 is over its target, all new stake goes to val2
+        let delegations = vec![delegation("val1", 1000), delegation("val2", 1000)];
+        let allocations =
+            allocate_stake_amount(&weights, &delegations, Uint128::new(1000)).unwrap();
+        assert_eq!(allocations, vec![("val2".to_string(), Uint128::new(1000))]);
+
+        assert_eq!(
+            most_underweight_validator(&weights, &delegations, Uint128::new(10)).unwrap(),
+            "val2".to_string()
+        );
+
+        // a zero weight validator is never the target, even when nobody is below target
+        let balanced = vec![delegation("val1", 1000), delegation("val2", 3000)];
+        assert_eq!(
+            most_underweight_validator(&weights, &balanced, Uint128::zero()).unwrap(),
+            "val1".to_string()
+        );
+
+        // zero total weight is rejected
+        let weights = vec![("val1".to_string(), 0)];
+        assert!(allocate_stake_amount(&weights, &[], Uint128::new(10)).is_err());
+    }
+
+    #[test]
+    fn test_split_fee() {
+        let recipients = vec![
+            FeeRecipient {
+                receiver: Addr::unchecked("treasury"),
+                bps: 5000,
+            },
+            FeeRecipient {
+                receiver: Addr::unchecked("incentives"),
+                bps: 3333,
+            },
+            FeeRecipient {
+                receiver: Addr::unchecked("dev"),
+                bps: 1667,
+            },
+        ];
+
+        // the rounding remainder goes to the first recipient
+        assert_eq!(
+            split_fee(&recipients, Uint128::new(1001)),
+            vec![
+                (Addr::unchecked("treasury"), Uint128::new(502)),
+                (Addr::unchecked("incentives"), Uint128::new(333)),
+                (Addr::unchecked("dev"), Uint128::new(166)),
+            ]
+        );
+
+        // zero shares are left out
+        assert_eq!(
+            split_fee(&recipients, Uint128::new(2)),
+            vec![(Addr::unchecked("treasury"), Uint128::new(2))]
+        );
+    }
+}
@@ -10,28 +10,45 @@ pub mod execute_config_stack;
 pub mod execute_config_unbonding_seconds;
 pub mod execute_era_active;
 pub mod execute_era_collect_withdraw;
+pub mod execute_era_process;
+pub mod execute_era_recover;
+pub mod execute_era_rebalance;
 pub mod execute_era_restake;
 pub mod execute_era_stake;
 pub mod execute_era_update;
+pub mod execute_fee_vault;
+pub mod execute_gov_vote;
 pub mod execute_icq_update_period;
 pub mod execute_init_pool;
+pub mod execute_instant_unstake;
 pub mod execute_open_channel;
 pub mod execute_pool_add_validator;
 pub mod execute_pool_rm_validator;
+pub mod execute_pool_role;
 pub mod execute_pool_update_validator;
 pub mod execute_redeem_token_for_share;
+pub mod execute_referral;
 pub mod execute_register_pool;
 pub mod execute_stake;
 pub mod execute_stake_lsm;
+pub mod execute_timelock;
 pub mod execute_unstake;
+pub mod execute_unstake_ticket;
+pub mod execute_accept_rate;
+pub mod execute_admin_transfer;
 pub mod execute_admin_transfer_funds;
 pub mod execute_admin_unbond_all;
+pub mod execute_cancel_unstake;
 pub mod execute_update_validators_icq;
+pub mod execute_wind_down;
 pub mod execute_withdraw;
+pub mod execute_withdraw_to_neutron;
 pub mod helper;
 pub mod query;
 pub mod query_callback;
 pub mod state;
+#[cfg(test)]
+mod testing;
 pub mod tx_callback;
 
 #[allow(unused_imports)]
@@ -1,6 +1,10 @@
 use crate::state::{
-    BalanceResponse, DelegatorDelegationsResponse, EraSnapshot, IcaInfo, IcaInfos, PoolInfo,
-    QueryIds, QueryKind, Stack, UnstakeInfo,
+    BalanceResponse, ClosedChannel, DelegatorDelegationsResponse, EraRate, EraRecord, EraSnapshot,
+    FeeRecipient, GovProposal, GovSignal, GovVoteMode, GovVoteOption, IcaInfo, IcaInfos,
+    LiquidityBuffer, NeutronWithdrawInfo, PoolInfo, PoolSummary, QueryIds, QueryKind,
+    RateAprResponse, RateTwapResponse, ReferralInfo, ReferralPool, Role, SlashRecord,
+    SlashingAction, Stack, TimelockedOperation, UnstakeInfo, ValidatorWeight,
+    WeightedGovVoteOption, WindDown, WithdrawStatus,
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Coin, Uint128};
@@ -37,6 +41,8 @@ pub enum QueryMsg {
     },
     #[returns(ValidatorResponse)]
     Validators { pool_addr: String },
+    #[returns([ValidatorWeight])]
+    ValidatorWeights { pool_addr: String },
     #[returns(PoolInfo)]
     PoolInfo { pool_addr: String },
     #[returns(Stack)]
@@ -74,6 +80,111 @@ pub enum QueryMsg {
     QueryIds { pool_addr: String },
     #[returns(Vec<String>)]
     InterchainAccountIdFromCreator { addr: Addr },
+    #[returns([PoolSummary])]
+    Pools {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns([EraRate])]
+    EraRateHistory {
+        pool_addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// unstake records of a pool ordered by unstake index, optionally filtered by status
+    #[returns([UnstakeInfo])]
+    PoolUnstakes {
+        pool_addr: String,
+        status: Option<WithdrawStatus>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Vec<String>)]
+    InterchainAccountIds {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(LiquidityBuffer)]
+    LiquidityBuffer { pool_addr: String },
+    #[returns(NeutronWithdrawInfo)]
+    NeutronWithdraw { withdraw_id: u64 },
+    #[returns(Option<Addr>)]
+    ReferralCode { code: String },
+    /// totals of a referral code in the pool, unclaimed_fee includes the fees not settled yet
+    #[returns(Option<ReferralInfo>)]
+    Referral { pool_addr: String, code: String },
+    #[returns(ReferralPool)]
+    ReferralPool { pool_addr: String },
+    /// recipients the platform fee is split between, empty when platform_fee_receiver gets it all
+    #[returns([FeeRecipient])]
+    PlatformFeeRecipients { pool_addr: String },
+    #[returns(GovVoteMode)]
+    GovVoteMode { pool_addr: String },
+    #[returns(Option<GovProposal>)]
+    GovProposal { pool_addr: String, proposal_id: u64 },
+    #[returns(Option<GovSignal>)]
+    GovSignal {
+        pool_addr: String,
+        proposal_id: u64,
+        voter: Addr,
+    },
+    /// ica channels of the pool closed by a timeout, empty unless the pool is degraded
+    #[returns([ClosedChannel])]
+    ClosedChannels { pool_addr: String },
+    /// unstake ticket contract of the pool
+    #[returns(Option<Addr>)]
+    UnstakeTicket { pool_addr: String },
+    /// receiver that withdraw_for pays the user's unstakes to
+    #[returns(Option<Addr>)]
+    WithdrawReceiver { pool_addr: String, user: Addr },
+    /// admin waiting to accept, of the stack when pool_addr is none
+    #[returns(Option<Addr>)]
+    PendingAdmin { pool_addr: Option<String> },
+    /// roles granted to account in the pool, the pool admin holds all of them
+    #[returns([Role])]
+    Roles { pool_addr: String, account: Addr },
+    #[returns([Addr])]
+    RoleMembers {
+        pool_addr: String,
+        role: Role,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    #[returns([SlashRecord])]
+    SlashRecords {
+        pool_addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns([EraRecord])]
+    EraRecords {
+        pool_addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// rate change of the last `eras` eras annualized, from the era records' timestamps
+    #[returns(RateAprResponse)]
+    RateApr { pool_addr: String, eras: u64 },
+    /// time weighted average rate of the last `eras` eras
+    #[returns(RateTwapResponse)]
+    RateTwap { pool_addr: String, eras: u64 },
+    /// untrn paid per era_process step
+    #[returns(Uint128)]
+    KeeperBounty { pool_addr: String },
+    /// untrn in the pool's fee vault
+    #[returns(Uint128)]
+    FeeVault { pool_addr: String },
+    #[returns(Option<WindDown>)]
+    WindDown { pool_addr: String },
+    #[returns([TimelockedOperation])]
+    TimelockOperations {
+        pool_addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// seconds a queued operation waits before it can be executed
+    #[returns(u64)]
+    TimelockDelay { pool_addr: String },
 }
 
 #[cw_serde]
@@ -98,6 +209,7 @@ pub struct ConfigStackParams {
     pub new_admin: Option<Addr>,
     pub stack_fee_commission: Option<Uint128>,
     pub lsd_token_code_id: Option<u64>,
+    pub unstake_ticket_code_id: Option<u64>,
     pub add_entrusted_pool: Option<String>,
     pub remove_entrusted_pool: Option<String>,
 }
@@ -113,6 +225,7 @@ pub struct ConfigPoolParams {
     pub pool_addr: String,
     pub platform_fee_receiver: Option<String>,
     pub minimal_stake: Option<Uint128>,
+    // deprecated and ignored, kept so queued timelock operations still load
     pub unstake_times_limit: Option<u64>,
     pub unbond_commission: Option<Uint128>,
     pub platform_fee_commission: Option<Uint128>,
@@ -122,6 +235,25 @@ pub struct ConfigPoolParams {
     pub lsm_pending_limit: Option<u64>,
     pub rate_change_limit: Option<Uint128>,
     pub new_admin: Option<Addr>,
+    pub liquidity_buffer_target: Option<Uint128>,
+    pub instant_unstake_commission: Option<Uint128>,
+    pub host_channel_id_of_ibc_denom: Option<String>,
+    pub keeper_bounty: Option<Uint128>,
+    pub slashing_action: Option<SlashingAction>,
+    /// splits the platform fee by basis points, an empty list sends it all to platform_fee_receiver
+    pub platform_fee_recipients: Option<Vec<FeeRecipient>>,
+    /// share of the platform fee paid to referrers by the stake they brought
+    pub referral_fee_commission: Option<Uint128>,
+    /// whether the admin votes on host chain proposals or relays the lsd holders' signals
+    pub gov_vote_mode: Option<GovVoteMode>,
+}
+
+/// operations that need to wait for the pool's timelock delay
+#[cw_serde]
+pub enum TimelockOperation {
+    AdminTransferFunds { receiver: String, amount: Uint128 },
+    ConfigPool(Box<ConfigPoolParams>),
+    SetTimelockDelay { delay_seconds: u64 },
 }
 
 #[cw_serde]
@@ -134,6 +266,43 @@ pub enum ExecuteMsg {
     ConfigPool(Box<ConfigPoolParams>),
     ConfigStack(Box<ConfigStackParams>),
     ConfigPoolStackFee(Box<ConfigPoolStackFeeParams>),
+    /// called by the proposed new_admin of the pool, or of the stack when pool_addr is none
+    AcceptAdmin {
+        pool_addr: Option<String>,
+    },
+    /// called by the current admin to drop the proposed new_admin
+    CancelAdminTransfer {
+        pool_addr: Option<String>,
+    },
+    GrantRole {
+        pool_addr: String,
+        role: Role,
+        account: Addr,
+    },
+    RevokeRole {
+        pool_addr: String,
+        role: Role,
+        account: Addr,
+    },
+    QueueOperation {
+        pool_addr: String,
+        operation: TimelockOperation,
+    },
+    /// called by the admin or the proposer
+    CancelOperation {
+        pool_addr: String,
+        id: u64,
+    },
+    /// anyone can execute a queued operation once its eta has passed
+    ExecuteOperation {
+        pool_addr: String,
+        id: u64,
+    },
+    /// increasing the delay takes effect at once, decreasing it has to be queued
+    ConfigTimelockDelay {
+        pool_addr: String,
+        delay_seconds: u64,
+    },
     ConfigUnbondingSeconds {
         remote_denom: String,
         unbonding_seconds: Option<u64>,
@@ -146,6 +315,10 @@ pub enum ExecuteMsg {
         pool_addr: String,
         closed_channel_id: String,
     },
+    /// registers the icas whose channels a timeout closed again, anyone can call it
+    ReopenChannel {
+        pool_addr: String,
+    },
     RedeemTokenForShare {
         pool_addr: String,
         tokens: Vec<Coin>,
@@ -153,16 +326,71 @@ pub enum ExecuteMsg {
     Stake {
         neutron_address: String,
         pool_addr: String,
+        /// referral code registered by register_referral_code
+        referral: Option<String>,
+    },
+    /// stake called by ibc hooks for a transfer of remote_denom sent by host_sender on the host chain
+    StakeFromHost {
+        neutron_address: String,
+        pool_addr: String,
+        host_sender: String,
     },
     Unstake {
         amount: Uint128,
         pool_addr: String,
     },
+    /// undoes an unstake of the current era that no era snapshot includes yet, lsd token is minted back at the current rate
+    CancelUnstake {
+        pool_addr: String,
+        unstake_index: u64,
+    },
+    /// unstakes from the pool's liquidity buffer and sends the tokens to receiver on the host chain
+    InstantUnstake {
+        amount: Uint128,
+        pool_addr: String,
+        receiver: Addr,
+    },
     Withdraw {
         pool_addr: String,
         receiver: Addr,
         unstake_index_list: Vec<u64>,
     },
+    /// like withdraw, for all matured unstakes of the caller
+    WithdrawAll {
+        pool_addr: String,
+        receiver: Addr,
+    },
+    /// permissionless, pays the matured unstakes of user to the receiver set by set_withdraw_receiver
+    WithdrawFor {
+        pool_addr: String,
+        user: Addr,
+    },
+    /// sets the caller's receiver for withdraw_for, none clears it
+    SetWithdrawReceiver {
+        pool_addr: String,
+        receiver: Option<Addr>,
+    },
+    /// like withdraw, for the caller's unstakes of eras start_era..=end_era
+    WithdrawEras {
+        pool_addr: String,
+        receiver: Addr,
+        start_era: u64,
+        end_era: u64,
+    },
+    /// like withdraw, but the pool ica sends the tokens back to a neutron address by ibc transfer
+    WithdrawToNeutron {
+        pool_addr: String,
+        receiver: Addr,
+        unstake_index_list: Vec<u64>,
+    },
+    /// called by ibc hooks when the transfer of a withdraw to neutron arrives
+    WithdrawToNeutronReceived {
+        withdraw_id: u64,
+    },
+    /// releases the unstakes of a withdraw to neutron whose transfer timed out
+    WithdrawToNeutronExpired {
+        withdraw_id: u64,
+    },
     PoolRmValidator {
         pool_addr: String,
         validator_addr: String,
@@ -170,11 +398,14 @@ pub enum ExecuteMsg {
     PoolAddValidator {
         pool_addr: String,
         validator_addr: String,
+        weight: Option<u64>,
     },
+    /// replaces old_validator with new_validator, or only sets the weight when they are equal
     PoolUpdateValidator {
         pool_addr: String,
         old_validator: String,
         new_validator: String,
+        weight: Option<u64>,
     },
     PoolUpdateValidatorsIcq {
         pool_addr: String,
@@ -194,9 +425,96 @@ pub enum ExecuteMsg {
     EraActive {
         pool_addr: String,
     },
+    /// settles an era step whose ack never arrived, once its timeout has passed:
+    /// the first call refreshes the icq results, the next one decides on them
+    EraRecover {
+        pool_addr: String,
+    },
+    /// lets the era active waiting on rate_change_limit through if its rate is within
+    /// max_deviation (in 1e6 like rate_change_limit) of the expected rate
+    AcceptRate {
+        pool_addr: String,
+        rate: Uint128,
+        max_deviation: Uint128,
+    },
+    /// runs the next era step of the pool and pays the caller the keeper bounty
+    EraProcess {
+        pool_addr: String,
+    },
+    /// tops up the pool's fee vault with the attached untrn
+    FundFeeVault {
+        pool_addr: String,
+    },
+    /// takes the exit snapshot of a pool wound down by admin_unbond_all once the unbonding is over,
+    /// the first call refreshes the balance icqs, the next one sweeps the withdraw ica into the
+    /// pool ica if it holds anything, or else uses the pool ica balance
+    WindDownSnapshot {
+        pool_addr: String,
+    },
+    /// burns lsd token for its share of the snapshot, sent to receiver on the host chain
+    WindDownExit {
+        pool_addr: String,
+        lsd_token_amount: Uint128,
+        receiver: Addr,
+    },
+    /// sends untrn from the pool's fee vault to receiver, admin only
+    WithdrawFeeVault {
+        pool_addr: String,
+        receiver: Addr,
+        amount: Uint128,
+    },
+    /// instantiates the pool's unstake ticket contract, new unstakes are minted as transferable tickets
+    EnableUnstakeTicket {
+        pool_addr: String,
+        name: String,
+        symbol: String,
+    },
+    /// redelegates from validators over their target weight to those under it
+    EraRebalance {
+        pool_addr: String,
+    },
     StakeLsm {
         neutron_address: String,
         pool_addr: String,
+        referral: Option<String>,
+    },
+    /// registers a referral code owned by the caller
+    RegisterReferralCode {
+        code: String,
+    },
+    /// sends the referral fees the code earned in the pool to the code owner
+    ClaimReferralFee {
+        pool_addr: String,
+        code: String,
+    },
+    /// votes on a host chain proposal from the pool ica, a single option is sent as MsgVote
+    GovVote {
+        pool_addr: String,
+        proposal_id: u64,
+        options: Vec<WeightedGovVoteOption>,
+    },
+    /// opens a host chain proposal for lsd holders' signals until signal_end (seconds)
+    OpenGovProposal {
+        pool_addr: String,
+        proposal_id: u64,
+        signal_end: u64,
+    },
+    /// escrows amount lsd token (allowance needed) and moves the caller's signal to option
+    SignalGovVote {
+        pool_addr: String,
+        proposal_id: u64,
+        option: GovVoteOption,
+        amount: Uint128,
+    },
+    /// sends the tally as a weighted vote from the pool ica after signal_end
+    RelayGovVote {
+        pool_addr: String,
+        proposal_id: u64,
+    },
+    /// returns the caller's escrowed lsd token
+    ReclaimGovVote {
+        pool_addr: String,
+        proposal_id: u64,
     },
     UpdateIcqUpdatePeriod {
         pool_addr: String,
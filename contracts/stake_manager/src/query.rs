@@ -1,10 +1,26 @@
+use crate::execute_open_channel::load_closed_channels;
+use crate::execute_referral::settle_referral_fee;
+use crate::helper::{DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT};
+use crate::state::EraStatus::ActiveEnded;
+use crate::state::{load_timelock_delay, TimelockedOperation, TIMELOCKED_OPERATIONS};
 use crate::state::{
     BalanceResponse, Balances, DelegatorDelegationsResponse, IcaInfos, QueryIds, QueryKind,
-    DECIMALS, ERA_RATE, ICA_ID_OF_CREATOR, INFO_OF_ICA_ID, TOTAL_STACK_FEE, UNBONDING_SECONDS,
+    DECIMALS, ERA_RATE, ICA_ID_OF_CREATOR, INFO_OF_ICA_ID, LIQUIDITY_BUFFERS, NEUTRON_WITHDRAWS,
+    TOTAL_STACK_FEE, UNBONDING_SECONDS,
 };
-use crate::state::{ADDRESS_TO_REPLY_ID, STACK};
-use crate::state::{POOLS, REPLY_ID_TO_QUERY_ID, UNSTAKES_INDEX_FOR_USER, UNSTAKES_OF_INDEX};
-use cosmwasm_std::{to_json_binary, Addr, Binary, Deps, Env};
+use crate::state::{EraRate, PoolSummary, Role, UnstakeInfo, WithdrawStatus, POOL_ROLES};
+use crate::state::{EraRecord, RateAprResponse, RateTwapResponse, ERA_RECORDS};
+use crate::state::{SlashRecord, KEEPER_BOUNTIES, SLASH_RECORDS, WITHDRAW_RECEIVERS};
+use crate::state::{ADDRESS_TO_REPLY_ID, PENDING_POOL_ADMINS, PENDING_STACK_ADMIN, STACK};
+use crate::state::{FEE_VAULTS, WIND_DOWNS};
+use crate::state::{GOV_PROPOSALS, GOV_SIGNALS, GOV_VOTE_MODES};
+use crate::state::{PLATFORM_FEE_RECIPIENTS, UNSTAKE_TICKETS};
+use crate::state::{POOLS, REPLY_ID_TO_QUERY_ID, UNSTAKES_OF_INDEX, UNSTAKES_OF_USER};
+use crate::state::{REFERRALS, REFERRAL_CODES, REFERRAL_POOLS};
+use crate::{error_conversion::ContractError, helper::SECONDS_PER_YEAR};
+use crate::{helper::load_validator_weights, state::ValidatorWeight};
+use cosmwasm_std::{to_json_binary, Addr, Binary, Decimal, Deps, Env, Order, StdResult, Uint128};
+use cw_storage_plus::Bound;
 use neutron_sdk::{
     bindings::query::{
         NeutronQuery, QueryInterchainAccountAddressResponse, QueryRegisteredQueryResponse,
@@ -28,15 +44,10 @@ pub fn query_user_unstake(
     user_neutron_addr: Addr,
 ) -> NeutronResult<Binary> {
     let mut results = vec![];
-
-    if let Some(unstakes) =
-        UNSTAKES_INDEX_FOR_USER.may_load(deps.storage, (user_neutron_addr, pool_addr.clone()))?
-    {
-        for unstake_index in unstakes {
-            let unstake_info =
-                UNSTAKES_OF_INDEX.load(deps.storage, (pool_addr.clone(), unstake_index))?;
-            results.push(unstake_info);
-        }
+    for unstake_index in user_unstake_indexes(deps, pool_addr.clone(), user_neutron_addr)? {
+        let unstake_info =
+            UNSTAKES_OF_INDEX.load(deps.storage, (pool_addr.clone(), unstake_index))?;
+        results.push(unstake_info);
     }
 
     Ok(to_json_binary(&results)?)
@@ -46,9 +57,119 @@ pub fn query_user_unstake_index(
     deps: Deps<NeutronQuery>,
     pool_addr: String,
     user_neutron_addr: Addr,
+) -> NeutronResult<Binary> {
+    let unstake_indexes = user_unstake_indexes(deps, pool_addr, user_neutron_addr)?;
+    Ok(to_json_binary(
+        &(!unstake_indexes.is_empty()).then_some(unstake_indexes),
+    )?)
+}
+
+// the unstake indexes of a user in era order
+fn user_unstake_indexes(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    user_neutron_addr: Addr,
+) -> StdResult<Vec<u64>> {
+    UNSTAKES_OF_USER
+        .prefix((user_neutron_addr, pool_addr))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| key.map(|(_, unstake_index)| unstake_index))
+        .collect()
+}
+
+pub fn query_referral_code(deps: Deps<NeutronQuery>, code: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &REFERRAL_CODES.may_load(deps.storage, code)?,
+    )?)
+}
+
+pub fn query_referral(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    code: String,
+) -> NeutronResult<Binary> {
+    let referral_pool = REFERRAL_POOLS
+        .may_load(deps.storage, pool_addr.clone())?
+        .unwrap_or_default();
+    let referral = REFERRALS
+        .may_load(deps.storage, (pool_addr, code))?
+        .map(|mut referral| {
+            settle_referral_fee(&mut referral, &referral_pool);
+            referral
+        });
+
+    Ok(to_json_binary(&referral)?)
+}
+
+pub fn query_referral_pool(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &REFERRAL_POOLS
+            .may_load(deps.storage, pool_addr)?
+            .unwrap_or_default(),
+    )?)
+}
+
+pub fn query_closed_channels(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(&load_closed_channels(
+        deps.storage,
+        pool_addr,
+    )?)?)
+}
+
+pub fn query_gov_vote_mode(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &GOV_VOTE_MODES
+            .may_load(deps.storage, pool_addr)?
+            .unwrap_or_default(),
+    )?)
+}
+
+pub fn query_gov_proposal(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    proposal_id: u64,
 ) -> NeutronResult<Binary> {
     Ok(to_json_binary(
-        &UNSTAKES_INDEX_FOR_USER.may_load(deps.storage, (user_neutron_addr, pool_addr))?,
+        &GOV_PROPOSALS.may_load(deps.storage, (pool_addr, proposal_id))?,
+    )?)
+}
+
+pub fn query_gov_signal(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    proposal_id: u64,
+    voter: Addr,
+) -> NeutronResult<Binary> {
+    Ok(to_json_binary(&GOV_SIGNALS.may_load(
+        deps.storage,
+        (pool_addr, proposal_id, voter),
+    )?)?)
+}
+
+pub fn query_platform_fee_recipients(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &PLATFORM_FEE_RECIPIENTS
+            .may_load(deps.storage, pool_addr)?
+            .unwrap_or_default(),
+    )?)
+}
+
+pub fn query_unstake_ticket(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &UNSTAKE_TICKETS.may_load(deps.storage, pool_addr)?,
+    )?)
+}
+
+pub fn query_withdraw_receiver(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    user: Addr,
+) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &WITHDRAW_RECEIVERS.may_load(deps.storage, (user, pool_addr))?,
     )?)
 }
 
@@ -62,6 +183,37 @@ pub fn query_era_rate(
     )?)
 }
 
+pub fn query_validator_weights(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+) -> NeutronResult<Binary> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let weights: Vec<ValidatorWeight> =
+        load_validator_weights(deps.storage, pool_addr, &pool_info.validator_addrs)?
+            .into_iter()
+            .map(|(validator, weight)| ValidatorWeight { validator, weight })
+            .collect();
+
+    Ok(to_json_binary(&weights)?)
+}
+
+pub fn query_liquidity_buffer(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+) -> NeutronResult<Binary> {
+    let liquidity_buffer = LIQUIDITY_BUFFERS
+        .may_load(deps.storage, pool_addr)?
+        .unwrap_or_default();
+
+    Ok(to_json_binary(&liquidity_buffer)?)
+}
+
+pub fn query_neutron_withdraw(deps: Deps<NeutronQuery>, withdraw_id: u64) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &NEUTRON_WITHDRAWS.load(deps.storage, withdraw_id)?,
+    )?)
+}
+
 pub fn query_ids(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
     let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
     let (_, withdraw, _) = INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id)?;
@@ -230,6 +382,18 @@ pub fn query_stack_info(deps: Deps<NeutronQuery>) -> NeutronResult<Binary> {
     Ok(to_json_binary(&stack_info)?)
 }
 
+pub fn query_pending_admin(
+    deps: Deps<NeutronQuery>,
+    pool_addr: Option<String>,
+) -> NeutronResult<Binary> {
+    let pending_admin = match pool_addr {
+        Some(pool_addr) => PENDING_POOL_ADMINS.may_load(deps.storage, pool_addr)?,
+        None => PENDING_STACK_ADMIN.may_load(deps.storage)?,
+    };
+
+    Ok(to_json_binary(&pending_admin)?)
+}
+
 pub fn query_total_stack_fee(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
     Ok(to_json_binary(
         &TOTAL_STACK_FEE.load(deps.storage, pool_addr)?,
@@ -307,3 +471,475 @@ pub fn interchain_account_id_from_creator(
 
     Ok(to_json_binary(&ica_id_of_creator)?)
 }
+
+fn query_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize
+}
+
+pub fn query_pools(
+    deps: Deps<NeutronQuery>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let pools = POOLS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .map(|item| {
+            let (pool_addr, pool_info) = item?;
+            Ok(PoolSummary {
+                pool_addr,
+                ica_id: pool_info.ica_id,
+                admin: pool_info.admin,
+                lsd_token: pool_info.lsd_token,
+                ibc_denom: pool_info.ibc_denom,
+                remote_denom: pool_info.remote_denom,
+                era: pool_info.era,
+                rate: pool_info.rate,
+                active: pool_info.active,
+                total_lsd_token_amount: pool_info.total_lsd_token_amount,
+                status: pool_info.status,
+                paused: pool_info.paused,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(to_json_binary(&pools)?)
+}
+
+pub fn query_era_rate_history(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let rates = ERA_RATE
+        .prefix(pool_addr)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .map(|item| {
+            let (era, rate) = item?;
+            Ok(EraRate { era, rate })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(to_json_binary(&rates)?)
+}
+
+pub fn query_pool_unstakes(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    status: Option<WithdrawStatus>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let unstakes = UNSTAKES_OF_INDEX
+        .prefix(pool_addr)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .filter(|item| match (item, &status) {
+            (Ok((_, unstake_info)), Some(status)) => unstake_info.status == *status,
+            _ => true,
+        })
+        .take(query_limit(limit))
+        .map(|item| item.map(|(_, unstake_info)| unstake_info))
+        .collect::<StdResult<Vec<UnstakeInfo>>>()?;
+
+    Ok(to_json_binary(&unstakes)?)
+}
+
+pub fn query_interchain_account_ids(
+    deps: Deps<NeutronQuery>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let ica_ids = INFO_OF_ICA_ID
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(to_json_binary(&ica_ids)?)
+}
+
+pub fn query_roles(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    account: Addr,
+) -> NeutronResult<Binary> {
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let roles: Vec<Role> = Role::ALL
+        .into_iter()
+        .filter(|role| {
+            pool_info
+                .authorize_role(deps.storage, pool_addr.clone(), role.clone(), &account)
+                .is_ok()
+        })
+        .collect();
+
+    Ok(to_json_binary(&roles)?)
+}
+
+pub fn query_role_members(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    role: Role,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let members = POOL_ROLES
+        .prefix((pool_addr, role.key()))
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    Ok(to_json_binary(&members)?)
+}
+
+pub fn query_timelock_operations(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let operations = TIMELOCKED_OPERATIONS
+        .prefix(pool_addr)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .map(|item| item.map(|(_, operation)| operation))
+        .collect::<StdResult<Vec<TimelockedOperation>>>()?;
+
+    Ok(to_json_binary(&operations)?)
+}
+
+pub fn query_timelock_delay(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(&load_timelock_delay(
+        deps.storage,
+        pool_addr,
+    )?)?)
+}
+
+pub fn query_keeper_bounty(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &KEEPER_BOUNTIES
+            .may_load(deps.storage, pool_addr)?
+            .unwrap_or_default(),
+    )?)
+}
+
+pub fn query_fee_vault(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &FEE_VAULTS
+            .may_load(deps.storage, pool_addr)?
+            .unwrap_or_default(),
+    )?)
+}
+
+pub fn query_wind_down(deps: Deps<NeutronQuery>, pool_addr: String) -> NeutronResult<Binary> {
+    Ok(to_json_binary(
+        &WIND_DOWNS.may_load(deps.storage, pool_addr)?,
+    )?)
+}
+
+pub fn query_slash_records(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let eras = SLASH_RECORDS
+        .prefix(pool_addr)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .collect::<StdResult<Vec<(u64, Vec<SlashRecord>)>>>()?;
+    let slash_records: Vec<SlashRecord> =
+        eras.into_iter().flat_map(|(_, records)| records).collect();
+
+    Ok(to_json_binary(&slash_records)?)
+}
+
+pub fn query_era_records(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let era_records = ERA_RECORDS
+        .prefix(pool_addr)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(query_limit(limit))
+        .map(|item| item.map(|(_, era_record)| era_record))
+        .collect::<StdResult<Vec<EraRecord>>>()?;
+
+    Ok(to_json_binary(&era_records)?)
+}
+
+// (era, rate, era active time) of the last `eras` eras and the one before them,
+// taken from the era records so era_seconds changes don't skew the durations
+fn load_active_eras(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    eras: u64,
+) -> NeutronResult<Vec<(u64, Uint128, u64)>> {
+    if eras == 0 {
+        return Err(ContractError::PeriodTooSmall {}.into());
+    }
+    let eras = eras.min(MAX_QUERY_LIMIT as u64);
+    let pool_info = POOLS.load(deps.storage, pool_addr.clone())?;
+    let end_era = if pool_info.status == ActiveEnded {
+        pool_info.era
+    } else {
+        pool_info.era.saturating_sub(1)
+    };
+
+    (end_era.saturating_sub(eras)..=end_era)
+        .map(|era| {
+            let active_time = ERA_RECORDS
+                .may_load(deps.storage, (pool_addr.clone(), era))?
+                .and_then(|era_record| {
+                    era_record
+                        .steps
+                        .iter()
+                        .rev()
+                        .find(|s| s.step == "era_active")
+                        .map(|s| (era_record.rate, s.timestamp))
+                });
+            let (rate, timestamp) = active_time.ok_or(ContractError::EraRecordNotFound(era))?;
+            Ok((era, rate, timestamp))
+        })
+        .collect()
+}
+
+pub fn query_rate_apr(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    eras: u64,
+) -> NeutronResult<Binary> {
+    let active_eras = load_active_eras(deps, pool_addr, eras)?;
+    let (start_era, start_rate, start_time) = active_eras[0];
+    let (end_era, end_rate, end_time) = active_eras[active_eras.len() - 1];
+    if end_time <= start_time {
+        return Err(ContractError::PeriodTooSmall {}.into());
+    }
+    let duration = end_time - start_time;
+
+    let apr = Decimal::checked_from_ratio(end_rate, start_rate)
+        .map_err(|_| ContractError::EraRateZero(start_era))?
+        .saturating_sub(Decimal::one())
+        .checked_mul(Decimal::from_ratio(SECONDS_PER_YEAR, duration))?;
+    let eras_per_year = (SECONDS_PER_YEAR * (end_era - start_era) / duration) as u32;
+    let apy = if eras_per_year == 0 {
+        apr
+    } else {
+        (Decimal::one() + apr / Decimal::from_ratio(eras_per_year, 1u128))
+            .checked_pow(eras_per_year)?
+            .saturating_sub(Decimal::one())
+    };
+
+    Ok(to_json_binary(&RateAprResponse {
+        start_era,
+        end_era,
+        start_time,
+        end_time,
+        start_rate,
+        end_rate,
+        apr,
+        apy,
+    })?)
+}
+
+pub fn query_rate_twap(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    eras: u64,
+) -> NeutronResult<Binary> {
+    let active_eras = load_active_eras(deps, pool_addr, eras)?;
+    let (start_era, _, start_time) = active_eras[0];
+    let (end_era, _, end_time) = active_eras[active_eras.len() - 1];
+    if end_time <= start_time {
+        return Err(ContractError::PeriodTooSmall {}.into());
+    }
+
+    // each rate holds from its era active until the next one
+    let weighted_sum = active_eras
+        .windows(2)
+        .map(|pair| pair[0].1.u128() * (pair[1].2 - pair[0].2) as u128)
+        .sum::<u128>();
+
+    Ok(to_json_binary(&RateTwapResponse {
+        start_era,
+        end_era,
+        start_time,
+        end_time,
+        twap: Uint128::new(weighted_sum / (end_time - start_time) as u128),
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{from_json, Uint128};
+
+    use super::{query_rate_apr, query_rate_twap};
+    use crate::helper::MAX_QUERY_LIMIT;
+    use crate::state::{
+        EraRecord, EraStatus, EraStepRecord, PoolInfo, RateAprResponse, RateTwapResponse,
+        ERA_RECORDS,
+    };
+    use crate::testing::{mock_neutron_dependencies, save_pool, NeutronDeps, POOL_ADDR};
+
+    const DAY: u64 = 86400;
+    const START_TIME: u64 = 1_700_000_000;
+
+    fn save_era_record(deps: &mut NeutronDeps, era: u64, rate: u128, timestamp: u64) {
+        ERA_RECORDS
+            .save(
+                deps.as_mut().storage,
+                (POOL_ADDR.to_string(), era),
+                &EraRecord {
+                    era,
+                    rate: Uint128::new(rate),
+                    steps: vec![
+                        EraStepRecord {
+                            step: "era_update".to_string(),
+                            height: era,
+                            timestamp: timestamp - 60,
+                        },
+                        EraStepRecord {
+                            step: "era_active".to_string(),
+                            height: era,
+                            timestamp,
+                        },
+                    ],
+                    ..EraRecord::default()
+                },
+            )
+            .unwrap();
+    }
+
+    // eras 0..=end_era, one a day, the rate grows by `step` each era
+    fn pool_with_eras(end_era: u64, start_rate: u128, step: u128) -> NeutronDeps {
+        let mut deps = mock_neutron_dependencies();
+        save_pool(
+            deps.as_mut().storage,
+            PoolInfo {
+                era: end_era,
+                status: EraStatus::ActiveEnded,
+                ..PoolInfo::default()
+            },
+        );
+        for era in 0..=end_era {
+            save_era_record(
+                &mut deps,
+                era,
+                start_rate + step * era as u128,
+                START_TIME + era * DAY,
+            );
+        }
+        deps
+    }
+
+    #[test]
+    fn test_rate_apr() {
+        let deps = pool_with_eras(10, 1_000_000, 1_000);
+
+        let resp: RateAprResponse =
+            from_json(query_rate_apr(deps.as_ref(), POOL_ADDR.to_string(), 2).unwrap()).unwrap();
+        assert_eq!((resp.start_era, resp.end_era), (8, 10));
+        assert_eq!(resp.start_rate, Uint128::new(1_008_000));
+        assert_eq!(resp.end_rate, Uint128::new(1_010_000));
+        assert_eq!(resp.end_time - resp.start_time, 2 * DAY);
+        // 2000 / 1008000 over two days
+        let apr = resp.apr.to_string().parse::<f64>().unwrap();
+        assert!((apr - 2000.0 / 1_008_000.0 * 365.0 / 2.0).abs() < 1e-9);
+        assert!(resp.apy > resp.apr);
+
+        // a falling rate gives zero
+        let mut deps = pool_with_eras(3, 1_010_000, 0);
+        save_era_record(&mut deps, 3, 1_000_000, START_TIME + 3 * DAY);
+        let resp: RateAprResponse =
+            from_json(query_rate_apr(deps.as_ref(), POOL_ADDR.to_string(), 3).unwrap()).unwrap();
+        assert!(resp.apr.is_zero());
+        assert!(resp.apy.is_zero());
+    }
+
+    #[test]
+    fn test_rate_apr_zero_start_rate() {
+        let mut deps = pool_with_eras(2, 1_000_000, 1_000);
+        save_era_record(&mut deps, 0, 0, START_TIME);
+
+        assert!(query_rate_apr(deps.as_ref(), POOL_ADDR.to_string(), 2).is_err());
+        assert!(query_rate_apr(deps.as_ref(), POOL_ADDR.to_string(), 1).is_ok());
+    }
+
+    #[test]
+    fn test_rate_twap() {
+        let mut deps = pool_with_eras(4, 1_000_000, 0);
+        // era 3 lasts two days before era 4 becomes active
+        save_era_record(&mut deps, 3, 1_003_000, START_TIME + 2 * DAY + DAY / 2);
+
+        let resp: RateTwapResponse =
+            from_json(query_rate_twap(deps.as_ref(), POOL_ADDR.to_string(), 2).unwrap()).unwrap();
+        assert_eq!((resp.start_era, resp.end_era), (2, 4));
+        assert_eq!(resp.end_time - resp.start_time, 2 * DAY);
+        // 1000000 for half a day, 1003000 for a day and a half
+        assert_eq!(resp.twap, Uint128::new(1_002_250));
+
+        // a missing record in the window is an error
+        ERA_RECORDS.remove(deps.as_mut().storage, (POOL_ADDR.to_string(), 3));
+        assert!(query_rate_twap(deps.as_ref(), POOL_ADDR.to_string(), 2).is_err());
+    }
+
+    #[test]
+    fn test_rate_query_eras_clamped() {
+        let end_era = MAX_QUERY_LIMIT as u64 + 20;
+        let mut deps = pool_with_eras(end_era, 1_000_000, 0);
+        // only the clamped window is loaded, older records may be gone
+        for era in 0..(end_era - MAX_QUERY_LIMIT as u64) {
+            ERA_RECORDS.remove(deps.as_mut().storage, (POOL_ADDR.to_string(), era));
+        }
+
+        let resp: RateTwapResponse =
+            from_json(query_rate_twap(deps.as_ref(), POOL_ADDR.to_string(), u64::MAX).unwrap())
+                .unwrap();
+        assert_eq!(resp.start_era, end_era - MAX_QUERY_LIMIT as u64);
+        assert_eq!(resp.end_era, end_era);
+        assert_eq!(resp.twap, Uint128::new(1_000_000));
+    }
+}
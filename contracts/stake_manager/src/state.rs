@@ -1,9 +1,13 @@
 use crate::error_conversion::ContractError;
 use crate::helper::{
-    QUERY_REPLY_ID_RANGE_END, QUERY_REPLY_ID_RANGE_START, REPLY_ID_RANGE_END, REPLY_ID_RANGE_START,
+    DEFAULT_TIMELOCK_DELAY_SECONDS, QUERY_REPLY_ID_RANGE_END, QUERY_REPLY_ID_RANGE_START,
+    REPLY_ID_RANGE_END, REPLY_ID_RANGE_START,
 };
+use crate::msg::TimelockOperation;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{from_json, to_json_vec, Addr, Binary, Coin, StdResult, Storage, Uint128};
+use cosmwasm_std::{
+    from_json, to_json_vec, Addr, Binary, Coin, Decimal, Env, Order, StdResult, Storage, Uint128,
+};
 use cw_storage_plus::{Item, Map};
 use neutron_sdk::NeutronResult;
 
@@ -27,6 +31,37 @@ impl Stack {
 
 pub const STACK: Item<Stack> = Item::new("stack");
 
+// admin proposed by the stack admin, waiting to accept
+pub const PENDING_STACK_ADMIN: Item<Addr> = Item::new("pending_stack_admin");
+
+// pool -> admin proposed by the pool admin, waiting to accept
+pub const PENDING_POOL_ADMINS: Map<String, Addr> = Map::new("pending_pool_admins");
+
+// pool -> seconds a sensitive operation waits in the timelock
+pub const TIMELOCK_DELAYS: Map<String, u64> = Map::new("timelock_delays");
+
+pub fn load_timelock_delay(store: &dyn Storage, pool_addr: String) -> StdResult<u64> {
+    Ok(TIMELOCK_DELAYS
+        .may_load(store, pool_addr)?
+        .unwrap_or(DEFAULT_TIMELOCK_DELAY_SECONDS))
+}
+
+#[cw_serde]
+pub struct TimelockedOperation {
+    pub id: u64,
+    pub pool_addr: String,
+    pub operation: TimelockOperation,
+    pub proposer: Addr,
+    // seconds, earliest time the operation can be executed
+    pub eta: u64,
+}
+
+pub const NEXT_TIMELOCK_ID: Item<u64> = Item::new("next_timelock_id");
+
+// (pool,id) -> queued operation
+pub const TIMELOCKED_OPERATIONS: Map<(String, u64), TimelockedOperation> =
+    Map::new("timelocked_operations");
+
 pub const TOTAL_STACK_FEE: Map<String, Uint128> = Map::new("total_stack_fee");
 
 #[cw_serde]
@@ -55,6 +90,7 @@ pub struct PoolInfo {
     pub era_seconds: u64,
     pub offset: i64,
     pub minimal_stake: Uint128,
+    // deprecated, unstakes are no longer capped; kept so stored pools still load
     pub unstake_times_limit: u64,
     pub next_unstake_index: u64,
     pub unbonding_period: u64,
@@ -133,6 +169,20 @@ impl PoolInfo {
         Err(ContractError::Unauthorized {}.into())
     }
 
+    // the pool admin holds every role
+    pub fn authorize_role(
+        &self,
+        store: &dyn Storage,
+        pool_addr: String,
+        role: Role,
+        addr: &Addr,
+    ) -> NeutronResult<()> {
+        if *addr == self.admin || POOL_ROLES.has(store, (pool_addr, role.key(), addr.clone())) {
+            return Ok(());
+        }
+        Err(ContractError::Unauthorized {}.into())
+    }
+
     pub fn require_era_ended(&self) -> NeutronResult<()> {
         if self.status != EraStatus::ActiveEnded {
             return Err(ContractError::EraProcessNotEnd {}.into());
@@ -150,6 +200,31 @@ impl PoolInfo {
 
 pub const POOLS: Map<String, PoolInfo> = Map::new("pools");
 
+#[cw_serde]
+pub enum Role {
+    // can only set paused
+    Pauser,
+    // adds, removes and updates validators of the pool
+    ValidatorManager,
+    // sets commissions and fee receivers
+    FeeManager,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Pauser, Role::ValidatorManager, Role::FeeManager];
+
+    pub fn key(&self) -> String {
+        match self {
+            Role::Pauser => "pauser".to_string(),
+            Role::ValidatorManager => "validator_manager".to_string(),
+            Role::FeeManager => "fee_manager".to_string(),
+        }
+    }
+}
+
+// (pool,role,account) -> granted
+pub const POOL_ROLES: Map<(String, String, Addr), bool> = Map::new("pool_roles");
+
 #[cw_serde]
 pub enum EraStatus {
     RegisterEnded,
@@ -187,6 +262,9 @@ pub struct UnstakeInfo {
     pub amount: Uint128,
     pub status: WithdrawStatus,
     pub index: u64,
+    // moved from active to unbond, amount keeps a precision margin back from it
+    #[serde(default)]
+    pub unbond_amount: Uint128,
 }
 
 // (poolAddress,unstakeIndex)
@@ -226,6 +304,55 @@ pub struct QueryIds {
     pub pool_validators_query_id: u64,
 }
 
+// for rpc query
+#[cw_serde]
+pub struct PoolSummary {
+    pub pool_addr: String,
+    pub ica_id: String,
+    pub admin: Addr,
+    pub lsd_token: Addr,
+    pub ibc_denom: String,
+    pub remote_denom: String,
+    pub era: u64,
+    pub rate: Uint128,
+    pub active: Uint128,
+    pub total_lsd_token_amount: Uint128,
+    pub status: EraStatus,
+    pub paused: bool,
+}
+
+// for rpc query
+#[cw_serde]
+pub struct EraRate {
+    pub era: u64,
+    pub rate: Uint128,
+}
+
+// for rpc query, over the eras between the era active of start_era and end_era
+#[cw_serde]
+pub struct RateAprResponse {
+    pub start_era: u64,
+    pub end_era: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub start_rate: Uint128,
+    pub end_rate: Uint128,
+    // zero when the rate dropped
+    pub apr: Decimal,
+    // apr compounded once per era
+    pub apy: Decimal,
+}
+
+// for rpc query, each era's rate weighted by how long it was the latest
+#[cw_serde]
+pub struct RateTwapResponse {
+    pub start_era: u64,
+    pub end_era: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub twap: Uint128,
+}
+
 #[cw_serde]
 pub struct IcaInfo {
     pub ctrl_connection_id: String,
@@ -252,10 +379,77 @@ impl Default for IcaInfo {
 //  key: ica id value: (pool IcaInfo, withdraw icaInfo, admin)
 pub const INFO_OF_ICA_ID: Map<String, (IcaInfo, IcaInfo, Addr)> = Map::new("info_of_ica_id");
 
-// (userAddress,poolAddress) => []unstakeIndex
+#[cw_serde]
+pub struct ClosedChannel {
+    pub channel_id: String,
+    // false for the withdraw ica
+    pub is_pool_ica: bool,
+    // when reopen_channel last registered the ica again, 0 if not yet
+    pub reopen_at: u64,
+}
+
+// (pool, ctrl channel id) -> ica channel closed by a packet timeout, txs over it wait for the reopen
+pub const CLOSED_CHANNELS: Map<(String, String), ClosedChannel> = Map::new("closed_channels");
+
+// (userAddress,poolAddress) => []unstakeIndex, legacy list moved into UNSTAKES_OF_USER by migrate
 pub const UNSTAKES_INDEX_FOR_USER: Map<(Addr, String), Vec<u64>> =
     Map::new("unstakes_index_for_user");
 
+// (userAddress,poolAddress,(era,unstakeIndex)) => (), the unstakes of a user in era order
+pub const UNSTAKES_OF_USER: Map<(Addr, String, (u64, u64)), ()> = Map::new("unstakes_of_user");
+
+// (userAddress,poolAddress,era) => unstakeIndex, the unstakes of a user in one era share a claim
+pub const UNSTAKE_INDEX_OF_ERA: Map<(Addr, String, u64), u64> = Map::new("unstake_index_of_era");
+
+// moves the legacy per user index lists into UNSTAKES_OF_USER
+pub fn migrate_unstakes_of_user(store: &mut dyn Storage) -> StdResult<()> {
+    let legacy = UNSTAKES_INDEX_FOR_USER
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((user, pool_addr), unstake_indexes) in legacy {
+        for unstake_index in unstake_indexes {
+            if let Some(unstake_info) =
+                UNSTAKES_OF_INDEX.may_load(store, (pool_addr.clone(), unstake_index))?
+            {
+                let key = (
+                    user.clone(),
+                    pool_addr.clone(),
+                    (unstake_info.era, unstake_index),
+                );
+                UNSTAKES_OF_USER.save(store, key, &())?;
+            }
+        }
+        UNSTAKES_INDEX_FOR_USER.remove(store, (user, pool_addr));
+    }
+    Ok(())
+}
+
+#[cw_serde]
+pub struct FeeRecipient {
+    pub receiver: Addr,
+    // share of the platform fee in basis points
+    pub bps: u64,
+}
+
+// pool -> recipients the platform fee is split between, the platform_fee_receiver gets it all when unset
+pub const PLATFORM_FEE_RECIPIENTS: Map<String, Vec<FeeRecipient>> =
+    Map::new("platform_fee_recipients");
+
+// pool -> next_unstake_index when the last era snapshot was taken, later unstakes aren't in a snapshot
+pub const SNAPSHOT_UNSTAKE_INDEXES: Map<String, u64> = Map::new("snapshot_unstake_indexes");
+
+// code id the unstake ticket contracts of pools are instantiated from
+pub const UNSTAKE_TICKET_CODE_ID: Item<u64> = Item::new("unstake_ticket_code_id");
+
+// pool -> unstake ticket contract, new unstakes of the pool are minted into it
+pub const UNSTAKE_TICKETS: Map<String, Addr> = Map::new("unstake_tickets");
+
+// (poolAddress,unstakeIndex) => unstake ticket contract, the ticket owner withdraws the unstake
+pub const TICKETED_UNSTAKES: Map<(String, u64), Addr> = Map::new("ticketed_unstakes");
+
+// (userAddress,poolAddress) => receiver that withdraw_for pays the user's matured unstakes to
+pub const WITHDRAW_RECEIVERS: Map<(Addr, String), Addr> = Map::new("withdraw_receivers");
+
 // contains query kinds that we expect to handle in `sudo_kv_query_result`
 #[cw_serde]
 pub enum QueryKind {
@@ -322,15 +516,246 @@ pub enum TxType {
     StakeLsm,
     AdminUnbondAll,
     AdminTransfer,
+    EraRebalance,
+    InstantUnstake,
+    UserWithdrawToNeutron,
+    GovVote,
+    WindDownExit,
+    WindDownSweep,
+}
+
+#[cw_serde]
+pub enum TxPayload {
+    SetWithdrawAddr {
+        withdraw_addr: String,
+    },
+    // redelegations are (src validator, dst validator)
+    UpdateValidator {
+        new_validators: Vec<String>,
+        #[serde(default)]
+        redelegations: Vec<(String, String)>,
+    },
+    RmValidator {
+        validator: String,
+        #[serde(default)]
+        redelegations: Vec<(String, String)>,
+    },
+    UserWithdraw {
+        amount: Uint128,
+        user: Addr,
+        receiver: String,
+        unstake_index_list: Vec<u64>,
+    },
+    EraUpdate {},
+    EraBond {
+        unbond_validators: Vec<String>,
+        buffer_top_up: Uint128,
+    },
+    EraCollectWithdraw {},
+    EraRebond {},
+    RedeemTokenForShare {
+        denoms: Vec<String>,
+    },
+    StakeLsm {
+        staker: String,
+        token_amount: Uint128,
+        share_token_amount: Uint128,
+        share_token_ibc_denom: String,
+        share_token_denom: String,
+        referral: Option<String>,
+    },
+    AdminUnbondAll {
+        validators: Vec<String>,
+    },
+    AdminTransfer {
+        amount: Uint128,
+        receiver: String,
+    },
+    // (src validator, dst validator)
+    EraRebalance {
+        redelegations: Vec<(String, String)>,
+    },
+    InstantUnstake {
+        user: Addr,
+        receiver: String,
+        lsd_token_amount: Uint128,
+        token_amount: Uint128,
+    },
+    UserWithdrawToNeutron {
+        withdraw_id: u64,
+    },
+    GovVote {
+        proposal_id: u64,
+        options: Vec<WeightedGovVoteOption>,
+        // relayed tally of an aggregated proposal
+        aggregated: bool,
+    },
+    WindDownExit {
+        user: Addr,
+        receiver: String,
+        lsd_token_amount: Uint128,
+        token_amount: Uint128,
+        neutron_amount: Uint128,
+    },
+    WindDownSweep {
+        amount: Uint128,
+    },
+}
+
+impl TxPayload {
+    pub fn tx_type(&self) -> TxType {
+        match self {
+            TxPayload::SetWithdrawAddr { .. } => TxType::SetWithdrawAddr,
+            TxPayload::UpdateValidator { .. } => TxType::UpdateValidator,
+            TxPayload::RmValidator { .. } => TxType::RmValidator,
+            TxPayload::UserWithdraw { .. } => TxType::UserWithdraw,
+            TxPayload::EraUpdate {} => TxType::EraUpdate,
+            TxPayload::EraBond { .. } => TxType::EraBond,
+            TxPayload::EraCollectWithdraw {} => TxType::EraCollectWithdraw,
+            TxPayload::EraRebond {} => TxType::EraRebond,
+            TxPayload::RedeemTokenForShare { .. } => TxType::RedeemTokenForShare,
+            TxPayload::StakeLsm { .. } => TxType::StakeLsm,
+            TxPayload::AdminUnbondAll { .. } => TxType::AdminUnbondAll,
+            TxPayload::AdminTransfer { .. } => TxType::AdminTransfer,
+            TxPayload::EraRebalance { .. } => TxType::EraRebalance,
+            TxPayload::InstantUnstake { .. } => TxType::InstantUnstake,
+            TxPayload::UserWithdrawToNeutron { .. } => TxType::UserWithdrawToNeutron,
+            TxPayload::GovVote { .. } => TxType::GovVote,
+            TxPayload::WindDownExit { .. } => TxType::WindDownExit,
+            TxPayload::WindDownSweep { .. } => TxType::WindDownSweep,
+        }
+    }
+
+    pub fn unsupported(&self) -> ContractError {
+        ContractError::UnsupportedMessage(format!("{:?}", self))
+    }
 }
+
 #[cw_serde]
 pub struct SudoPayload {
+    pub message: TxPayload,
+    pub pool_addr: String,
+    pub port_id: String,
+}
+
+// payload format used before TxPayload, the message fields were joined by `_`
+#[cw_serde]
+pub struct LegacySudoPayload {
     pub message: String,
     pub pool_addr: String,
     pub port_id: String,
     pub tx_type: TxType,
 }
 
+impl TryFrom<LegacySudoPayload> for SudoPayload {
+    type Error = ContractError;
+
+    fn try_from(legacy: LegacySudoPayload) -> Result<Self, Self::Error> {
+        let unsupported = || ContractError::UnsupportedMessage(legacy.message.clone());
+        let split = |sep: char| -> Vec<String> {
+            if legacy.message.is_empty() {
+                vec![]
+            } else {
+                legacy.message.split(sep).map(String::from).collect()
+            }
+        };
+        let parse_amount =
+            |s: &String| -> Result<Uint128, ContractError> { s.parse().map_err(|_| unsupported()) };
+
+        let message = match legacy.tx_type {
+            TxType::SetWithdrawAddr => TxPayload::SetWithdrawAddr {
+                withdraw_addr: legacy.message.clone(),
+            },
+            TxType::UpdateValidator => TxPayload::UpdateValidator {
+                new_validators: split('_'),
+                redelegations: vec![],
+            },
+            TxType::RmValidator => TxPayload::RmValidator {
+                validator: legacy.message.clone(),
+                redelegations: vec![],
+            },
+            TxType::UserWithdraw => {
+                let parts = split('_');
+                if parts.len() <= 3 {
+                    return Err(unsupported());
+                }
+                TxPayload::UserWithdraw {
+                    amount: parse_amount(&parts[0])?,
+                    user: Addr::unchecked(parts[1].clone()),
+                    receiver: parts[2].clone(),
+                    unstake_index_list: parts[3..]
+                        .iter()
+                        .map(|index| index.parse::<u64>().map_err(|_| unsupported()))
+                        .collect::<Result<Vec<u64>, ContractError>>()?,
+                }
+            }
+            TxType::EraUpdate => TxPayload::EraUpdate {},
+            TxType::EraBond => TxPayload::EraBond {
+                unbond_validators: split('_'),
+                buffer_top_up: Uint128::zero(),
+            },
+            TxType::EraCollectWithdraw => TxPayload::EraCollectWithdraw {},
+            TxType::EraRebond => TxPayload::EraRebond {},
+            TxType::RedeemTokenForShare => TxPayload::RedeemTokenForShare { denoms: split(',') },
+            TxType::StakeLsm => {
+                let parts = split('_');
+                if parts.len() != 5 {
+                    return Err(unsupported());
+                }
+                TxPayload::StakeLsm {
+                    staker: parts[0].clone(),
+                    token_amount: parse_amount(&parts[1])?,
+                    share_token_amount: parse_amount(&parts[2])?,
+                    share_token_ibc_denom: parts[3].clone(),
+                    share_token_denom: parts[4].clone(),
+                    referral: None,
+                }
+            }
+            TxType::AdminUnbondAll => TxPayload::AdminUnbondAll {
+                validators: split('_'),
+            },
+            TxType::AdminTransfer => {
+                let (amount, receiver) = legacy.message.split_once('_').ok_or_else(unsupported)?;
+                TxPayload::AdminTransfer {
+                    amount: parse_amount(&amount.to_string())?,
+                    receiver: receiver.to_string(),
+                }
+            }
+            TxType::EraRebalance => TxPayload::EraRebalance {
+                redelegations: split('_')
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [src, dst] => Ok((src.clone(), dst.clone())),
+                        _ => Err(unsupported()),
+                    })
+                    .collect::<Result<Vec<(String, String)>, ContractError>>()?,
+            },
+            // introduced after the typed payload, never stored in the legacy format
+            TxType::InstantUnstake
+            | TxType::UserWithdrawToNeutron
+            | TxType::GovVote
+            | TxType::WindDownExit
+            | TxType::WindDownSweep => return Err(unsupported()),
+        };
+
+        Ok(SudoPayload {
+            message,
+            pool_addr: legacy.pool_addr,
+            port_id: legacy.port_id,
+        })
+    }
+}
+
+// payloads saved before the upgrade are still in flight, so fall back to the legacy format
+fn parse_sudo_payload(data: Vec<u8>) -> StdResult<SudoPayload> {
+    let data = Binary(data);
+    if let Ok(payload) = from_json::<SudoPayload>(&data) {
+        return Ok(payload);
+    }
+    let legacy: LegacySudoPayload = from_json(&data)?;
+    Ok(legacy.try_into()?)
+}
+
 pub const REPLY_ID_TO_PAYLOAD: Map<u64, Vec<u8>> = Map::new("reply_id_to_payload");
 pub fn save_reply_payload(store: &mut dyn Storage, payload: SudoPayload) -> StdResult<u64> {
     let id = get_next_reply_id(store)?;
@@ -339,7 +764,7 @@ pub fn save_reply_payload(store: &mut dyn Storage, payload: SudoPayload) -> StdR
 }
 pub fn read_reply_payload(store: &dyn Storage, id: u64) -> StdResult<SudoPayload> {
     let data = REPLY_ID_TO_PAYLOAD.load(store, id)?;
-    from_json(Binary(data))
+    parse_sudo_payload(data)
 }
 
 /// SUDO_PAYLOAD - tmp storage for sudo handler payloads
@@ -362,7 +787,13 @@ pub fn read_sudo_payload(
     seq_id: u64,
 ) -> StdResult<SudoPayload> {
     let data = SUDO_PAYLOAD.load(store, (channel_id, seq_id))?;
-    from_json(Binary(data))
+    parse_sudo_payload(data)
+}
+
+// drops an in flight payload, so a late ack can't run the callback again
+pub fn remove_sudo_payload(store: &mut dyn Storage, channel_id: String, seq_id: u64) {
+    SUDO_PAYLOAD.remove(store, (channel_id.clone(), seq_id));
+    PACKET_FEES.remove(store, (channel_id, seq_id));
 }
 
 // key: (ica address, query kind) value: query reply id
@@ -380,9 +811,345 @@ pub const QUERY_ID_TO_REPLY_ID: Map<u64, u64> = Map::new("query_id_to_reply_id")
 pub const VALIDATORS_UNBONDS_TIME: Map<(String, String), Vec<u64>> =
     Map::new("validators_unbonds_time");
 
+// (pool,src validator,dst validator) -> vec[timestamp]
+pub const VALIDATORS_REDELEGATIONS_TIME: Map<(String, String, String), Vec<u64>> =
+    Map::new("validators_redelegations_time");
+
+// (pool,validator) -> target weight
+pub const VALIDATOR_WEIGHTS: Map<(String, String), u64> = Map::new("validator_weights");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct LiquidityBuffer {
+    // size the buffer is topped up to from new bond
+    pub target: Uint128,
+    // undelegated tokens on the pool ica available for instant unstake
+    pub amount: Uint128,
+    // sent by instant unstakes that are not acknowledged yet
+    pub pending: Uint128,
+    pub instant_unstake_commission: Uint128,
+}
+
+// pool -> liquidity buffer
+pub const LIQUIDITY_BUFFERS: Map<String, LiquidityBuffer> = Map::new("liquidity_buffers");
+
+// pool -> host chain end of channel_id_of_ibc_denom
+pub const HOST_CHANNEL_ID_OF_IBC_DENOM: Map<String, String> =
+    Map::new("host_channel_id_of_ibc_denom");
+
+#[cw_serde]
+pub struct NeutronWithdrawInfo {
+    pub pool_addr: String,
+    pub unstaker: Addr,
+    pub receiver: Addr,
+    pub amount: Uint128,
+    pub unstake_index_list: Vec<u64>,
+    // seconds, the ibc transfer can not be received after it
+    pub timeout_timestamp: u64,
+    pub ica_acked: bool,
+    pub transfer_received: bool,
+}
+
+pub const NEXT_NEUTRON_WITHDRAW_ID: Item<u64> = Item::new("next_neutron_withdraw_id");
+
+// withdraw id -> withdraw sent back to neutron by ibc transfer
+pub const NEUTRON_WITHDRAWS: Map<u64, NeutronWithdrawInfo> = Map::new("neutron_withdraws");
+
+#[cw_serde]
+pub struct EraStepCheckpoint {
+    pub payload: SudoPayload,
+    // seconds, when the step was sent
+    pub started_at: u64,
+    // icq amount before the step and once it lands: the pool balance for era update,
+    // the withdraw ica balance for collect withdraw and the pool delegations otherwise.
+    // none if the icq had no result yet, era_recover can't decide then
+    pub before: Option<Uint128>,
+    pub expected: Option<Uint128>,
+    // set by the first era_recover, the icq results have to be submitted after it
+    pub recover_height: u64,
+    pub reply_id: u64,
+    // (channel, seq) of the step's packet, set once its reply arrives
+    pub packet: Option<(String, u64)>,
+}
+
+// pool -> era step waiting for its ack
+pub const ERA_STEP_CHECKPOINTS: Map<String, EraStepCheckpoint> = Map::new("era_step_checkpoints");
+
+// pool -> untrn held by the contract on behalf of the pool
+pub const FEE_VAULTS: Map<String, Uint128> = Map::new("fee_vaults");
+
+// pool -> untrn paid from the fee vault to the caller of each era_process step
+pub const KEEPER_BOUNTIES: Map<String, Uint128> = Map::new("keeper_bounties");
+
+// untrn neutron refunds to the contract once a packet is acked or times out
+#[cw_serde]
+pub struct PacketFee {
+    pub pool_addr: String,
+    pub refund_on_ack: Uint128,
+    pub refund_on_timeout: Uint128,
+}
+
+// reply id -> packet fee, moved to PACKET_FEES once the packet's sequence is known
+pub const PACKET_FEES_OF_REPLY: Map<u64, PacketFee> = Map::new("packet_fees_of_reply");
+
+// (channel id, sequence) -> packet fee, credited back to the pool's fee vault
+pub const PACKET_FEES: Map<(String, u64), PacketFee> = Map::new("packet_fees");
+
+// what era active does besides recording when it finds a slashed validator
+#[cw_serde]
+#[derive(Default)]
+pub enum SlashingAction {
+    #[default]
+    RecordOnly,
+    // stop delegating to the validator, era_rebalance then moves its stake away
+    ZeroWeight,
+    PausePool,
+}
+
+// pool -> slashing action
+pub const SLASHING_ACTIONS: Map<String, SlashingAction> = Map::new("slashing_actions");
+
+#[cw_serde]
+pub struct SlashRecord {
+    pub era: u64,
+    pub validator: String,
+    // validator tokens per share, scaled by CAL_BASE
+    pub token_rate_before: Uint128,
+    pub token_rate_after: Uint128,
+    // tokens the pool's delegation lost
+    pub shortfall: Uint128,
+}
+
+// (pool,validator) -> validator tokens per share seen by the last era active
+pub const VALIDATOR_TOKEN_RATES: Map<(String, String), Uint128> = Map::new("validator_token_rates");
+
+// (pool,era) -> slashes found by era active
+pub const SLASH_RECORDS: Map<(String, u64), Vec<SlashRecord>> = Map::new("slash_records");
+
+#[cw_serde]
+pub struct AcceptedRate {
+    pub era: u64,
+    // the rate the admin expects era active to reach
+    pub rate: Uint128,
+    // how far the new rate may be from it, in CAL_BASE like rate_change_limit
+    pub max_deviation: Uint128,
+}
+
+// pool -> rate the admin accepted over rate_change_limit for one era
+pub const ACCEPTED_RATE_ERAS: Map<String, AcceptedRate> = Map::new("accepted_rate_eras");
+
+// for rpc query
+#[cw_serde]
+pub struct ValidatorWeight {
+    pub validator: String,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub struct EraStepRecord {
+    pub step: String,
+    pub height: u64,
+    // seconds
+    pub timestamp: u64,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct EraRecord {
+    pub era: u64,
+    pub bond: Uint128,
+    pub unbond: Uint128,
+    pub active: Uint128,
+    pub restake_amount: Uint128,
+    // delegations growth over the era snapshot, before fees
+    pub reward: Uint128,
+    // lsd token minted to the platform fee receiver and the stack fee receiver
+    pub platform_fee: Uint128,
+    pub stack_fee: Uint128,
+    pub rate: Uint128,
+    // every step run in the era, retries included
+    pub steps: Vec<EraStepRecord>,
+}
+
+// (pool, era) -> era record
+pub const ERA_RECORDS: Map<(String, u64), EraRecord> = Map::new("era_records");
+
+pub fn record_era_step(
+    store: &mut dyn Storage,
+    pool_addr: String,
+    era: u64,
+    step: &str,
+    env: &Env,
+) -> StdResult<EraRecord> {
+    let mut era_record = ERA_RECORDS
+        .may_load(store, (pool_addr.clone(), era))?
+        .unwrap_or_default();
+    era_record.era = era;
+    era_record.steps.push(EraStepRecord {
+        step: step.to_string(),
+        height: env.block.height,
+        timestamp: env.block.time.seconds(),
+    });
+    ERA_RECORDS.save(store, (pool_addr, era), &era_record)?;
+
+    Ok(era_record)
+}
+
 // (pool, era) -> rate
 pub const ERA_RATE: Map<(String, u64), Uint128> = Map::new("era_rate");
 
+// referral code -> owner, who claims the code's referral fees
+pub const REFERRAL_CODES: Map<String, Addr> = Map::new("referral_codes");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ReferralPool {
+    // share of the platform fee paid to referrers
+    pub fee_commission: Uint128,
+    pub total_staked: Uint128,
+    // referral fee per token staked through referrals, accumulated
+    pub fee_index: Decimal,
+}
+
+// pool -> referral fee config and totals
+pub const REFERRAL_POOLS: Map<String, ReferralPool> = Map::new("referral_pools");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ReferralInfo {
+    // stake brought by the code that hasn't been unstaked, fees accrue on it
+    pub staked: Uint128,
+    pub lsd_minted: Uint128,
+    // the pool's fee_index when unclaimed_fee was last settled
+    pub fee_index: Decimal,
+    pub unclaimed_fee: Uint128,
+    pub claimed_fee: Uint128,
+}
+
+// (pool, referral code) -> referral totals
+pub const REFERRALS: Map<(String, String), ReferralInfo> = Map::new("referrals");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ReferredStake {
+    pub staked: Uint128,
+    // lsd token minted for staked that the staker hasn't burnt yet
+    pub lsd_token_amount: Uint128,
+}
+
+// (pool, staker, referral code) -> the staker's part of the code's stake
+pub const REFERRED_STAKES: Map<(String, Addr, String), ReferredStake> = Map::new("referred_stakes");
+
+// who decides how the pool ica votes on host chain proposals
+#[cw_serde]
+#[derive(Default)]
+pub enum GovVoteMode {
+    #[default]
+    Admin,
+    // lsd holders signal, the tally is relayed as a weighted vote
+    Aggregate,
+}
+
+// pool -> gov vote mode
+pub const GOV_VOTE_MODES: Map<String, GovVoteMode> = Map::new("gov_vote_modes");
+
+#[cw_serde]
+#[derive(Copy, Eq, Hash)]
+pub enum GovVoteOption {
+    Yes,
+    Abstain,
+    No,
+    NoWithVeto,
+}
+
+impl GovVoteOption {
+    pub const ALL: [GovVoteOption; 4] = [
+        GovVoteOption::Yes,
+        GovVoteOption::Abstain,
+        GovVoteOption::No,
+        GovVoteOption::NoWithVeto,
+    ];
+
+    // cosmos.gov.v1beta1.VoteOption
+    pub fn proto_value(&self) -> i32 {
+        match self {
+            GovVoteOption::Yes => 1,
+            GovVoteOption::Abstain => 2,
+            GovVoteOption::No => 3,
+            GovVoteOption::NoWithVeto => 4,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct WeightedGovVoteOption {
+    pub option: GovVoteOption,
+    pub weight: Decimal,
+}
+
+#[cw_serde]
+pub enum GovProposalStatus {
+    Signaling,
+    // the tally is on its way to the host chain
+    Relaying,
+    Relayed,
+}
+
+#[cw_serde]
+pub struct GovProposal {
+    // signals are accepted until then, the tally can be relayed after
+    pub signal_end: u64,
+    pub status: GovProposalStatus,
+    // escrowed lsd token per option
+    pub tally: Vec<(GovVoteOption, Uint128)>,
+}
+
+// (pool, host chain proposal id) -> aggregated vote
+pub const GOV_PROPOSALS: Map<(String, u64), GovProposal> = Map::new("gov_proposals");
+
+#[cw_serde]
+pub struct GovSignal {
+    pub option: GovVoteOption,
+    // lsd token escrowed in this contract until the voter reclaims it
+    pub amount: Uint128,
+}
+
+// (pool, proposal id, voter) -> signal
+pub const GOV_SIGNALS: Map<(String, u64, Addr), GovSignal> = Map::new("gov_signals");
+
+#[cw_serde]
+pub enum WindDownStatus {
+    // admin_unbond_all was acked, waiting for the unbonding to finish
+    Unbonding,
+    // lsd holders burn lsd token for their share of the snapshot
+    Exiting,
+    // all lsd token has exited
+    Closed,
+}
+
+#[cw_serde]
+pub struct WindDown {
+    pub status: WindDownStatus,
+    // everything is back on the pool ica after this time
+    pub unbonded_at: u64,
+    // the snapshot waits for a balance icq result submitted after this height, 0 until requested
+    pub snapshot_height: u64,
+    // the withdraw ica balance is on its way to the pool ica
+    #[serde(default)]
+    pub sweeping: bool,
+    // pool ica balance left to lsd holders after the open unstakes
+    pub host_amount: Uint128,
+    // stake still on neutron, paid out in ibc denom
+    pub neutron_amount: Uint128,
+    pub lsd_supply: Uint128,
+    // burnt lsd token whose payout is in flight
+    pub exiting_lsd: Uint128,
+    pub exited_lsd: Uint128,
+}
+
+// pool -> wind down started by admin_unbond_all
+pub const WIND_DOWNS: Map<String, WindDown> = Map::new("wind_downs");
+
 // denom -> unbonding_seconds
 pub const UNBONDING_SECONDS: Map<String, u64> = Map::new("unbonding_seconds");
 
@@ -392,8 +1159,13 @@ pub const DECIMALS: Map<String, u8> = Map::new("decimals");
 pub const ICA_ID_OF_CREATOR: Map<Addr, Vec<String>> = Map::new("ica_id_of_creator");
 #[cfg(test)]
 mod tests {
-    use super::PoolInfo;
+    use super::{
+        migrate_unstakes_of_user, parse_sudo_payload, PoolInfo, TxPayload, TxType, UnstakeInfo,
+        WithdrawStatus, UNSTAKES_INDEX_FOR_USER, UNSTAKES_OF_INDEX, UNSTAKES_OF_USER,
+    };
     use core::ops::{Div, Sub};
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::{to_json_vec, Addr, Order, StdResult, Uint128};
 
     #[test]
     fn test_update_era_logic() {
@@ -431,4 +1203,83 @@ mod tests {
             assert_eq!(2, era);
         }
     }
+
+    #[test]
+    fn test_parse_legacy_sudo_payload() {
+        let legacy = to_json_vec(&super::LegacySudoPayload {
+            message: "100_neutron1user_cosmos1receiver_3_5".to_string(),
+            pool_addr: "pool".to_string(),
+            port_id: "port".to_string(),
+            tx_type: TxType::UserWithdraw,
+        })
+        .unwrap();
+        let payload = parse_sudo_payload(legacy).unwrap();
+        assert_eq!(
+            payload.message,
+            TxPayload::UserWithdraw {
+                amount: Uint128::new(100),
+                user: Addr::unchecked("neutron1user"),
+                receiver: "cosmos1receiver".to_string(),
+                unstake_index_list: vec![3, 5],
+            }
+        );
+        assert_eq!(payload.pool_addr, "pool");
+
+        let malformed = to_json_vec(&super::LegacySudoPayload {
+            message: "abc_neutron1user".to_string(),
+            pool_addr: "pool".to_string(),
+            port_id: "port".to_string(),
+            tx_type: TxType::AdminTransfer,
+        })
+        .unwrap();
+        assert!(parse_sudo_payload(malformed).is_err());
+
+        let current = to_json_vec(&super::SudoPayload {
+            message: TxPayload::EraUpdate {},
+            pool_addr: "pool".to_string(),
+            port_id: "port".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            parse_sudo_payload(current).unwrap().message.tx_type(),
+            TxType::EraUpdate
+        );
+    }
+
+    #[test]
+    fn test_migrate_unstakes_of_user() {
+        let mut storage = MockStorage::new();
+        let user = Addr::unchecked("user");
+        for (index, era) in [(1, 7), (2, 5), (4, 6)] {
+            let unstake_info = UnstakeInfo {
+                era,
+                pool_addr: "pool".to_string(),
+                unstaker: user.to_string(),
+                amount: Uint128::new(100),
+                status: WithdrawStatus::Default,
+                index,
+                unbond_amount: Uint128::new(105),
+            };
+            UNSTAKES_OF_INDEX
+                .save(&mut storage, ("pool".to_string(), index), &unstake_info)
+                .unwrap();
+        }
+        // 3 is already withdrawn
+        UNSTAKES_INDEX_FOR_USER
+            .save(
+                &mut storage,
+                (user.clone(), "pool".to_string()),
+                &vec![1, 2, 3, 4],
+            )
+            .unwrap();
+
+        migrate_unstakes_of_user(&mut storage).unwrap();
+        let keys = UNSTAKES_OF_USER
+            .prefix((user.clone(), "pool".to_string()))
+            .keys(&storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(keys, vec![(5, 2), (6, 4), (7, 1)]);
+        assert!(!UNSTAKES_INDEX_FOR_USER.has(&storage, (user, "pool".to_string())));
+    }
 }
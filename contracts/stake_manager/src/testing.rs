@@ -0,0 +1,364 @@
+use std::marker::PhantomData;
+
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{Delegation, Validator};
+use cosmos_sdk_proto::prost::Message;
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    coins, to_json_binary, to_json_vec, Addr, Api, Binary, CanonicalAddr, ContractResult,
+    CosmosMsg, DepsMut, OwnedDeps, RecoverPubkeyError, Reply, StdResult, Storage, SubMsg,
+    SubMsgResponse, SubMsgResult, SystemError, SystemResult, VerificationError,
+};
+use neutron_sdk::bindings::msg::{IbcFee, MsgIbcTransferResponse, NeutronMsg};
+use neutron_sdk::bindings::query::NeutronQuery;
+use neutron_sdk::bindings::query::{
+    QueryRegisteredQueryResponse, QueryRegisteredQueryResultResponse,
+};
+use neutron_sdk::bindings::types::{InterchainQueryResult, RegisteredQuery, StorageValue};
+use neutron_sdk::interchain_queries::types::QueryType;
+use neutron_sdk::query::min_ibc_fee::MinIbcFeeResponse;
+use neutron_sdk::sudo::msg::RequestPacket;
+
+use crate::helper::{get_withdraw_ica_id, FEE_DENOM};
+use crate::state::{
+    IcaInfo, PoolInfo, QueryKind, ADDRESS_TO_REPLY_ID, INFO_OF_ICA_ID, POOLS, REPLY_ID_TO_QUERY_ID,
+};
+use crate::tx_callback::prepare_sudo_payload;
+
+pub const POOL_ADDR: &str = "cosmos1pool";
+pub const WITHDRAW_ADDR: &str = "cosmos1withdraw";
+pub const ICA_ID: &str = "pool1";
+pub const POOL_CHANNEL_ID: &str = "channel-10";
+pub const WITHDRAW_CHANNEL_ID: &str = "channel-11";
+pub const ADMIN: &str = "admin";
+pub const ACK_FEE: u128 = 1000;
+pub const TIMEOUT_FEE: u128 = 2000;
+
+// query ids of the pool's kv queries, see register_pool_queries
+pub const POOL_BALANCES_QUERY_ID: u64 = 1;
+pub const WITHDRAW_BALANCES_QUERY_ID: u64 = 2;
+pub const DELEGATIONS_QUERY_ID: u64 = 3;
+pub const VALIDATORS_QUERY_ID: u64 = 4;
+
+pub type NeutronDeps = OwnedDeps<MockStorage, MockApi, MockQuerier<NeutronQuery>, NeutronQuery>;
+
+// (query id, local height the result was submitted at, kv results)
+pub type IcqResult = (u64, u64, Vec<StorageValue>);
+
+// answers the min ibc fee query, other neutron queries fail
+pub fn mock_neutron_dependencies() -> NeutronDeps {
+    mock_neutron_dependencies_with_api(MockApi::default())
+}
+
+pub fn mock_neutron_dependencies_with_api<A: Api>(
+    api: A,
+) -> OwnedDeps<MockStorage, A, MockQuerier<NeutronQuery>, NeutronQuery> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api,
+        querier: mock_neutron_querier(vec![]),
+        custom_query_type: PhantomData,
+    }
+}
+
+// also answers the registered kv queries with the given results
+pub fn mock_neutron_querier(icq_results: Vec<IcqResult>) -> MockQuerier<NeutronQuery> {
+    MockQuerier::<NeutronQuery>::new(&[]).with_custom_handler(move |query| {
+        let icq_result = |query_id: &u64| icq_results.iter().find(|(id, _, _)| id == query_id);
+        let resp = match query {
+            NeutronQuery::MinIbcFee {} => to_json_binary(&MinIbcFeeResponse {
+                min_fee: mock_ibc_fee(),
+            }),
+            NeutronQuery::RegisteredInterchainQuery { query_id }
+                if icq_result(query_id).is_some() =>
+            {
+                let (_, height, _) = icq_result(query_id).unwrap();
+                to_json_binary(&QueryRegisteredQueryResponse {
+                    registered_query: RegisteredQuery {
+                        id: *query_id,
+                        owner: "contract".to_string(),
+                        keys: vec![],
+                        query_type: QueryType::KV,
+                        transactions_filter: "".to_string(),
+                        connection_id: "connection-0".to_string(),
+                        update_period: 1,
+                        last_submitted_result_local_height: *height,
+                        last_submitted_result_remote_height: Default::default(),
+                        deposit: vec![],
+                        submit_timeout: 0,
+                        registered_at_height: 0,
+                    },
+                })
+            }
+            NeutronQuery::InterchainQueryResult { query_id } if icq_result(query_id).is_some() => {
+                let (_, height, kv_results) = icq_result(query_id).unwrap();
+                to_json_binary(&QueryRegisteredQueryResultResponse {
+                    result: InterchainQueryResult {
+                        kv_results: kv_results.clone(),
+                        height: *height,
+                        revision: 0,
+                    },
+                })
+            }
+            _ => {
+                return SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "neutron query".to_string(),
+                })
+            }
+        };
+        SystemResult::Ok(ContractResult::Ok(resp.unwrap()))
+    })
+}
+
+// the pool and withdraw ica balances and the pool delegations and validators
+pub fn register_pool_queries(store: &mut dyn Storage) {
+    for (addr, kind, query_id) in [
+        (POOL_ADDR, QueryKind::Balances, POOL_BALANCES_QUERY_ID),
+        (
+            WITHDRAW_ADDR,
+            QueryKind::Balances,
+            WITHDRAW_BALANCES_QUERY_ID,
+        ),
+        (POOL_ADDR, QueryKind::Delegations, DELEGATIONS_QUERY_ID),
+        (POOL_ADDR, QueryKind::Validators, VALIDATORS_QUERY_ID),
+    ] {
+        ADDRESS_TO_REPLY_ID
+            .save(store, (addr.to_string(), kind.to_string()), &query_id)
+            .unwrap();
+        REPLY_ID_TO_QUERY_ID
+            .save(store, query_id, &query_id)
+            .unwrap();
+    }
+}
+
+fn storage_value(value: Vec<u8>) -> StorageValue {
+    StorageValue {
+        storage_prefix: "".to_string(),
+        key: Binary::default(),
+        value: Binary::from(value),
+    }
+}
+
+// sdk decs are integers with 18 decimals
+fn sdk_dec(amount: u128) -> String {
+    format!("{}{}", amount, "0".repeat(18))
+}
+
+// a v045 balances result
+pub fn balances_kv(balances: &[(&str, u128)]) -> Vec<StorageValue> {
+    balances
+        .iter()
+        .map(|(denom, amount)| {
+            storage_value(
+                ProtoCoin {
+                    denom: denom.to_string(),
+                    amount: amount.to_string(),
+                }
+                .encode_to_vec(),
+            )
+        })
+        .collect()
+}
+
+fn validator(operator_address: &str, tokens: u128, shares: u128) -> Validator {
+    Validator {
+        operator_address: operator_address.to_string(),
+        tokens: tokens.to_string(),
+        delegator_shares: sdk_dec(shares),
+        ..Validator::default()
+    }
+}
+
+// a v045 delegations result, the pool holds all (validator, tokens, shares) of each validator
+pub fn delegations_kv(denom: &str, validators: &[(&str, u128, u128)]) -> Vec<StorageValue> {
+    let mut kv_results = vec![storage_value(to_json_vec(denom).unwrap())];
+    for (validator_address, tokens, shares) in validators {
+        kv_results.push(storage_value(
+            Delegation {
+                delegator_address: POOL_ADDR.to_string(),
+                validator_address: validator_address.to_string(),
+                shares: sdk_dec(*shares),
+            }
+            .encode_to_vec(),
+        ));
+        kv_results.push(storage_value(
+            validator(validator_address, *tokens, *shares).encode_to_vec(),
+        ));
+    }
+    kv_results
+}
+
+// a validators result from (validator, tokens, shares)
+pub fn validators_kv(validators: &[(&str, u128, u128)]) -> Vec<StorageValue> {
+    validators
+        .iter()
+        .map(|(operator_address, tokens, shares)| {
+            storage_value(validator(operator_address, *tokens, *shares).encode_to_vec())
+        })
+        .collect()
+}
+
+// MockApi only humanizes its own canonical length, this one shows any canonical address as hex,
+// e.g. the 32 byte ibc hooks intermediate sender
+pub struct HexApi(MockApi);
+
+impl Default for HexApi {
+    fn default() -> Self {
+        HexApi(MockApi::default())
+    }
+}
+
+impl Api for HexApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.0.addr_validate(human)
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        self.0.addr_canonicalize(human)
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        Ok(Addr::unchecked(canonical.to_string()))
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+pub fn mock_ibc_fee() -> IbcFee {
+    IbcFee {
+        recv_fee: vec![],
+        ack_fee: coins(ACK_FEE, FEE_DENOM),
+        timeout_fee: coins(TIMEOUT_FEE, FEE_DENOM),
+    }
+}
+
+pub fn ctrl_port_id(ica_id: &str) -> String {
+    format!("icacontroller-contract.{}", ica_id)
+}
+
+// a registered pool with both icas open
+pub fn save_pool(store: &mut dyn Storage, pool_info: PoolInfo) -> PoolInfo {
+    let pool_info = PoolInfo {
+        ica_id: ICA_ID.to_string(),
+        admin: Addr::unchecked(ADMIN),
+        ..pool_info
+    };
+    POOLS
+        .save(store, POOL_ADDR.to_string(), &pool_info)
+        .unwrap();
+
+    let pool_ica_info = IcaInfo {
+        ctrl_connection_id: "connection-0".to_string(),
+        ctrl_port_id: ctrl_port_id(ICA_ID),
+        ctrl_channel_id: POOL_CHANNEL_ID.to_string(),
+        ica_addr: POOL_ADDR.to_string(),
+        ..IcaInfo::default()
+    };
+    let withdraw_ica_info = IcaInfo {
+        ctrl_connection_id: "connection-0".to_string(),
+        ctrl_port_id: ctrl_port_id(&get_withdraw_ica_id(ICA_ID.to_string())),
+        ctrl_channel_id: WITHDRAW_CHANNEL_ID.to_string(),
+        ica_addr: WITHDRAW_ADDR.to_string(),
+        ..IcaInfo::default()
+    };
+    INFO_OF_ICA_ID
+        .save(
+            store,
+            ICA_ID.to_string(),
+            &(pool_ica_info, withdraw_ica_info, Addr::unchecked(ADMIN)),
+        )
+        .unwrap();
+
+    pool_info
+}
+
+// neutron replies with the packet a submsg went out as, its ack or timeout then comes for that packet
+pub fn relay_packet(
+    deps: DepsMut,
+    submsg: &SubMsg<NeutronMsg>,
+    channel_id: &str,
+    seq_id: u64,
+) -> RequestPacket {
+    prepare_sudo_payload(
+        deps,
+        mock_env(),
+        Reply {
+            id: submsg.id,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(
+                    to_json_binary(&MsgIbcTransferResponse {
+                        sequence_id: seq_id,
+                        channel: channel_id.to_string(),
+                    })
+                    .unwrap(),
+                ),
+            }),
+        },
+    )
+    .unwrap();
+
+    RequestPacket {
+        sequence: Some(seq_id),
+        source_port: None,
+        source_channel: Some(channel_id.to_string()),
+        destination_port: None,
+        destination_channel: None,
+        data: None,
+        timeout_height: None,
+        timeout_timestamp: None,
+    }
+}
+
+// the bank sends in the ica tx of a submsg
+pub fn submitted_msg_sends(submsg: &SubMsg<NeutronMsg>) -> Vec<MsgSend> {
+    let CosmosMsg::Custom(NeutronMsg::SubmitTx { msgs, .. }) = &submsg.msg else {
+        panic!("not an ica tx: {:?}", submsg.msg);
+    };
+    msgs.iter()
+        .filter(|msg| msg.type_url == "/cosmos.bank.v1beta1.MsgSend")
+        .map(|msg| MsgSend::decode(msg.value.as_slice()).unwrap())
+        .collect()
+}
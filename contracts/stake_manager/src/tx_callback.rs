@@ -4,7 +4,17 @@ use crate::execute_admin_transfer_funds::{
 use crate::execute_admin_unbond_all::{
     sudo_admin_unbond_all_callback, sudo_admin_unbond_all_failed_callback,
 };
+use crate::execute_era_rebalance::{
+    sudo_era_rebalance_callback, sudo_era_rebalance_failed_callback,
+};
+use crate::execute_era_recover::save_era_step_packet;
 use crate::execute_era_restake::sudo_era_rebond_failed_callback;
+use crate::execute_fee_vault::{credit_fee_refund, packet_fee};
+use crate::execute_gov_vote::{sudo_gov_vote_callback, sudo_gov_vote_failed_callback};
+use crate::execute_instant_unstake::{
+    sudo_instant_unstake_callback, sudo_instant_unstake_failed_callback,
+};
+use crate::execute_open_channel::record_closed_channel;
 use crate::execute_pool_update_validator::{
     sudo_update_validator_callback, sudo_update_validator_failed_callback,
 };
@@ -12,11 +22,19 @@ use crate::execute_redeem_token_for_share::{
     sudo_redeem_token_for_share_callback, sudo_redeem_token_for_share_failed_callback,
 };
 use crate::execute_stake_lsm::{sudo_stake_lsm_callback, sudo_stake_lsm_failed_callback};
+use crate::execute_wind_down::{sudo_wind_down_exit_callback, sudo_wind_down_exit_failed_callback};
+use crate::execute_wind_down::{
+    sudo_wind_down_sweep_callback, sudo_wind_down_sweep_failed_callback,
+};
 use crate::execute_withdraw::{sudo_withdraw_callback, sudo_withdraw_failed_callback};
+use crate::execute_withdraw_to_neutron::{
+    sudo_withdraw_to_neutron_callback, sudo_withdraw_to_neutron_failed_callback,
+};
 use crate::helper::sudo_set_withdraw_addr_failed_callback;
 use crate::state::{
     read_reply_payload, read_sudo_payload, save_reply_payload, save_sudo_payload, SudoPayload,
-    TxType, SUDO_PAYLOAD,
+    TxType, CLOSED_CHANNELS, INFO_OF_ICA_ID, PACKET_FEES, PACKET_FEES_OF_REPLY, POOLS,
+    SUDO_PAYLOAD,
 };
 use crate::{error_conversion::ContractError, execute_era_restake::sudo_era_rebond_callback};
 use crate::{
@@ -33,27 +51,71 @@ use crate::{
     helper::sudo_set_withdraw_addr_callback,
 };
 use cosmwasm_std::{
-    from_json, Binary, CosmosMsg, DepsMut, Env, Reply, Response, StdError, StdResult, SubMsg,
+    from_json, Binary, Deps, DepsMut, Env, Reply, Response, StdError, StdResult, SubMsg,
 };
 use neutron_sdk::sudo::msg::RequestPacket;
 use neutron_sdk::{
     bindings::{
-        msg::{MsgIbcTransferResponse, NeutronMsg},
+        msg::{IbcFee, MsgIbcTransferResponse, NeutronMsg},
         query::NeutronQuery,
     },
     NeutronResult,
 };
 
 // saves payload to process later to the storage and returns a SubmitTX Cosmos SubMsg with necessary reply id
-pub fn msg_with_sudo_callback<C: Into<CosmosMsg<T>>, T>(
+pub fn msg_with_sudo_callback(
     deps: DepsMut<NeutronQuery>,
-    msg: C,
+    msg: NeutronMsg,
     payload: SudoPayload,
-) -> StdResult<SubMsg<T>> {
+) -> StdResult<SubMsg<NeutronMsg>> {
+    // a closed ica channel can't take packets until it is reopened
+    if let Some(channel_id) = msg_channel_id(deps.as_ref(), payload.pool_addr.clone(), &msg)? {
+        if CLOSED_CHANNELS.has(deps.storage, (payload.pool_addr.clone(), channel_id)) {
+            return Err(ContractError::PoolChannelClosed {}.into());
+        }
+    }
     let id = save_reply_payload(deps.storage, payload)?;
     Ok(SubMsg::reply_on_success(msg, id))
 }
 
+// same as msg_with_sudo_callback, the fee neutron refunds after the ack or timeout goes to the pool's fee vault
+pub fn msg_with_fee_refund(
+    mut deps: DepsMut<NeutronQuery>,
+    msg: NeutronMsg,
+    payload: SudoPayload,
+    ibc_fee: &IbcFee,
+) -> StdResult<SubMsg<NeutronMsg>> {
+    let packet_fee = packet_fee(payload.pool_addr.clone(), ibc_fee);
+    let submsg = msg_with_sudo_callback(deps.branch(), msg, payload)?;
+    PACKET_FEES_OF_REPLY.save(deps.storage, submsg.id, &packet_fee)?;
+    Ok(submsg)
+}
+
+// the channel msg goes out on: the ctrl channel of the pool or withdraw ica for ica txs
+fn msg_channel_id(
+    deps: Deps<NeutronQuery>,
+    pool_addr: String,
+    msg: &NeutronMsg,
+) -> StdResult<Option<String>> {
+    match msg {
+        NeutronMsg::SubmitTx {
+            interchain_account_id,
+            ..
+        } => {
+            let pool_info = POOLS.load(deps.storage, pool_addr)?;
+            let (pool_ica_info, withdraw_ica_info, _) =
+                INFO_OF_ICA_ID.load(deps.storage, pool_info.ica_id.clone())?;
+            if *interchain_account_id == pool_info.ica_id {
+                Ok(Some(pool_ica_info.ctrl_channel_id))
+            } else {
+                Ok(Some(withdraw_ica_info.ctrl_channel_id))
+            }
+        }
+        NeutronMsg::IbcTransfer { source_channel, .. } => Ok(Some(source_channel.clone())),
+        _ => Ok(None),
+    }
+}
+
 // prepare_sudo_payload is called from reply handler
 // The method is used to extract sequence id and channel from SubmitTxResponse to process sudo payload defined in msg_with_sudo_callback later in Sudo handler.
 // Such flow msg_with_sudo_callback() -> reply() -> prepare_sudo_payload() -> sudo() allows you "attach" some payload to your Transfer message
@@ -72,6 +134,11 @@ pub fn prepare_sudo_payload(mut deps: DepsMut, _env: Env, msg: Reply) -> StdResu
 
     let seq_id = resp.sequence_id;
     let channel_id = resp.channel;
+    if let Some(packet_fee) = PACKET_FEES_OF_REPLY.may_load(deps.storage, msg.id)? {
+        PACKET_FEES_OF_REPLY.remove(deps.storage, msg.id);
+        PACKET_FEES.save(deps.storage, (channel_id.clone(), seq_id), &packet_fee)?;
+    }
+    save_era_step_packet(deps.storage, msg.id, &payload, channel_id.clone(), seq_id)?;
     save_sudo_payload(deps.branch().storage, channel_id, seq_id, payload)?;
     Ok(Response::new())
 }
@@ -88,6 +155,7 @@ pub fn sudo_response(
     let channel_id = req
         .source_channel
         .ok_or_else(|| ContractError::CallBackErrChannelIDNotFound {})?;
+    credit_fee_refund(deps.storage, channel_id.clone(), seq_id, false)?;
 
     if let Ok(payload) = read_sudo_payload(deps.storage, channel_id.clone(), seq_id) {
         SUDO_PAYLOAD.remove(deps.storage, (channel_id, seq_id));
@@ -104,6 +172,7 @@ pub fn sudo_error(deps: DepsMut, req: RequestPacket) -> NeutronResult<Response<N
     let channel_id = req
         .source_channel
         .ok_or_else(|| ContractError::CallBackErrChannelIDNotFound {})?;
+    credit_fee_refund(deps.storage, channel_id.clone(), seq_id, false)?;
 
     if let Ok(payload) = read_sudo_payload(deps.storage, channel_id.clone(), seq_id) {
         SUDO_PAYLOAD.remove(deps.storage, (channel_id, seq_id));
@@ -120,41 +189,56 @@ pub fn sudo_timeout(deps: DepsMut, req: RequestPacket) -> NeutronResult<Response
     let channel_id = req
         .source_channel
         .ok_or_else(|| ContractError::CallBackErrChannelIDNotFound {})?;
+    credit_fee_refund(deps.storage, channel_id.clone(), seq_id, true)?;
+    // the channel closes even when the packet has no payload left
+    let closed_event = match req.source_port {
+        Some(port_id) => record_closed_channel(deps.storage, port_id, channel_id.clone())?,
+        None => None,
+    };
 
     if let Ok(payload) = read_sudo_payload(deps.storage, channel_id.clone(), seq_id) {
         SUDO_PAYLOAD.remove(deps.storage, (channel_id, seq_id));
-        return sudo_failed_callback(deps, payload);
+        return Ok(sudo_failed_callback(deps, payload)?.add_events(closed_event));
+    }
+    if let Some(closed_event) = closed_event {
+        return Ok(Response::new().add_event(closed_event));
     }
 
     Err(ContractError::CallBackErrErrorMsg {}.into())
 }
 
-fn sudo_callback(
+pub fn sudo_callback(
     deps: DepsMut,
     env: Env,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    match payload.tx_type {
+    match payload.message.tx_type() {
         TxType::SetWithdrawAddr => sudo_set_withdraw_addr_callback(deps, payload),
         TxType::EraUpdate => sudo_era_update_callback(deps, env, payload),
         TxType::EraBond => sudo_era_bond_callback(deps, env, payload),
         TxType::EraCollectWithdraw => sudo_era_collect_withdraw_callback(deps, env, payload),
         TxType::EraRebond => sudo_era_rebond_callback(deps, env, payload),
         TxType::UserWithdraw => sudo_withdraw_callback(deps, payload),
-        TxType::UpdateValidator => sudo_update_validator_callback(deps, payload),
-        TxType::RmValidator => sudo_rm_validator_callback(deps, payload),
+        TxType::UpdateValidator => sudo_update_validator_callback(deps, env, payload),
+        TxType::RmValidator => sudo_rm_validator_callback(deps, env, payload),
         TxType::StakeLsm => sudo_stake_lsm_callback(deps, payload),
         TxType::RedeemTokenForShare => sudo_redeem_token_for_share_callback(deps, payload),
-        TxType::AdminUnbondAll => sudo_admin_unbond_all_callback(payload),
+        TxType::AdminUnbondAll => sudo_admin_unbond_all_callback(deps, env, payload),
         TxType::AdminTransfer => sudo_admin_transfer_callback(payload),
+        TxType::EraRebalance => sudo_era_rebalance_callback(deps, env, payload),
+        TxType::InstantUnstake => sudo_instant_unstake_callback(deps, payload),
+        TxType::UserWithdrawToNeutron => sudo_withdraw_to_neutron_callback(deps, payload),
+        TxType::GovVote => sudo_gov_vote_callback(deps, payload),
+        TxType::WindDownExit => sudo_wind_down_exit_callback(deps, payload),
+        TxType::WindDownSweep => sudo_wind_down_sweep_callback(deps, env, payload),
     }
 }
 
-fn sudo_failed_callback(
+pub fn sudo_failed_callback(
     deps: DepsMut,
     payload: SudoPayload,
 ) -> NeutronResult<Response<NeutronMsg>> {
-    match payload.tx_type {
+    match payload.message.tx_type() {
         TxType::SetWithdrawAddr => sudo_set_withdraw_addr_failed_callback(deps, payload),
         TxType::EraUpdate => sudo_era_update_failed_callback(deps, payload),
         TxType::EraBond => sudo_era_bond_failed_callback(deps, payload),
@@ -167,5 +251,11 @@ fn sudo_failed_callback(
         TxType::RedeemTokenForShare => sudo_redeem_token_for_share_failed_callback(deps, payload),
         TxType::AdminUnbondAll => sudo_admin_unbond_all_failed_callback(payload),
         TxType::AdminTransfer => sudo_admin_transfer_failed_callback(payload),
+        TxType::EraRebalance => sudo_era_rebalance_failed_callback(deps, payload),
+        TxType::InstantUnstake => sudo_instant_unstake_failed_callback(deps, payload),
+        TxType::UserWithdrawToNeutron => sudo_withdraw_to_neutron_failed_callback(deps, payload),
+        TxType::GovVote => sudo_gov_vote_failed_callback(deps, payload),
+        TxType::WindDownExit => sudo_wind_down_exit_failed_callback(deps, payload),
+        TxType::WindDownSweep => sudo_wind_down_sweep_failed_callback(deps, payload),
     }
 }
@@ -0,0 +1,778 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, BlockInfo, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdError, StdResult, Storage,
+};
+use cw2::{ensure_from_older_version, set_contract_version};
+use cw_storage_plus::Bound;
+use cw_utils::Expiration;
+
+use crate::error::ContractError;
+use crate::msg::{
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Cw721ReceiveMsg,
+    ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, NftInfoResponse, NumTokensResponse,
+    OperatorResponse, OperatorsResponse, OwnerOfResponse, QueryMsg, TokensResponse,
+};
+use crate::state::{
+    Approval, ContractInfo, TicketExtension, TokenInfo, CONTRACT_INFO, MINTER, OPERATORS,
+    OWNER_TOKENS, TOKENS, TOKEN_COUNT,
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:unstake-ticket";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    CONTRACT_INFO.save(
+        deps.storage,
+        &ContractInfo {
+            name: msg.name,
+            symbol: msg.symbol,
+        },
+    )?;
+    MINTER.save(deps.storage, &deps.api.addr_validate(&msg.minter)?)?;
+    TOKEN_COUNT.save(deps.storage, &0)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => execute_transfer_nft(deps, env, info, recipient, token_id),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => execute_send_nft(deps, env, info, contract, token_id, msg),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => execute_approve(deps, env, info, spender, token_id, expires),
+        ExecuteMsg::Revoke { spender, token_id } => {
+            execute_revoke(deps, env, info, spender, token_id)
+        }
+        ExecuteMsg::ApproveAll { operator, expires } => {
+            execute_approve_all(deps, env, info, operator, expires)
+        }
+        ExecuteMsg::RevokeAll { operator } => execute_revoke_all(deps, info, operator),
+        ExecuteMsg::Mint {
+            token_id,
+            owner,
+            token_uri,
+            extension,
+        } => execute_mint(deps, info, token_id, owner, token_uri, extension),
+        ExecuteMsg::Burn { token_id } => execute_burn(deps, info, token_id),
+    }
+}
+
+pub fn execute_mint(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    owner: String,
+    token_uri: Option<String>,
+    extension: TicketExtension,
+) -> Result<Response, ContractError> {
+    if info.sender != MINTER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if TOKENS.has(deps.storage, &token_id) {
+        return Err(ContractError::Claimed {});
+    }
+
+    let owner = deps.api.addr_validate(&owner)?;
+    TOKENS.save(
+        deps.storage,
+        &token_id,
+        &TokenInfo {
+            owner: owner.clone(),
+            approvals: vec![],
+            token_uri,
+            extension,
+        },
+    )?;
+    OWNER_TOKENS.save(deps.storage, (&owner, &token_id), &())?;
+    TOKEN_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "mint")
+        .add_attribute("minter", info.sender)
+        .add_attribute("owner", owner)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_burn(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    // the owner can't burn a ticket, it would strand the unstake behind it
+    if info.sender != MINTER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let token = TOKENS.load(deps.storage, &token_id)?;
+    TOKENS.remove(deps.storage, &token_id);
+    OWNER_TOKENS.remove(deps.storage, (&token.owner, &token_id));
+    TOKEN_COUNT.update(deps.storage, |count| -> StdResult<_> {
+        Ok(count.saturating_sub(1))
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "burn")
+        .add_attribute("sender", info.sender)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_transfer_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    transfer_nft(
+        deps.storage,
+        &env.block,
+        &info.sender,
+        &recipient,
+        &token_id,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_send_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    transfer_nft(deps.storage, &env.block, &info.sender, &contract, &token_id)?;
+
+    let receive_msg = Cw721ReceiveMsg {
+        sender: info.sender.to_string(),
+        token_id: token_id.clone(),
+        msg,
+    }
+    .into_cosmos_msg(contract.to_string())?;
+
+    Ok(Response::new()
+        .add_message(receive_msg)
+        .add_attribute("action", "send_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", contract)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let mut token = TOKENS.load(deps.storage, &token_id)?;
+    check_can_approve(deps.storage, &env.block, &token, &info.sender)?;
+
+    token
+        .approvals
+        .retain(|approval| approval.spender != spender);
+    token.approvals.push(Approval {
+        spender: spender.clone(),
+        expires,
+    });
+    TOKENS.save(deps.storage, &token_id, &token)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve")
+        .add_attribute("sender", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+
+    let mut token = TOKENS.load(deps.storage, &token_id)?;
+    check_can_approve(deps.storage, &env.block, &token, &info.sender)?;
+
+    token
+        .approvals
+        .retain(|approval| approval.spender != spender);
+    TOKENS.save(deps.storage, &token_id, &token)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke")
+        .add_attribute("sender", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_approve_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    OPERATORS.save(deps.storage, (&info.sender, &operator), &expires)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("operator", operator))
+}
+
+pub fn execute_revoke_all(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    OPERATORS.remove(deps.storage, (&info.sender, &operator));
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("operator", operator))
+}
+
+fn transfer_nft(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    sender: &Addr,
+    recipient: &Addr,
+    token_id: &str,
+) -> Result<(), ContractError> {
+    let mut token = TOKENS.load(storage, token_id)?;
+    check_can_send(storage, block, &token, sender)?;
+
+    OWNER_TOKENS.remove(storage, (&token.owner, token_id));
+    OWNER_TOKENS.save(storage, (recipient, token_id), &())?;
+
+    // approvals are given by the owner, they don't carry over
+    token.owner = recipient.clone();
+    token.approvals = vec![];
+    TOKENS.save(storage, token_id, &token)?;
+
+    Ok(())
+}
+
+fn is_operator(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+    operator: &Addr,
+) -> StdResult<bool> {
+    Ok(OPERATORS
+        .may_load(storage, (owner, operator))?
+        .map(|expires| !expires.is_expired(block))
+        .unwrap_or_default())
+}
+
+// the owner and its operators can approve
+fn check_can_approve(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    token: &TokenInfo,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if token.owner == *sender || is_operator(storage, block, &token.owner, sender)? {
+        return Ok(());
+    }
+
+    Err(ContractError::Unauthorized {})
+}
+
+// the owner, its operators and the token's spenders can send
+fn check_can_send(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    token: &TokenInfo,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if check_can_approve(storage, block, token, sender).is_ok() {
+        return Ok(());
+    }
+    if token
+        .approvals
+        .iter()
+        .any(|approval| approval.spender == *sender && !approval.is_expired(block))
+    {
+        return Ok(());
+    }
+
+    Err(ContractError::Unauthorized {})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => to_json_binary(&query_owner_of(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or_default(),
+        )?),
+        QueryMsg::Approval {
+            token_id,
+            spender,
+            include_expired,
+        } => to_json_binary(&query_approval(
+            deps,
+            env,
+            token_id,
+            spender,
+            include_expired.unwrap_or_default(),
+        )?),
+        QueryMsg::Approvals {
+            token_id,
+            include_expired,
+        } => to_json_binary(&ApprovalsResponse {
+            approvals: query_owner_of(deps, env, token_id, include_expired.unwrap_or_default())?
+                .approvals,
+        }),
+        QueryMsg::Operator {
+            owner,
+            operator,
+            include_expired,
+        } => to_json_binary(&query_operator(
+            deps,
+            env,
+            owner,
+            operator,
+            include_expired.unwrap_or_default(),
+        )?),
+        QueryMsg::AllOperators {
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        } => to_json_binary(&query_all_operators(
+            deps,
+            env,
+            owner,
+            include_expired.unwrap_or_default(),
+            start_after,
+            limit,
+        )?),
+        QueryMsg::NumTokens {} => to_json_binary(&NumTokensResponse {
+            count: TOKEN_COUNT.load(deps.storage)?,
+        }),
+        QueryMsg::ContractInfo {} => {
+            let info = CONTRACT_INFO.load(deps.storage)?;
+            to_json_binary(&ContractInfoResponse {
+                name: info.name,
+                symbol: info.symbol,
+            })
+        }
+        QueryMsg::NftInfo { token_id } => to_json_binary(&query_nft_info(deps, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_json_binary(&AllNftInfoResponse {
+            access: query_owner_of(
+                deps,
+                env,
+                token_id.clone(),
+                include_expired.unwrap_or_default(),
+            )?,
+            info: query_nft_info(deps, token_id)?,
+        }),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_tokens(deps, owner, start_after, limit)?),
+        QueryMsg::AllTokens { start_after, limit } => {
+            to_json_binary(&query_all_tokens(deps, start_after, limit)?)
+        }
+        QueryMsg::Minter {} => to_json_binary(&MinterResponse {
+            minter: Some(MINTER.load(deps.storage)?.to_string()),
+        }),
+    }
+}
+
+fn unexpired_approvals(
+    approvals: Vec<Approval>,
+    block: &BlockInfo,
+    include_expired: bool,
+) -> Vec<Approval> {
+    approvals
+        .into_iter()
+        .filter(|approval| include_expired || !approval.is_expired(block))
+        .collect()
+}
+
+pub fn query_owner_of(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<OwnerOfResponse> {
+    let token = TOKENS.load(deps.storage, &token_id)?;
+    Ok(OwnerOfResponse {
+        owner: token.owner.to_string(),
+        approvals: unexpired_approvals(token.approvals, &env.block, include_expired),
+    })
+}
+
+pub fn query_approval(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    spender: String,
+    include_expired: bool,
+) -> StdResult<ApprovalResponse> {
+    let token = TOKENS.load(deps.storage, &token_id)?;
+    unexpired_approvals(token.approvals, &env.block, include_expired)
+        .into_iter()
+        .find(|approval| approval.spender == spender)
+        .map(|approval| ApprovalResponse { approval })
+        .ok_or_else(|| StdError::not_found("approval"))
+}
+
+pub fn query_operator(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    operator: String,
+    include_expired: bool,
+) -> StdResult<OperatorResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let operator = deps.api.addr_validate(&operator)?;
+    OPERATORS
+        .may_load(deps.storage, (&owner, &operator))?
+        .filter(|expires| include_expired || !expires.is_expired(&env.block))
+        .map(|expires| OperatorResponse {
+            approval: Approval {
+                spender: operator.clone(),
+                expires,
+            },
+        })
+        .ok_or_else(|| StdError::not_found("operator"))
+}
+
+pub fn query_all_operators(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    include_expired: bool,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let owner = deps.api.addr_validate(&owner)?;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let operators = OPERATORS
+        .prefix(&owner)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .filter(|item| {
+            item.as_ref().map_or(true, |(_, expires)| {
+                include_expired || !expires.is_expired(&env.block)
+            })
+        })
+        .take(limit)
+        .map(|item| item.map(|(spender, expires)| Approval { spender, expires }))
+        .collect::<StdResult<_>>()?;
+
+    Ok(OperatorsResponse { operators })
+}
+
+pub fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
+    let token = TOKENS.load(deps.storage, &token_id)?;
+    Ok(NftInfoResponse {
+        token_uri: token.token_uri,
+        extension: token.extension,
+    })
+}
+
+pub fn query_tokens(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let owner = deps.api.addr_validate(&owner)?;
+
+    let tokens = OWNER_TOKENS
+        .prefix(&owner)
+        .keys(
+            deps.storage,
+            start_after.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+pub fn query_all_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let tokens = TOKENS
+        .keys(
+            deps.storage,
+            start_after.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let original_version =
+        ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attribute("original_version", original_version.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_json, Uint128};
+
+    use super::*;
+
+    const MINTER_ADDR: &str = "stake_manager";
+
+    fn setup(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            name: "Unstake Ticket".to_string(),
+            symbol: "UT".to_string(),
+            minter: MINTER_ADDR.to_string(),
+        };
+        instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn mint(deps: DepsMut, token_id: &str, owner: &str) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: TicketExtension {
+                pool_addr: "pool".to_string(),
+                era: 3,
+                amount: Uint128::new(1000),
+            },
+        };
+        execute(deps, mock_env(), mock_info(MINTER_ADDR, &[]), msg)
+    }
+
+    fn owner_of(deps: Deps, token_id: &str) -> String {
+        let msg = QueryMsg::OwnerOf {
+            token_id: token_id.to_string(),
+            include_expired: None,
+        };
+        from_json::<OwnerOfResponse>(query(deps, mock_env(), msg).unwrap())
+            .unwrap()
+            .owner
+    }
+
+    #[test]
+    fn only_minter_can_mint() {
+        let mut deps = mock_dependencies();
+        setup(deps.as_mut());
+
+        let msg = ExecuteMsg::Mint {
+            token_id: "1".to_string(),
+            owner: "alice".to_string(),
+            token_uri: None,
+            extension: TicketExtension {
+                pool_addr: "pool".to_string(),
+                era: 3,
+                amount: Uint128::new(1000),
+            },
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        mint(deps.as_mut(), "1", "alice").unwrap();
+        assert_eq!(owner_of(deps.as_ref(), "1"), "alice");
+        let err = mint(deps.as_mut(), "1", "bob").unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+
+        let info: NftInfoResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::NftInfo {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(info.extension.era, 3);
+        assert_eq!(info.extension.amount, Uint128::new(1000));
+    }
+
+    #[test]
+    fn transfer_with_approval() {
+        let mut deps = mock_dependencies();
+        setup(deps.as_mut());
+        mint(deps.as_mut(), "1", "alice").unwrap();
+
+        let transfer = ExecuteMsg::TransferNft {
+            recipient: "carol".to_string(),
+            token_id: "1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            transfer.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let approve = ExecuteMsg::Approve {
+            spender: "bob".to_string(),
+            token_id: "1".to_string(),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), approve).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer).unwrap();
+        assert_eq!(owner_of(deps.as_ref(), "1"), "carol");
+
+        // the approval is gone with the old owner
+        let transfer = ExecuteMsg::TransferNft {
+            recipient: "bob".to_string(),
+            token_id: "1".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let tokens = query_tokens(deps.as_ref(), "carol".to_string(), None, None).unwrap();
+        assert_eq!(tokens.tokens, vec!["1".to_string()]);
+        let tokens = query_tokens(deps.as_ref(), "alice".to_string(), None, None).unwrap();
+        assert!(tokens.tokens.is_empty());
+    }
+
+    #[test]
+    fn operator_can_send() {
+        let mut deps = mock_dependencies();
+        setup(deps.as_mut());
+        mint(deps.as_mut(), "1", "alice").unwrap();
+
+        let approve_all = ExecuteMsg::ApproveAll {
+            operator: "bob".to_string(),
+            expires: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            approve_all,
+        )
+        .unwrap();
+
+        let send = ExecuteMsg::SendNft {
+            contract: "market".to_string(),
+            token_id: "1".to_string(),
+            msg: Binary::default(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), send).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(owner_of(deps.as_ref(), "1"), "market");
+    }
+
+    #[test]
+    fn only_minter_can_burn() {
+        let mut deps = mock_dependencies();
+        setup(deps.as_mut());
+        mint(deps.as_mut(), "1", "alice").unwrap();
+        mint(deps.as_mut(), "2", "alice").unwrap();
+
+        let burn = ExecuteMsg::Burn {
+            token_id: "1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            burn.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute(deps.as_mut(), mock_env(), mock_info(MINTER_ADDR, &[]), burn).unwrap();
+        let count: NumTokensResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap()).unwrap();
+        assert_eq!(count.count, 1);
+        let tokens = query_all_tokens(deps.as_ref(), None, None).unwrap();
+        assert_eq!(tokens.tokens, vec!["2".to_string()]);
+    }
+}
@@ -0,0 +1,15 @@
+/*!
+Unstake tickets of the stake manager, as cw721 NFTs. It implements the
+[CW721 spec](https://github.com/CosmWasm/cw-nfts/blob/main/packages/cw721/README.md)
+with each token carrying the pool, era and amount of one unstake record.
+
+The stake manager is the minter. It mints a ticket when a user unstakes, pays the
+withdrawal to whoever owns the ticket and burns it when the withdrawal is acknowledged.
+*/
+
+pub mod contract;
+mod error;
+pub mod msg;
+pub mod state;
+
+pub use crate::error::ContractError;
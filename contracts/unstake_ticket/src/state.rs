@@ -0,0 +1,47 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Uint128};
+use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub struct ContractInfo {
+    pub name: String,
+    pub symbol: String,
+}
+
+/// the unstake record a ticket stands for
+#[cw_serde]
+pub struct TicketExtension {
+    pub pool_addr: String,
+    pub era: u64,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Approval {
+    pub spender: Addr,
+    pub expires: Expiration,
+}
+
+impl Approval {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires.is_expired(block)
+    }
+}
+
+#[cw_serde]
+pub struct TokenInfo {
+    pub owner: Addr,
+    pub approvals: Vec<Approval>,
+    pub token_uri: Option<String>,
+    pub extension: TicketExtension,
+}
+
+pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
+pub const MINTER: Item<Addr> = Item::new("minter");
+pub const TOKEN_COUNT: Item<u64> = Item::new("token_count");
+pub const TOKENS: Map<&str, TokenInfo> = Map::new("tokens");
+// (owner, token_id) => (), lists the tokens of an owner
+pub const OWNER_TOKENS: Map<(&Addr, &str), ()> = Map::new("owner_tokens");
+// (owner, operator) => expiration
+pub const OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new("operators");
@@ -0,0 +1,9 @@
+; ModuleID = 'autocfg_0eeec8965127486f_0.b5ab7dce8bda6bc7-cgu.0'
+source_filename = "autocfg_0eeec8965127486f_0.b5ab7dce8bda6bc7-cgu.0"
+target datalayout = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128"
+target triple = "x86_64-unknown-linux-gnu"
+
+!llvm.module.flags = !{!0, !1}
+
+!0 = !{i32 8, !"PIC Level", i32 2}
+!1 = !{i32 2, !"RtLibUseGOT", i32 1}